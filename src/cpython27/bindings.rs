@@ -10,6 +10,7 @@
 #![allow(clippy::default_trait_access)]
 #![allow(clippy::cast_lossless)]
 #![allow(clippy::trivially_copy_pass_by_ref)]
+#![allow(clippy::upper_case_acronyms)]
 
 /* automatically generated by rust-bindgen */
 
@@ -737,3 +738,329 @@ pub type PyFrameObject = _frame;
 pub struct PyMemberDef {
     pub _address: u8,
 }
+
+// The following are not part of the public CPython headers; they are copied
+// from the private definitions in the CPython 2.7 sources.
+
+// Objects/descrobject.c
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct propertyobject {
+    pub ob_refcnt: Py_ssize_t,
+    pub ob_type: *mut _typeobject,
+    pub prop_get: *mut PyObject,
+    pub prop_set: *mut PyObject,
+    pub prop_del: *mut PyObject,
+    pub prop_doc: *mut PyObject,
+    pub getter_doc: ::std::os::raw::c_int,
+}
+
+// Include/pyerrors.h
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct PyBaseExceptionObject {
+    pub ob_refcnt: Py_ssize_t,
+    pub ob_type: *mut _typeobject,
+    pub dict: *mut PyObject,
+    pub args: *mut PyObject,
+    pub message: *mut PyObject,
+}
+
+// Include/memoryobject.h
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct PyMemoryViewObject {
+    pub ob_refcnt: Py_ssize_t,
+    pub ob_type: *mut _typeobject,
+    pub base: *mut PyObject,
+    pub view: Py_buffer,
+}
+
+// Objects/bufferobject.c
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct PyBufferObject {
+    pub ob_refcnt: Py_ssize_t,
+    pub ob_type: *mut _typeobject,
+    pub b_base: *mut PyObject,
+    pub b_ptr: *mut ::std::os::raw::c_void,
+    pub b_size: Py_ssize_t,
+    pub b_offset: Py_ssize_t,
+    pub b_readonly: ::std::os::raw::c_int,
+    pub b_hash: ::std::os::raw::c_long,
+}
+
+// Include/setobject.h
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct setentry {
+    pub hash: ::std::os::raw::c_long,
+    pub key: *mut PyObject,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct PySetObject {
+    pub ob_refcnt: Py_ssize_t,
+    pub ob_type: *mut _typeobject,
+    pub fill: Py_ssize_t,
+    pub used: Py_ssize_t,
+    pub mask: Py_ssize_t,
+    pub table: *mut setentry,
+    pub lookup: *mut ::std::os::raw::c_void,
+    pub smalltable: [setentry; 8usize],
+    pub hash: ::std::os::raw::c_long,
+    pub weakreflist: *mut PyObject,
+}
+
+// Objects/moduleobject.c
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct PyModuleObject {
+    pub ob_refcnt: Py_ssize_t,
+    pub ob_type: *mut _typeobject,
+    pub md_dict: *mut PyObject,
+}
+
+// Objects/funcobject.c: `classmethod` and `staticmethod` share this layout,
+// with the field named `cm_callable` and `sm_callable` respectively.
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct PyMethodWrapperObject {
+    pub ob_refcnt: Py_ssize_t,
+    pub ob_type: *mut _typeobject,
+    pub callable: *mut PyObject,
+}
+
+// Include/funcobject.h
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct PyFunctionObject {
+    pub ob_refcnt: Py_ssize_t,
+    pub ob_type: *mut _typeobject,
+    pub func_code: *mut PyObject,
+    pub func_globals: *mut PyObject,
+    pub func_defaults: *mut PyObject,
+    pub func_closure: *mut PyObject,
+    pub func_doc: *mut PyObject,
+    pub func_name: *mut PyObject,
+    pub func_dict: *mut PyObject,
+    pub func_weakreflist: *mut PyObject,
+    pub func_module: *mut PyObject,
+}
+
+// Modules/mmapmodule.c (Unix)
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct PyMmapObject {
+    pub ob_refcnt: Py_ssize_t,
+    pub ob_type: *mut _typeobject,
+    pub data: *mut ::std::os::raw::c_char,
+    pub size: usize,
+    pub pos: usize,
+    pub offset: i64,
+    pub fd: ::std::os::raw::c_int,
+    pub access: ::std::os::raw::c_int,
+}
+
+// Include/descrobject.h
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct PyWrapperDescrObject {
+    pub ob_refcnt: Py_ssize_t,
+    pub ob_type: *mut _typeobject,
+    pub d_type: *mut _typeobject,
+    pub d_name: *mut PyObject,
+    pub d_base: *mut ::std::os::raw::c_void,
+    pub d_wrapped: *mut ::std::os::raw::c_void,
+}
+
+// Objects/descrobject.c
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct wrapperobject {
+    pub ob_refcnt: Py_ssize_t,
+    pub ob_type: *mut _typeobject,
+    pub descr: *mut PyWrapperDescrObject,
+    pub self_: *mut PyObject,
+}
+
+// Objects/enumobject.c. `enumobject` starts with the same fields, named
+// `en_index` and `en_sit`, as does `listreviterobject` in Objects/listobject.c.
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct reversedobject {
+    pub ob_refcnt: Py_ssize_t,
+    pub ob_type: *mut _typeobject,
+    pub index: Py_ssize_t,
+    pub seq: *mut PyObject,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct PySliceObject {
+    pub ob_refcnt: Py_ssize_t,
+    pub ob_type: *mut _typeobject,
+    pub start: *mut PyObject,
+    pub stop: *mut PyObject,
+    pub step: *mut PyObject,
+}
+
+// Objects/capsule.c
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct PyCapsule {
+    pub ob_refcnt: Py_ssize_t,
+    pub ob_type: *mut _typeobject,
+    pub pointer: *mut ::std::os::raw::c_void,
+    pub name: *const ::std::os::raw::c_char,
+    pub context: *mut ::std::os::raw::c_void,
+    pub destructor: *mut ::std::os::raw::c_void,
+}
+
+// Include/bytearrayobject.h
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct PyByteArrayObject {
+    pub ob_refcnt: Py_ssize_t,
+    pub ob_type: *mut _typeobject,
+    pub ob_size: Py_ssize_t,
+    pub ob_exports: ::std::os::raw::c_int,
+    pub ob_alloc: Py_ssize_t,
+    pub ob_bytes: *mut ::std::os::raw::c_char,
+}
+
+// Include/weakrefobject.h
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct PyWeakReference {
+    pub ob_refcnt: Py_ssize_t,
+    pub ob_type: *mut _typeobject,
+    pub wr_object: *mut PyObject,
+    pub wr_callback: *mut PyObject,
+    pub hash: ::std::os::raw::c_long,
+    pub wr_prev: *mut PyWeakReference,
+    pub wr_next: *mut PyWeakReference,
+}
+
+// Modules/arraymodule.c
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct arraydescr {
+    pub typecode: ::std::os::raw::c_int,
+    pub itemsize: ::std::os::raw::c_int,
+    pub getitem: *mut ::std::os::raw::c_void,
+    pub setitem: *mut ::std::os::raw::c_void,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct arrayobject {
+    pub ob_refcnt: Py_ssize_t,
+    pub ob_type: *mut _typeobject,
+    pub ob_size: Py_ssize_t,
+    pub ob_item: *mut ::std::os::raw::c_char,
+    pub allocated: Py_ssize_t,
+    pub ob_descr: *mut arraydescr,
+    pub weakreflist: *mut PyObject,
+}
+
+// Modules/_collectionsmodule.c
+pub const DEQUE_BLOCKLEN: usize = 62;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct block {
+    pub data: [*mut PyObject; DEQUE_BLOCKLEN],
+    pub rightlink: *mut block,
+    pub leftlink: *mut block,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct dequeobject {
+    pub ob_refcnt: Py_ssize_t,
+    pub ob_type: *mut _typeobject,
+    pub leftblock: *mut block,
+    pub rightblock: *mut block,
+    pub leftindex: Py_ssize_t,
+    pub rightindex: Py_ssize_t,
+    pub len: Py_ssize_t,
+    pub state: ::std::os::raw::c_long,
+    pub maxlen: Py_ssize_t,
+    pub weakreflist: *mut PyObject,
+}
+
+// Include/datetime.h
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct PyDateTime_Date {
+    pub ob_refcnt: Py_ssize_t,
+    pub ob_type: *mut _typeobject,
+    pub hashcode: ::std::os::raw::c_long,
+    pub hastzinfo: ::std::os::raw::c_char,
+    pub data: [::std::os::raw::c_uchar; 4usize],
+    pub _padding: [u8; 3usize],
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct _PyDateTime_BaseDateTime {
+    pub ob_refcnt: Py_ssize_t,
+    pub ob_type: *mut _typeobject,
+    pub hashcode: ::std::os::raw::c_long,
+    pub hastzinfo: ::std::os::raw::c_char,
+    pub data: [::std::os::raw::c_uchar; 10usize],
+    pub _padding: [u8; 5usize],
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct PyDateTime_Delta {
+    pub ob_refcnt: Py_ssize_t,
+    pub ob_type: *mut _typeobject,
+    pub hashcode: ::std::os::raw::c_long,
+    pub days: ::std::os::raw::c_int,
+    pub seconds: ::std::os::raw::c_int,
+    pub microseconds: ::std::os::raw::c_int,
+    pub _padding: [u8; 4usize],
+}
+
+// Safety: `#[repr(C)]`, no padding, and every field is an integer or a raw
+// pointer, for which any bit pattern is valid.
+unsafe impl bytemuck::Zeroable for _object {}
+unsafe impl bytemuck::Pod for _object {}
+unsafe impl bytemuck::Zeroable for PyVarObject {}
+unsafe impl bytemuck::Pod for PyVarObject {}
+unsafe impl bytemuck::Zeroable for PyMethodWrapperObject {}
+unsafe impl bytemuck::Pod for PyMethodWrapperObject {}
+unsafe impl bytemuck::Zeroable for PyFunctionObject {}
+unsafe impl bytemuck::Pod for PyFunctionObject {}
+unsafe impl bytemuck::Zeroable for PyMmapObject {}
+unsafe impl bytemuck::Pod for PyMmapObject {}
+unsafe impl bytemuck::Zeroable for PyWrapperDescrObject {}
+unsafe impl bytemuck::Pod for PyWrapperDescrObject {}
+unsafe impl bytemuck::Zeroable for wrapperobject {}
+unsafe impl bytemuck::Pod for wrapperobject {}
+unsafe impl bytemuck::Zeroable for PyDateTime_Date {}
+unsafe impl bytemuck::Pod for PyDateTime_Date {}
+unsafe impl bytemuck::Zeroable for _PyDateTime_BaseDateTime {}
+unsafe impl bytemuck::Pod for _PyDateTime_BaseDateTime {}
+unsafe impl bytemuck::Zeroable for PyDateTime_Delta {}
+unsafe impl bytemuck::Pod for PyDateTime_Delta {}
+unsafe impl bytemuck::Zeroable for reversedobject {}
+unsafe impl bytemuck::Pod for reversedobject {}
+unsafe impl bytemuck::Zeroable for PySliceObject {}
+unsafe impl bytemuck::Pod for PySliceObject {}
+unsafe impl bytemuck::Zeroable for block {}
+unsafe impl bytemuck::Pod for block {}
+unsafe impl bytemuck::Zeroable for dequeobject {}
+unsafe impl bytemuck::Pod for dequeobject {}
+unsafe impl bytemuck::Zeroable for PyCapsule {}
+unsafe impl bytemuck::Pod for PyCapsule {}
+unsafe impl bytemuck::Zeroable for arraydescr {}
+unsafe impl bytemuck::Pod for arraydescr {}
+unsafe impl bytemuck::Zeroable for arrayobject {}
+unsafe impl bytemuck::Pod for arrayobject {}
+unsafe impl bytemuck::Zeroable for PyWeakReference {}
+unsafe impl bytemuck::Pod for PyWeakReference {}
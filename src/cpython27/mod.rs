@@ -6,16 +6,23 @@ use num_bigint::BigInt;
 
 use crate::error::{Error, Result};
 use crate::interpreter::{
-    BoolObject, BytesObject, ClassObject, DictEntry, DictObject, FloatObject, InstanceObject,
-    IntObject, Interpreter, ListItems, ListObject, NoneObject, Object, Pointer, StringObject,
-    TryDeref, TupleItems, TupleObject, Type, TypeObject, TypedObject, UnicodeObject, VarObject,
+    bigint_from_digits, ArrayObject, ArrayValues, BoolObject, BytesObject, CapsuleObject,
+    ClassObject, CodeObject, DateTimeObject, DateTimeValue, DefaultDictObject, DequeObject,
+    DictEntry, DictItems, DictObject, ExceptionObject, FloatObject, IndexedIteratorObject,
+    InstanceObject, IntObject, Interpreter, ListItems, ListObject, LongObject, MemoryViewObject,
+    MethodWrapperObject, MmapObject, NoneObject, Object, Pointer, PropertyObject, SetObject,
+    SliceObject, SlotWrapperObject, StringObject, TracebackObject, TryDeref, TupleItems,
+    TupleObject, Type, TypeObject, TypedObject, UnicodeObject, VarObject, WrapperDescriptorObject,
     PY_SIZE_T,
 };
-use crate::memory::Memory;
+use crate::memory::{Memory, MemoryExt};
 
-mod bindings;
+pub(crate) mod bindings;
 
 /// An interpreter marker type for decoding of CPython 2.7 memory.
+///
+/// All object types are `Copy`, except for [`PyTypeObject`] and
+/// [`PyClassObject`], which own their decoded names and are only `Clone`.
 #[derive(Debug, Copy, Clone)]
 pub struct Cpython2_7;
 
@@ -37,11 +44,33 @@ impl Interpreter for Cpython2_7 {
     type BoolObject = PyBoolObject<Self>;
     type IntObject = PyIntObject<Self>;
     type FloatObject = PyFloatObject<Self>;
+    type PropertyObject = PyPropertyObject<Self>;
+    type ExceptionObject = PyExceptionObject<Self>;
+    type MemoryViewObject = PyMemoryViewObject<Self>;
+    type TracebackObject = PyTracebackObject<Self>;
+    type SetObject = PySetObject<Self>;
+    type MethodWrapperObject = PyMethodWrapperObject<Self>;
+    type MmapObject = PyMmapObject<Self>;
+    type WrapperDescriptorObject = PyWrapperDescriptorObject<Self>;
+    type SlotWrapperObject = PySlotWrapperObject<Self>;
+    type CodeObject = PyCodeObject<Self>;
+    type DateTimeObject = PyDateTimeObject<Self>;
+    type IndexedIteratorObject = PyIndexedIteratorObject<Self>;
+    type SliceObject = PySliceObject<Self>;
+    type DefaultDictObject = PyDefaultDictObject<Self>;
+    type LongObject = PyLongObject<Self>;
+    type DequeObject = PyDequeObject<Self>;
+    type CapsuleObject = PyCapsuleObject<Self>;
+    type ArrayObject = PyArrayObject<Self>;
 }
 
 /// An interpreter marker type for decoding of CPython 2.7 memory with small
 /// string objects. It is not clear to me when this is the case, but it seems
 /// some CPython 2.7-compatible targets have strings that are 4 bytes smaller.
+///
+/// The layout is detected per string (see [`PySmallStringObject`]), so this
+/// marker also decodes standard targets, at the cost of reading a few more
+/// bytes per string.
 #[derive(Debug, Copy, Clone)]
 pub struct Cpython2_7SmallString;
 
@@ -63,6 +92,24 @@ impl Interpreter for Cpython2_7SmallString {
     type BoolObject = PyBoolObject<Self>;
     type IntObject = PyIntObject<Self>;
     type FloatObject = PyFloatObject<Self>;
+    type PropertyObject = PyPropertyObject<Self>;
+    type ExceptionObject = PyExceptionObject<Self>;
+    type MemoryViewObject = PyMemoryViewObject<Self>;
+    type TracebackObject = PyTracebackObject<Self>;
+    type SetObject = PySetObject<Self>;
+    type MethodWrapperObject = PyMethodWrapperObject<Self>;
+    type MmapObject = PyMmapObject<Self>;
+    type WrapperDescriptorObject = PyWrapperDescriptorObject<Self>;
+    type SlotWrapperObject = PySlotWrapperObject<Self>;
+    type CodeObject = PyCodeObject<Self>;
+    type DateTimeObject = PyDateTimeObject<Self>;
+    type IndexedIteratorObject = PyIndexedIteratorObject<Self>;
+    type SliceObject = PySliceObject<Self>;
+    type DefaultDictObject = PyDefaultDictObject<Self>;
+    type LongObject = PyLongObject<Self>;
+    type DequeObject = PyDequeObject<Self>;
+    type CapsuleObject = PyCapsuleObject<Self>;
+    type ArrayObject = PyArrayObject<Self>;
 }
 
 #[derive(Clone, Debug)]
@@ -72,6 +119,7 @@ pub enum PyTypedObject<I: Interpreter> {
     None(I::NoneObject),
     Class(I::ClassObject),
     Instance(I::InstanceObject),
+    Bytes(I::BytesObject),
     Str(I::StringObject),
     Unicode(I::UnicodeObject),
     Tuple(I::TupleObject),
@@ -80,19 +128,27 @@ pub enum PyTypedObject<I: Interpreter> {
     Bool(I::BoolObject),
     Int(I::IntObject),
     Float(I::FloatObject),
-}
-
-// Hacky: this does not exist in Python 2.7.
-pub struct PyBytesObject<I> {
-    _interp: PhantomData<I>,
-}
-impl<I: Interpreter> BytesObject<I> for PyBytesObject<I> {
-    fn to_var_object(&self) -> I::VarObject {
-        unimplemented!("Bytes does not exist in Python 2.7")
-    }
-    fn read(&self, _mem: &impl Memory) -> Result<Vec<u8>> {
-        unimplemented!("Bytes does not exist in Python 2.7")
-    }
+    Property(I::PropertyObject),
+    Exception(I::ExceptionObject),
+    MemoryView(I::MemoryViewObject),
+    Traceback(I::TracebackObject),
+    Set(I::SetObject),
+    ClassMethod(I::MethodWrapperObject),
+    StaticMethod(I::MethodWrapperObject),
+    Mmap(I::MmapObject),
+    WrapperDescriptor(I::WrapperDescriptorObject),
+    SlotWrapper(I::SlotWrapperObject),
+    Code(I::CodeObject),
+    #[cfg(feature = "datetime")]
+    DateTime(I::DateTimeObject),
+    Enumerate(I::IndexedIteratorObject),
+    Reversed(I::IndexedIteratorObject),
+    Slice(I::SliceObject),
+    DefaultDict(I::DefaultDictObject),
+    Long(I::LongObject),
+    Deque(I::DequeObject),
+    Capsule(I::CapsuleObject),
+    Array(I::ArrayObject),
 }
 
 impl<I: Interpreter> TypedObject<I> for PyTypedObject<I> {
@@ -103,6 +159,7 @@ impl<I: Interpreter> TypedObject<I> for PyTypedObject<I> {
             PyTypedObject::None(_) => Type::None,
             PyTypedObject::Class(_) => Type::Class,
             PyTypedObject::Instance(_) => Type::Instance,
+            PyTypedObject::Bytes(_) => Type::Bytes,
             PyTypedObject::Str(_) => Type::String,
             PyTypedObject::Unicode(_) => Type::Unicode,
             PyTypedObject::Tuple(_) => Type::Tuple,
@@ -111,6 +168,27 @@ impl<I: Interpreter> TypedObject<I> for PyTypedObject<I> {
             PyTypedObject::Bool(_) => Type::Bool,
             PyTypedObject::Int(_) => Type::Int,
             PyTypedObject::Float(_) => Type::Float,
+            PyTypedObject::Property(_) => Type::Property,
+            PyTypedObject::Exception(_) => Type::Exception,
+            PyTypedObject::MemoryView(_) => Type::MemoryView,
+            PyTypedObject::Traceback(_) => Type::Traceback,
+            PyTypedObject::Set(_) => Type::Set,
+            PyTypedObject::ClassMethod(_) => Type::ClassMethod,
+            PyTypedObject::StaticMethod(_) => Type::StaticMethod,
+            PyTypedObject::Mmap(_) => Type::Mmap,
+            PyTypedObject::WrapperDescriptor(_) => Type::WrapperDescriptor,
+            PyTypedObject::SlotWrapper(_) => Type::SlotWrapper,
+            PyTypedObject::Code(_) => Type::Code,
+            #[cfg(feature = "datetime")]
+            PyTypedObject::DateTime(_) => Type::DateTime,
+            PyTypedObject::Enumerate(_) => Type::Enumerate,
+            PyTypedObject::Reversed(_) => Type::Reversed,
+            PyTypedObject::Slice(_) => Type::Slice,
+            PyTypedObject::DefaultDict(_) => Type::DefaultDict,
+            PyTypedObject::Long(_) => Type::Long,
+            PyTypedObject::Deque(_) => Type::Deque,
+            PyTypedObject::Capsule(_) => Type::Capsule,
+            PyTypedObject::Array(_) => Type::Array,
         }
     }
 
@@ -151,7 +229,11 @@ impl<I: Interpreter> TypedObject<I> for PyTypedObject<I> {
         }
     }
     fn as_bytes(self) -> Option<I::BytesObject> {
-        None
+        if let PyTypedObject::Bytes(object) = self {
+            Some(object)
+        } else {
+            None
+        }
     }
     fn as_string(self) -> Option<I::StringObject> {
         if let PyTypedObject::Str(object) = self {
@@ -209,6 +291,164 @@ impl<I: Interpreter> TypedObject<I> for PyTypedObject<I> {
             None
         }
     }
+    fn as_property(self) -> Option<I::PropertyObject> {
+        if let PyTypedObject::Property(object) = self {
+            Some(object)
+        } else {
+            None
+        }
+    }
+    fn as_exception(self) -> Option<I::ExceptionObject> {
+        if let PyTypedObject::Exception(object) = self {
+            Some(object)
+        } else {
+            None
+        }
+    }
+    fn as_memory_view(self) -> Option<I::MemoryViewObject> {
+        if let PyTypedObject::MemoryView(object) = self {
+            Some(object)
+        } else {
+            None
+        }
+    }
+
+    fn as_traceback(self) -> Option<I::TracebackObject> {
+        if let PyTypedObject::Traceback(object) = self {
+            Some(object)
+        } else {
+            None
+        }
+    }
+
+    fn as_set(self) -> Option<I::SetObject> {
+        if let PyTypedObject::Set(object) = self {
+            Some(object)
+        } else {
+            None
+        }
+    }
+
+    fn as_class_method(self) -> Option<I::MethodWrapperObject> {
+        if let PyTypedObject::ClassMethod(object) = self {
+            Some(object)
+        } else {
+            None
+        }
+    }
+
+    fn as_static_method(self) -> Option<I::MethodWrapperObject> {
+        if let PyTypedObject::StaticMethod(object) = self {
+            Some(object)
+        } else {
+            None
+        }
+    }
+
+    fn as_mmap(self) -> Option<I::MmapObject> {
+        if let PyTypedObject::Mmap(object) = self {
+            Some(object)
+        } else {
+            None
+        }
+    }
+
+    fn as_wrapper_descriptor(self) -> Option<I::WrapperDescriptorObject> {
+        if let PyTypedObject::WrapperDescriptor(object) = self {
+            Some(object)
+        } else {
+            None
+        }
+    }
+
+    fn as_slot_wrapper(self) -> Option<I::SlotWrapperObject> {
+        if let PyTypedObject::SlotWrapper(object) = self {
+            Some(object)
+        } else {
+            None
+        }
+    }
+
+    fn as_code(self) -> Option<I::CodeObject> {
+        if let PyTypedObject::Code(object) = self {
+            Some(object)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "datetime")]
+    fn as_date_time(self) -> Option<I::DateTimeObject> {
+        if let PyTypedObject::DateTime(object) = self {
+            Some(object)
+        } else {
+            None
+        }
+    }
+
+    fn as_enumerate(self) -> Option<I::IndexedIteratorObject> {
+        if let PyTypedObject::Enumerate(object) = self {
+            Some(object)
+        } else {
+            None
+        }
+    }
+
+    fn as_reversed(self) -> Option<I::IndexedIteratorObject> {
+        if let PyTypedObject::Reversed(object) = self {
+            Some(object)
+        } else {
+            None
+        }
+    }
+
+    fn as_slice(self) -> Option<I::SliceObject> {
+        if let PyTypedObject::Slice(object) = self {
+            Some(object)
+        } else {
+            None
+        }
+    }
+
+    fn as_default_dict(self) -> Option<I::DefaultDictObject> {
+        if let PyTypedObject::DefaultDict(object) = self {
+            Some(object)
+        } else {
+            None
+        }
+    }
+
+    fn as_long(self) -> Option<I::LongObject> {
+        if let PyTypedObject::Long(object) = self {
+            Some(object)
+        } else {
+            None
+        }
+    }
+
+    fn as_deque(self) -> Option<I::DequeObject> {
+        if let PyTypedObject::Deque(object) = self {
+            Some(object)
+        } else {
+            None
+        }
+    }
+
+    fn as_capsule(self) -> Option<I::CapsuleObject> {
+        if let PyTypedObject::Capsule(object) = self {
+            Some(object)
+        } else {
+            None
+        }
+    }
+
+    fn as_array(self) -> Option<I::ArrayObject> {
+        if let PyTypedObject::Array(object) = self {
+            Some(object)
+        } else {
+            None
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -216,24 +456,56 @@ pub struct PyTypeObject<I> {
     me: Pointer,
     object: bindings::PyTypeObject,
     name: String,
+    name_truncated: bool,
     _interp: PhantomData<I>,
 }
 
 pub const PY_TYPE_OBJECT_SIZE: usize = std::mem::size_of::<bindings::PyTypeObject>();
 
+/// The name given to types whose `tp_name` cannot be read.
+pub const UNREADABLE_TYPE_NAME: &str = "<unreadable type>";
+
+impl<I> PyTypeObject<I> {
+    /// The raw `tp_name` pointer. Useful when the name was unreadable.
+    pub fn tp_name(&self) -> Pointer {
+        Pointer::new(self.object.tp_name as usize)
+    }
+}
+
 impl<I: Interpreter> TryDeref for PyTypeObject<I> {
-    fn try_deref(mem: &impl Memory, pointer: Pointer) -> Result<Self> {
+    fn try_deref(mem: &(impl Memory + ?Sized), pointer: Pointer) -> Result<Self> {
         let b: [u8; PY_TYPE_OBJECT_SIZE] = mem
             .get_vec(pointer.address(), PY_TYPE_OBJECT_SIZE)?
             .try_into()
-            .expect("const size");
+            .map_err(|_| Error::Decode)?;
+
+        let type_object: bindings::PyTypeObject = unsafe { std::mem::transmute(b) };
 
-        let type_object = unsafe { std::mem::transmute(b) };
+        let tp_name = Pointer::new(type_object.tp_name as usize);
+        // One byte past the limit tells whether the name is truncated.
+        let mut name_truncated = false;
+        let name = match tp_name.deref_c_str(mem, Some(I::MAX_TYPE_NAME_LENGTH + 1)) {
+            Ok(name) if name.chars().count() > I::MAX_TYPE_NAME_LENGTH => {
+                name_truncated = true;
+                name.chars().take(I::MAX_TYPE_NAME_LENGTH).collect()
+            }
+            Ok(name) => name,
+            Err(error) => {
+                tracing::warn!(
+                    "could not read name of type at {:#x} (tp_name {:#x}): {}",
+                    pointer.address(),
+                    tp_name.address(),
+                    error
+                );
+                UNREADABLE_TYPE_NAME.to_owned()
+            }
+        };
 
         Ok(Self {
             me: pointer,
             object: type_object,
-            name: Pointer::new(type_object.tp_name as usize).deref_c_str(mem, Some(1_000))?,
+            name,
+            name_truncated,
             _interp: PhantomData,
         })
     }
@@ -241,7 +513,13 @@ impl<I: Interpreter> TryDeref for PyTypeObject<I> {
 
 impl<I: Interpreter> TypeObject<I> for PyTypeObject<I>
 where
-    I: Interpreter<TypeObject = Self, TypedObject = PyTypedObject<I>, VarObject = PyVarObject<I>>,
+    I: Interpreter<
+        TypeObject = Self,
+        TypedObject = PyTypedObject<I>,
+        VarObject = PyVarObject<I>,
+        MemoryViewObject = PyMemoryViewObject<I>,
+        DateTimeObject = PyDateTimeObject<I>,
+    >,
 {
     fn to_var_object(&self) -> I::VarObject {
         PyVarObject {
@@ -259,6 +537,10 @@ where
         &self.name
     }
 
+    fn name_truncated(&self) -> bool {
+        self.name_truncated
+    }
+
     fn tp_basicsize(&self) -> isize {
         self.object.tp_basicsize
     }
@@ -271,12 +553,95 @@ where
         self.object.tp_dictoffset
     }
 
-    fn downcast(&self, mem: &impl Memory, object: I::Object) -> Result<I::TypedObject> {
+    fn tp_weaklistoffset(&self) -> isize {
+        self.object.tp_weaklistoffset
+    }
+
+    fn builtin_base(&self) -> Option<Type> {
+        let flags = self.object.tp_flags;
+        if flags & python27_sys::Py_TPFLAGS_INT_SUBCLASS != 0 {
+            Some(Type::Int)
+        } else if flags & python27_sys::Py_TPFLAGS_LONG_SUBCLASS != 0 {
+            Some(Type::Long)
+        } else if flags & python27_sys::Py_TPFLAGS_STRING_SUBCLASS != 0 {
+            Some(Type::String)
+        } else if flags & python27_sys::Py_TPFLAGS_UNICODE_SUBCLASS != 0 {
+            Some(Type::Unicode)
+        } else if flags & python27_sys::Py_TPFLAGS_TUPLE_SUBCLASS != 0 {
+            Some(Type::Tuple)
+        } else if flags & python27_sys::Py_TPFLAGS_LIST_SUBCLASS != 0 {
+            Some(Type::List)
+        } else {
+            None
+        }
+    }
+
+    fn is_heap_type(&self) -> bool {
+        self.object.tp_flags & python27_sys::Py_TPFLAGS_HEAPTYPE != 0
+    }
+
+    fn tp_as_buffer(&self) -> Pointer {
+        Pointer::new(self.object.tp_as_buffer as usize)
+    }
+
+    fn exported_buffer(
+        &self,
+        mem: &(impl Memory + ?Sized),
+        object: &I::Object,
+    ) -> Result<Option<(Pointer, usize)>> {
+        if self.tp_as_buffer().null() {
+            return Ok(None);
+        }
+        match self.name() {
+            "bytearray" => {
+                let var_object: bindings::PyVarObject = mem.read_struct(object.me().address())?;
+                let bytes = mem.get_usize(
+                    offset_pointer(
+                        object.me(),
+                        offset_of!(bindings::PyByteArrayObject, ob_bytes),
+                    )?
+                    .address(),
+                )?;
+                Ok(Some((Pointer::new(bytes), item_count(var_object.ob_size)?)))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn tp_mro(&self, mem: &(impl Memory + ?Sized)) -> Result<Option<I::TupleObject>> {
+        let mro_ptr = Pointer::new(self.object.tp_mro as usize);
+        if mro_ptr.null() {
+            Ok(None)
+        } else {
+            Ok(Some(mro_ptr.try_deref_me(mem)?))
+        }
+    }
+
+    fn tp_base(&self, mem: &(impl Memory + ?Sized)) -> Result<Option<I::TypeObject>> {
+        let base_ptr = Pointer::new(self.object.tp_base as usize);
+        if base_ptr.null() {
+            Ok(None)
+        } else {
+            Ok(Some(base_ptr.try_deref_me(mem)?))
+        }
+    }
+
+    fn tp_dict(&self, mem: &(impl Memory + ?Sized)) -> Result<Option<I::DictObject>> {
+        let dict_ptr = Pointer::new(self.object.tp_dict as usize);
+        if dict_ptr.null() {
+            Ok(None)
+        } else {
+            Ok(Some(dict_ptr.try_deref_me(mem)?))
+        }
+    }
+
+    fn downcast(&self, mem: &(impl Memory + ?Sized), object: I::Object) -> Result<I::TypedObject> {
         let typed = match self.name.as_str() {
             "type" => PyTypedObject::Type(object.me().try_deref_me(mem)?),
             "NoneType" => PyTypedObject::None(object.me().try_deref_me(mem)?),
             "classobj" => PyTypedObject::Class(object.me().try_deref_me(mem)?),
             "instance" => PyTypedObject::Instance(object.me().try_deref_me(mem)?),
+            "bytes" => PyTypedObject::Bytes(object.me().try_deref_me(mem)?),
             "str" => PyTypedObject::Str(object.me().try_deref_me(mem)?),
             "unicode" => PyTypedObject::Unicode(object.me().try_deref_me(mem)?),
             "tuple" => PyTypedObject::Tuple(object.me().try_deref_me(mem)?),
@@ -285,6 +650,46 @@ where
             "bool" => PyTypedObject::Bool(object.me().try_deref_me(mem)?),
             "int" => PyTypedObject::Int(object.me().try_deref_me(mem)?),
             "float" => PyTypedObject::Float(object.me().try_deref_me(mem)?),
+            "property" => PyTypedObject::Property(object.me().try_deref_me(mem)?),
+            _ if self.object.tp_flags & python27_sys::Py_TPFLAGS_BASE_EXC_SUBCLASS != 0 => {
+                PyTypedObject::Exception(object.me().try_deref_me(mem)?)
+            }
+            "memoryview" => PyTypedObject::MemoryView(object.me().try_deref_me(mem)?),
+            "buffer" => {
+                PyTypedObject::MemoryView(PyMemoryViewObject::try_deref_buffer(mem, object.me())?)
+            }
+            "traceback" => PyTypedObject::Traceback(object.me().try_deref_me(mem)?),
+            "set" => PyTypedObject::Set(object.me().try_deref_me(mem)?),
+            "frozenset" => PyTypedObject::Set(object.me().try_deref_me(mem)?),
+            "classmethod" => PyTypedObject::ClassMethod(object.me().try_deref_me(mem)?),
+            "staticmethod" => PyTypedObject::StaticMethod(object.me().try_deref_me(mem)?),
+            "mmap.mmap" => PyTypedObject::Mmap(object.me().try_deref_me(mem)?),
+            "wrapper_descriptor" => {
+                PyTypedObject::WrapperDescriptor(object.me().try_deref_me(mem)?)
+            }
+            "method-wrapper" => PyTypedObject::SlotWrapper(object.me().try_deref_me(mem)?),
+            "code" => PyTypedObject::Code(object.me().try_deref_me(mem)?),
+            #[cfg(feature = "datetime")]
+            "datetime.date" => {
+                PyTypedObject::DateTime(PyDateTimeObject::try_deref_date(mem, object.me())?)
+            }
+            #[cfg(feature = "datetime")]
+            "datetime.datetime" => {
+                PyTypedObject::DateTime(PyDateTimeObject::try_deref_datetime(mem, object.me())?)
+            }
+            #[cfg(feature = "datetime")]
+            "datetime.timedelta" => {
+                PyTypedObject::DateTime(PyDateTimeObject::try_deref_timedelta(mem, object.me())?)
+            }
+            "enumerate" => PyTypedObject::Enumerate(object.me().try_deref_me(mem)?),
+            "reversed" => PyTypedObject::Reversed(object.me().try_deref_me(mem)?),
+            "listreverseiterator" => PyTypedObject::Reversed(object.me().try_deref_me(mem)?),
+            "slice" => PyTypedObject::Slice(object.me().try_deref_me(mem)?),
+            "collections.defaultdict" => PyTypedObject::DefaultDict(object.me().try_deref_me(mem)?),
+            "long" => PyTypedObject::Long(object.me().try_deref_me(mem)?),
+            "collections.deque" => PyTypedObject::Deque(object.me().try_deref_me(mem)?),
+            "PyCapsule" => PyTypedObject::Capsule(object.me().try_deref_me(mem)?),
+            "array.array" => PyTypedObject::Array(object.me().try_deref_me(mem)?),
             _ => PyTypedObject::Object((*self).clone(), object),
         };
 
@@ -299,18 +704,13 @@ pub struct PyObject<I> {
     _interp: std::marker::PhantomData<I>,
 }
 
-const PY_OBJECT_SIZE: usize = std::mem::size_of::<bindings::PyObject>();
-
 impl<I> TryDeref for PyObject<I> {
-    fn try_deref(mem: &impl Memory, pointer: Pointer) -> Result<Self> {
-        let b: [u8; PY_OBJECT_SIZE] = mem
-            .get_vec(pointer.address(), PY_OBJECT_SIZE)?
-            .try_into()
-            .expect("const size");
+    fn try_deref(mem: &(impl Memory + ?Sized), pointer: Pointer) -> Result<Self> {
+        let object: bindings::PyObject = mem.read_struct(pointer.address())?;
 
         Ok(Self {
             me: pointer,
-            object: unsafe { std::mem::transmute(b) },
+            object,
             _interp: std::marker::PhantomData,
         })
     }
@@ -321,7 +721,11 @@ impl<I: Interpreter<Object = Self>> Object<I> for PyObject<I> {
         self.me
     }
 
-    fn ob_type(&self, mem: &impl Memory) -> Result<I::TypeObject> {
+    fn ob_refcnt(&self) -> isize {
+        self.object.ob_refcnt
+    }
+
+    fn ob_type(&self, mem: &(impl Memory + ?Sized)) -> Result<I::TypeObject> {
         self.ob_type_pointer().try_deref_me(mem)
     }
 
@@ -329,16 +733,192 @@ impl<I: Interpreter<Object = Self>> Object<I> for PyObject<I> {
         Pointer::new(self.object.ob_type as usize)
     }
 
-    fn attributes(&self, mem: &impl Memory) -> Result<Option<I::DictObject>> {
-        let dictoffset = self.ob_type(mem)?.tp_dictoffset();
+    fn attributes(&self, mem: &(impl Memory + ?Sized)) -> Result<Option<I::DictObject>> {
+        instance_dict::<I>(mem, self.me, &self.ob_type(mem)?)
+    }
 
-        if dictoffset == 0 {
-            Ok(None)
-        } else {
-            let dict_ptr: Pointer = (self.me + dictoffset).try_deref_me(mem)?;
-            Ok(Some(dict_ptr.try_deref_me(mem)?))
+    fn weak_references(&self, mem: &(impl Memory + ?Sized)) -> Result<Vec<I::Object>> {
+        weak_references::<I>(mem, self.me, &self.ob_type(mem)?)
+    }
+}
+
+/// Read the weakref list of the object at `me`, mirroring
+/// `PyObject_GET_WEAKREFS_LISTPTR`: the list head is at `tp_weaklistoffset`,
+/// and the weakrefs are linked through `wr_next`. Each must refer back to `me`;
+/// a weakref that does not, or a list that loops, fails with `Error::Decode`.
+fn weak_references<I: Interpreter>(
+    mem: &(impl Memory + ?Sized),
+    me: Pointer,
+    tp: &I::TypeObject,
+) -> Result<Vec<I::Object>> {
+    let offset = tp.tp_weaklistoffset();
+    if offset <= 0 {
+        return Ok(Vec::new());
+    }
+
+    let mut references = Vec::new();
+    let mut seen = std::collections::HashSet::new();
+    let mut next: Pointer = me
+        .checked_add_signed(offset)
+        .ok_or(Error::Decode)?
+        .try_deref_me(mem)?;
+    while !next.null() {
+        if !seen.insert(next) {
+            return Err(Error::Decode);
+        }
+        let reference: bindings::PyWeakReference = mem.read_struct(next.address())?;
+        if reference.wr_object as usize != me.address() {
+            return Err(Error::Decode);
         }
+        references.push(next.try_deref_me(mem)?);
+        next = Pointer::new(reference.wr_next as usize);
     }
+
+    Ok(references)
+}
+
+/// Read the instance dict of the object at `me`, mirroring
+/// `_PyObject_GetDictPtr`. A negative `tp_dictoffset` is relative to the end of
+/// the object, which for var-sized types depends on `ob_size`.
+fn instance_dict<I: Interpreter>(
+    mem: &(impl Memory + ?Sized),
+    me: Pointer,
+    tp: &I::TypeObject,
+) -> Result<Option<I::DictObject>> {
+    let dictoffset = tp.tp_dictoffset();
+    if dictoffset == 0 {
+        return Ok(None);
+    }
+
+    let items_size = if dictoffset < 0 && tp.tp_itemsize() != 0 {
+        let var_object: I::VarObject = me.try_deref_me(mem)?;
+        var_object
+            .ob_size()
+            .checked_abs()
+            .and_then(|size| size.checked_mul(tp.tp_itemsize().abs()))
+            .ok_or(Error::Decode)?
+    } else {
+        0
+    };
+    let offset =
+        dict_offset(dictoffset, tp.tp_basicsize(), items_size, PY_SIZE_T).ok_or(Error::Decode)?;
+
+    let dict_ptr: Pointer = me
+        .checked_add_signed(offset)
+        .ok_or(Error::Decode)?
+        .try_deref_me(mem)?;
+    // The dict is created lazily, so a NULL slot means no attributes.
+    if dict_ptr.null() {
+        Ok(None)
+    } else {
+        Ok(Some(dict_ptr.try_deref_me(mem)?))
+    }
+}
+
+/// The offset of an object's instance dict pointer from its `tp_dictoffset`, on
+/// a target with `word_size`-byte words. A negative offset is relative to the
+/// end of the object: its `tp_basicsize` plus `items_size` bytes of items,
+/// aligned to a full word as `_PyObject_VAR_SIZE` does. `None` if a corrupt
+/// size overflows.
+fn dict_offset(
+    dictoffset: isize,
+    basicsize: isize,
+    items_size: isize,
+    word_size: usize,
+) -> Option<isize> {
+    if dictoffset >= 0 {
+        return Some(dictoffset);
+    }
+    let size: usize = basicsize.checked_add(items_size)?.try_into().ok()?;
+    let size: isize = size
+        .div_ceil(word_size)
+        .checked_mul(word_size)?
+        .try_into()
+        .ok()?;
+    size.checked_add(dictoffset)
+}
+
+/// The pointer `offset` bytes past `pointer`, failing with `Error::Decode`
+/// rather than wrapping around if `pointer` is corrupt.
+fn offset_pointer(pointer: Pointer, offset: usize) -> Result<Pointer> {
+    pointer.checked_add(offset).ok_or(Error::Decode)
+}
+
+/// A dict hash table slot: a `PyDictEntry` read with the target's word size.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct DictSlot {
+    hash: usize,
+    key: usize,
+    value: usize,
+}
+
+/// Read `slots` consecutive `PyDictEntry`s at `table` on a target with
+/// `word_size`-byte words. Each entry is three words, `me_hash`, `me_key` and
+/// `me_value`, so entries are 12 bytes apart on 32-bit targets and 24 bytes
+/// apart on 64-bit ones.
+fn read_dict_slots(
+    mem: &(impl Memory + ?Sized),
+    table: usize,
+    slots: usize,
+    word_size: usize,
+) -> Result<Vec<DictSlot>> {
+    let words = read_words(
+        mem,
+        table,
+        slots.checked_mul(3).ok_or(Error::SizeError)?,
+        word_size,
+    )?;
+    Ok(words
+        .chunks_exact(3)
+        .map(|entry| DictSlot {
+            // `me_hash` is signed, so sign-extend it as the host would.
+            hash: signed_word(entry[0], word_size) as usize,
+            key: entry[1],
+            value: entry[2],
+        })
+        .collect())
+}
+
+/// Read `count` consecutive `word_size`-byte words at `address`, zero-extended
+/// to the host's word size.
+fn read_words(
+    mem: &(impl Memory + ?Sized),
+    address: usize,
+    count: usize,
+    word_size: usize,
+) -> Result<Vec<usize>> {
+    if word_size != 4 && word_size != 8 {
+        return Err(Error::Decode);
+    }
+    let size = count.checked_mul(word_size).ok_or(Error::SizeError)?;
+    let bytes = mem.get_vec(address, size)?;
+    if bytes.len() < size {
+        return Err(Error::Decode);
+    }
+
+    bytes
+        .chunks_exact(word_size)
+        .map(|word| {
+            Ok(match word_size {
+                4 => u32::from_ne_bytes(word.try_into().map_err(|_| Error::Decode)?) as usize,
+                _ => u64::from_ne_bytes(word.try_into().map_err(|_| Error::Decode)?) as usize,
+            })
+        })
+        .collect()
+}
+
+/// The signed value of a `word_size`-byte word read by [`read_words`].
+fn signed_word(word: usize, word_size: usize) -> isize {
+    match word_size {
+        4 => word as u32 as i32 as isize,
+        _ => word as isize,
+    }
+}
+
+/// The item count of a var-sized object whose `ob_size` cannot be negative,
+/// unlike that of `long`, which stores the sign there.
+fn item_count(ob_size: isize) -> Result<usize> {
+    ob_size.try_into().map_err(|_| Error::Decode)
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -351,15 +931,12 @@ pub struct PyVarObject<I> {
 pub const PY_VAR_OBJECT_SIZE: usize = std::mem::size_of::<bindings::PyVarObject>();
 
 impl<I> TryDeref for PyVarObject<I> {
-    fn try_deref(mem: &impl Memory, pointer: Pointer) -> Result<Self> {
-        let b: [u8; PY_VAR_OBJECT_SIZE] = mem
-            .get_vec(pointer.address(), PY_VAR_OBJECT_SIZE)?
-            .try_into()
-            .expect("const size");
+    fn try_deref(mem: &(impl Memory + ?Sized), pointer: Pointer) -> Result<Self> {
+        let object: bindings::PyVarObject = mem.read_struct(pointer.address())?;
 
         Ok(Self {
             me: pointer,
-            object: unsafe { std::mem::transmute(b) },
+            object,
             _interp: std::marker::PhantomData,
         })
     }
@@ -381,25 +958,8 @@ impl<I: Interpreter<Object = PyObject<I>, VarObject = Self>> VarObject<I> for Py
         self.object.ob_size
     }
 
-    fn attributes(&self, mem: &impl Memory) -> Result<Option<I::DictObject>> {
-        let tp: I::TypeObject = self.to_object().ob_type(mem)?;
-        let dictoffset = tp.tp_dictoffset();
-
-        if dictoffset == 0 {
-            Ok(None)
-        } else {
-            let dict_ptr: Pointer = if dictoffset < 0 {
-                (self.me + dictoffset).try_deref_me(mem)?
-            } else {
-                let offset = (tp.tp_basicsize()
-                    + self.ob_size().abs() * tp.tp_itemsize()
-                    + dictoffset) as usize;
-                // Align to full word.
-                let offset = (offset + PY_SIZE_T - 1) / PY_SIZE_T * PY_SIZE_T;
-                (self.me + offset).try_deref_me(mem)?
-            };
-            Ok(Some(dict_ptr.try_deref_me(mem)?))
-        }
+    fn attributes(&self, mem: &(impl Memory + ?Sized)) -> Result<Option<I::DictObject>> {
+        instance_dict::<I>(mem, self.me, &self.to_object().ob_type(mem)?)
     }
 }
 
@@ -413,15 +973,16 @@ pub struct PyNoneObject<I> {
 pub const PY_NONE_OBJECT_SIZE: usize = std::mem::size_of::<bindings::PyObject>();
 
 impl<I> TryDeref for PyNoneObject<I> {
-    fn try_deref(mem: &impl Memory, pointer: Pointer) -> Result<Self> {
+    fn try_deref(mem: &(impl Memory + ?Sized), pointer: Pointer) -> Result<Self> {
         let b: [u8; PY_NONE_OBJECT_SIZE] = mem
             .get_vec(pointer.address(), PY_NONE_OBJECT_SIZE)?
             .try_into()
-            .expect("const size");
+            .map_err(|_| Error::Decode)?;
+        let object: bindings::PyObject = unsafe { std::mem::transmute(b) };
 
         Ok(Self {
             me: pointer,
-            object: unsafe { std::mem::transmute(b) },
+            object,
             _interp: PhantomData,
         })
     }
@@ -460,11 +1021,11 @@ impl<I> std::fmt::Debug for PyClassObject<I> {
 pub const PY_CLASS_OBJECT_SIZE: usize = std::mem::size_of::<python27_sys::PyClassObject>();
 
 impl<I: Interpreter> TryDeref for PyClassObject<I> {
-    fn try_deref(mem: &impl Memory, pointer: Pointer) -> Result<Self> {
+    fn try_deref(mem: &(impl Memory + ?Sized), pointer: Pointer) -> Result<Self> {
         let b: [u8; PY_CLASS_OBJECT_SIZE] = mem
             .get_vec(pointer.address(), PY_CLASS_OBJECT_SIZE)?
             .try_into()
-            .expect("const size");
+            .map_err(|_| Error::Decode)?;
 
         let class_object: python27_sys::PyClassObject = unsafe { std::mem::transmute(b) };
         let class_name_string: I::StringObject =
@@ -495,13 +1056,17 @@ impl<I: Interpreter<Object = PyObject<I>>> ClassObject<I> for PyClassObject<I> {
         &self.name
     }
 
-    fn bases(&self, mem: &impl Memory) -> Result<Option<I::ClassObject>> {
-        let class_ptr: Pointer = Pointer::new(self.object.cl_bases as usize);
-        if class_ptr.null() {
-            Ok(None)
-        } else {
-            Ok(Some(class_ptr.try_deref_me(mem)?))
+    fn bases(&self, mem: &(impl Memory + ?Sized)) -> Result<Vec<I::ClassObject>> {
+        // `cl_bases` is a tuple of classes.
+        let bases_ptr: Pointer = Pointer::new(self.object.cl_bases as usize);
+        if bases_ptr.null() {
+            return Ok(Vec::new());
         }
+        let bases: I::TupleObject = bases_ptr.try_deref_me(mem)?;
+        bases
+            .items(mem)
+            .map(|item| item?.me().try_deref_me(mem))
+            .collect()
     }
 }
 
@@ -523,15 +1088,16 @@ impl<I> std::fmt::Debug for PyInstanceObject<I> {
 pub const PY_INSTANCE_OBJECT_SIZE: usize = std::mem::size_of::<python27_sys::PyInstanceObject>();
 
 impl<I> TryDeref for PyInstanceObject<I> {
-    fn try_deref(mem: &impl Memory, pointer: Pointer) -> Result<Self> {
+    fn try_deref(mem: &(impl Memory + ?Sized), pointer: Pointer) -> Result<Self> {
         let b: [u8; PY_INSTANCE_OBJECT_SIZE] = mem
             .get_vec(pointer.address(), PY_INSTANCE_OBJECT_SIZE)?
             .try_into()
-            .expect("const size");
+            .map_err(|_| Error::Decode)?;
+        let object: python27_sys::PyInstanceObject = unsafe { std::mem::transmute(b) };
 
         Ok(Self {
             me: pointer,
-            object: unsafe { std::mem::transmute(b) },
+            object,
             _interp: PhantomData,
         })
     }
@@ -549,12 +1115,12 @@ impl<I: Interpreter<Object = PyObject<I>>> InstanceObject<I> for PyInstanceObjec
         }
     }
 
-    fn class(&self, mem: &impl Memory) -> Result<I::ClassObject> {
+    fn class(&self, mem: &(impl Memory + ?Sized)) -> Result<I::ClassObject> {
         let class_ptr: Pointer = Pointer::new(self.object.in_class as usize);
         class_ptr.try_deref_me(mem)
     }
 
-    fn attributes(&self, mem: &impl Memory) -> Result<I::DictObject> {
+    fn attributes(&self, mem: &(impl Memory + ?Sized)) -> Result<I::DictObject> {
         let dict_ptr: Pointer = Pointer::new(self.object.in_dict as usize);
         dict_ptr.try_deref_me(mem)
     }
@@ -570,15 +1136,16 @@ pub struct PyStringObject<I> {
 pub const PY_STRING_OBJECT_SIZE: usize = std::mem::size_of::<bindings::PyStringObject>();
 
 impl<I> TryDeref for PyStringObject<I> {
-    fn try_deref(mem: &impl Memory, pointer: Pointer) -> Result<Self> {
+    fn try_deref(mem: &(impl Memory + ?Sized), pointer: Pointer) -> Result<Self> {
         let b: [u8; PY_STRING_OBJECT_SIZE] = mem
             .get_vec(pointer.address(), PY_STRING_OBJECT_SIZE)?
             .try_into()
-            .expect("const size");
+            .map_err(|_| Error::Decode)?;
+        let object: bindings::PyStringObject = unsafe { std::mem::transmute(b) };
 
         Ok(Self {
             me: pointer,
-            object: unsafe { std::mem::transmute(b) },
+            object,
             _interp: PhantomData,
         })
     }
@@ -597,41 +1164,61 @@ impl<I: Interpreter<VarObject = PyVarObject<I>>> StringObject<I> for PyStringObj
         }
     }
 
-    fn read_bytes(&self, mem: &impl Memory) -> Result<Vec<u8>> {
-        let size = self.object.ob_size as usize;
+    fn read_bytes(&self, mem: &(impl Memory + ?Sized)) -> Result<Vec<u8>> {
+        let size = item_count(self.object.ob_size)?;
         if size > 2_000 {
             return Err(Error::SizeError);
         }
         mem.get_vec(
-            (self.me + offset_of!(bindings::PyStringObject, ob_sval)).address(),
+            offset_pointer(self.me, offset_of!(bindings::PyStringObject, ob_sval))?.address(),
             size,
         )
     }
+
+    /// Reads at most `max_len` bytes, so unlike `read_bytes` this is not
+    /// limited to short strings.
+    fn read_bytes_prefix(
+        &self,
+        mem: &(impl Memory + ?Sized),
+        max_len: usize,
+    ) -> Result<(Vec<u8>, usize)> {
+        let size = item_count(self.object.ob_size)?;
+        let bytes = mem.get_vec(
+            offset_pointer(self.me, offset_of!(bindings::PyStringObject, ob_sval))?.address(),
+            size.min(max_len),
+        )?;
+        Ok((bytes, size))
+    }
 }
 
+/// Python 2.7 has no separate `bytes` type (it is an alias of `str`), but
+/// Python 3 targets do. Its layout matches `PyStringObject`, so it is decoded
+/// the same way.
 #[derive(Copy, Clone, Debug)]
-pub struct PySmallStringObject<I> {
+pub struct PyBytesObject<I> {
     me: Pointer,
     object: bindings::PyStringObject,
     _interp: PhantomData<I>,
 }
 
-impl<I> TryDeref for PySmallStringObject<I> {
-    fn try_deref(mem: &impl Memory, pointer: Pointer) -> Result<Self> {
+impl<I> TryDeref for PyBytesObject<I> {
+    fn try_deref(mem: &(impl Memory + ?Sized), pointer: Pointer) -> Result<Self> {
         let b: [u8; PY_STRING_OBJECT_SIZE] = mem
             .get_vec(pointer.address(), PY_STRING_OBJECT_SIZE)?
             .try_into()
-            .expect("const size");
+            .map_err(|_| Error::Decode)?;
+
+        let object: bindings::PyStringObject = unsafe { std::mem::transmute(b) };
 
         Ok(Self {
             me: pointer,
-            object: unsafe { std::mem::transmute(b) },
+            object,
             _interp: PhantomData,
         })
     }
 }
 
-impl<I: Interpreter<VarObject = PyVarObject<I>>> StringObject<I> for PySmallStringObject<I> {
+impl<I: Interpreter<VarObject = PyVarObject<I>>> BytesObject<I> for PyBytesObject<I> {
     fn to_var_object(&self) -> I::VarObject {
         PyVarObject {
             me: self.me,
@@ -644,37 +1231,133 @@ impl<I: Interpreter<VarObject = PyVarObject<I>>> StringObject<I> for PySmallStri
         }
     }
 
-    // The - 4 seems wrong, but at least one of the Python 2.7 targets requires
-    // this.
-    fn read_bytes(&self, mem: &impl Memory) -> Result<Vec<u8>> {
-        let size = self.object.ob_size as usize;
+    fn read(&self, mem: &(impl Memory + ?Sized)) -> Result<Vec<u8>> {
+        let size = item_count(self.object.ob_size)?;
         if size > 2_000 {
             return Err(Error::SizeError);
         }
         mem.get_vec(
-            (self.me + (offset_of!(bindings::PyStringObject, ob_sval) - 4)).address(),
+            offset_pointer(self.me, offset_of!(bindings::PyStringObject, ob_sval))?.address(),
             size,
         )
     }
+
+    /// Reads at most `max_len` bytes, so unlike `read` this is not limited to
+    /// short objects.
+    fn read_prefix(
+        &self,
+        mem: &(impl Memory + ?Sized),
+        max_len: usize,
+    ) -> Result<(Vec<u8>, usize)> {
+        let size = item_count(self.object.ob_size)?;
+        let bytes = mem.get_vec(
+            offset_pointer(self.me, offset_of!(bindings::PyStringObject, ob_sval))?.address(),
+            size.min(max_len),
+        )?;
+        Ok((bytes, size))
+    }
 }
 
-#[derive(Copy, Clone)]
-pub struct PyUnicodeObject<I> {
+#[derive(Copy, Clone, Debug)]
+pub struct PySmallStringObject<I> {
     me: Pointer,
-    object: python27_sys::PyUnicodeObject,
+    object: bindings::PyStringObject,
     _interp: PhantomData<I>,
 }
 
-impl<I> std::fmt::Debug for PyUnicodeObject<I> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("PyUnicodeObject")
-            .field("me", &self.me)
-            .finish()
-    }
-}
+impl<I> TryDeref for PySmallStringObject<I> {
+    fn try_deref(mem: &(impl Memory + ?Sized), pointer: Pointer) -> Result<Self> {
+        let b: [u8; PY_STRING_OBJECT_SIZE] = mem
+            .get_vec(pointer.address(), PY_STRING_OBJECT_SIZE)?
+            .try_into()
+            .map_err(|_| Error::Decode)?;
+        let object: bindings::PyStringObject = unsafe { std::mem::transmute(b) };
+
+        Ok(Self {
+            me: pointer,
+            object,
+            _interp: PhantomData,
+        })
+    }
+}
+
+impl<I: Interpreter<VarObject = PyVarObject<I>>> StringObject<I> for PySmallStringObject<I> {
+    fn to_var_object(&self) -> I::VarObject {
+        PyVarObject {
+            me: self.me,
+            object: bindings::PyVarObject {
+                ob_refcnt: self.object.ob_refcnt,
+                ob_type: self.object.ob_type,
+                ob_size: self.object.ob_size,
+            },
+            _interp: std::marker::PhantomData,
+        }
+    }
+
+    /// Strings are NUL-terminated, which tells the layouts apart: the byte at
+    /// `ob_size` past the start of the bytes must be 0. On the standard layout
+    /// the 4 bytes before `ob_sval` are `ob_sstate`, which is 0, 1 or 2; that
+    /// is checked too, and wins if both layouts look plausible, as they do for
+    /// short strings.
+    fn read_bytes(&self, mem: &(impl Memory + ?Sized)) -> Result<Vec<u8>> {
+        const SHIFT: usize = 4;
+
+        let size = item_count(self.object.ob_size)?;
+        if size > 2_000 {
+            return Err(Error::SizeError);
+        }
+        let small = offset_pointer(
+            self.me,
+            offset_of!(bindings::PyStringObject, ob_sval) - SHIFT,
+        )?;
+
+        // The standard layout's terminator lies past the end of small strings,
+        // possibly in unmapped memory.
+        let mut bytes = match mem.get_vec(small.address(), size + 1 + SHIFT) {
+            Ok(bytes) => bytes,
+            Err(_) => mem.get_vec(small.address(), size + 1)?,
+        };
+        let sstate = bytes
+            .get(..SHIFT)
+            .and_then(|sstate| sstate.try_into().ok())
+            .map(i32::from_le_bytes);
+        if bytes.get(size + SHIFT) == Some(&0) && sstate.is_some_and(|s| (0..=2).contains(&s)) {
+            tracing::debug!("str at {:#x} has the standard layout", self.me.address());
+            bytes.drain(..SHIFT);
+        } else if bytes.get(size) == Some(&0) {
+            tracing::debug!("str at {:#x} has the small layout", self.me.address());
+        } else {
+            return Err(Error::Decode);
+        }
+        bytes.truncate(size);
+
+        Ok(bytes)
+    }
+}
+
+#[derive(Copy, Clone)]
+pub struct PyUnicodeObject<I> {
+    me: Pointer,
+    object: python27_sys::PyUnicodeObject,
+    _interp: PhantomData<I>,
+}
+
+impl<I> std::fmt::Debug for PyUnicodeObject<I> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PyUnicodeObject")
+            .field("me", &self.me)
+            .finish()
+    }
+}
 
 pub const PY_UNICODE_OBJECT_SIZE: usize = std::mem::size_of::<python27_sys::PyUnicodeObject>();
 
+/// The size in bytes of a `Py_UNICODE` code unit: 2 if the `python27-sys`
+/// this crate is built against is configured for UCS-2 and 4 for UCS-4. It is
+/// not read from the target, so a target built with the other width is
+/// misread.
+pub const PY_UNICODE_SIZE: usize = std::mem::size_of::<python27_sys::Py_UNICODE>();
+
 impl<I> PyUnicodeObject<I> {
     pub fn size(&self) -> isize {
         self.object.length
@@ -682,15 +1365,16 @@ impl<I> PyUnicodeObject<I> {
 }
 
 impl<I> TryDeref for PyUnicodeObject<I> {
-    fn try_deref(mem: &impl Memory, pointer: Pointer) -> Result<Self> {
+    fn try_deref(mem: &(impl Memory + ?Sized), pointer: Pointer) -> Result<Self> {
         let b: [u8; PY_UNICODE_OBJECT_SIZE] = mem
             .get_vec(pointer.address(), PY_UNICODE_OBJECT_SIZE)?
             .try_into()
-            .expect("const size");
+            .map_err(|_| Error::Decode)?;
+        let object: python27_sys::PyUnicodeObject = unsafe { std::mem::transmute(b) };
 
         Ok(Self {
             me: pointer,
-            object: unsafe { std::mem::transmute(b) },
+            object,
             _interp: PhantomData,
         })
     }
@@ -708,27 +1392,51 @@ impl<I: Interpreter<Object = PyObject<I>>> UnicodeObject<I> for PyUnicodeObject<
         }
     }
 
-    fn read_bytes(&self, mem: &impl Memory) -> Result<Vec<u8>> {
-        let length = self.object.length as usize;
+    /// The raw `Py_UNICODE` buffer: `length` code units of
+    /// `Py_UNICODE_SIZE` bytes each.
+    fn read_bytes(&self, mem: &(impl Memory + ?Sized)) -> Result<Vec<u8>> {
+        let length = item_count(self.object.length)?;
         if length > 2_000 {
             return Err(Error::SizeError);
         }
 
-        mem.get_vec(
-            // (&self.object.ob_sval as *const [i8; 1]) as usize,
-            self.object.data as usize,
-            length,
-        )
+        mem.get_vec(self.object.data as usize, length * PY_UNICODE_SIZE)
     }
 
-    fn read(&self, mem: &impl Memory) -> Result<String> {
-        let length = self.object.length as usize;
-        if length > 2_000 {
-            return Err(Error::SizeError);
-        }
-        let bytes = mem.get_u16_vec(self.object.data as usize, length)?;
+    fn read(&self, mem: &(impl Memory + ?Sized)) -> Result<String> {
+        Ok(decode_py_unicode(&self.read_bytes(mem)?))
+    }
+
+    /// Reads at most `max_len` code units, so unlike `read` this is not
+    /// limited to short strings.
+    fn read_prefix(&self, mem: &(impl Memory + ?Sized), max_len: usize) -> Result<(String, usize)> {
+        let length = item_count(self.object.length)?;
+        let size = length
+            .min(max_len)
+            .checked_mul(PY_UNICODE_SIZE)
+            .ok_or(Error::SizeError)?;
+        let bytes = mem.get_vec(self.object.data as usize, size)?;
+        Ok((decode_py_unicode(&bytes), length))
+    }
+}
 
-        Ok(String::from_utf16_lossy(&bytes))
+/// Decode a `Py_UNICODE` buffer.
+fn decode_py_unicode(bytes: &[u8]) -> String {
+    match PY_UNICODE_SIZE {
+        // UCS-2 builds store non-BMP characters as surrogate pairs.
+        2 => String::from_utf16_lossy(
+            &bytes
+                .chunks_exact(2)
+                .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+                .collect::<Vec<_>>(),
+        ),
+        _ => bytes
+            .chunks_exact(4)
+            .map(|unit| {
+                let unit = u32::from_le_bytes([unit[0], unit[1], unit[2], unit[3]]);
+                char::from_u32(unit).unwrap_or(char::REPLACEMENT_CHARACTER)
+            })
+            .collect(),
     }
 }
 
@@ -742,15 +1450,16 @@ pub struct PyTupleObject<I> {
 pub const PY_TUPLE_OBJECT_SIZE: usize = std::mem::size_of::<bindings::PyTupleObject>();
 
 impl<I> TryDeref for PyTupleObject<I> {
-    fn try_deref(mem: &impl Memory, pointer: Pointer) -> Result<Self> {
+    fn try_deref(mem: &(impl Memory + ?Sized), pointer: Pointer) -> Result<Self> {
         let b: [u8; PY_TUPLE_OBJECT_SIZE] = mem
             .get_vec(pointer.address(), PY_TUPLE_OBJECT_SIZE)?
             .try_into()
-            .expect("const size");
+            .map_err(|_| Error::Decode)?;
+        let object: bindings::PyTupleObject = unsafe { std::mem::transmute(b) };
 
         Ok(Self {
             me: pointer,
-            object: unsafe { std::mem::transmute(b) },
+            object,
             _interp: PhantomData,
         })
     }
@@ -771,11 +1480,31 @@ impl<I: Interpreter<Object = PyObject<I>, VarObject = PyVarObject<I>>> TupleObje
         }
     }
 
-    fn items<'a, M: Memory>(&self, mem: &'a M) -> TupleItems<'a, I, M> {
-        let tuple_pointer = self.me + offset_of!(bindings::PyTupleObject, ob_item);
-        let size = self.object.ob_size as usize;
+    fn len(&self) -> usize {
+        item_count(self.object.ob_size).unwrap_or(0)
+    }
+
+    fn items<'a, M: Memory + ?Sized>(&self, mem: &'a M) -> TupleItems<'a, I, M> {
+        let tuple_pointer = offset_pointer(self.me, offset_of!(bindings::PyTupleObject, ob_item));
+        match tuple_pointer
+            .and_then(|tuple_pointer| Ok((tuple_pointer, item_count(self.object.ob_size)?)))
+        {
+            Ok((tuple_pointer, size)) => TupleItems::new(mem, tuple_pointer, size),
+            Err(error) => TupleItems::error(mem, error),
+        }
+    }
 
-        TupleItems::new(mem, tuple_pointer, size)
+    fn inconsistent_with_type(&self, type_object: &I::TypeObject) -> bool {
+        let header = offset_of!(bindings::PyTupleObject, ob_item) as isize;
+        let item_size = std::mem::size_of::<*mut bindings::PyObject>() as isize;
+        type_object.tp_basicsize() != header
+            || type_object.tp_itemsize() != item_size
+            || self
+                .object
+                .ob_size
+                .checked_mul(item_size)
+                .and_then(|size| size.checked_add(header))
+                .is_none_or(|size| size < header)
     }
 }
 
@@ -789,15 +1518,16 @@ pub struct PyListObject<I> {
 pub const PY_LIST_OBJECT_SIZE: usize = std::mem::size_of::<bindings::PyListObject>();
 
 impl<I> TryDeref for PyListObject<I> {
-    fn try_deref(mem: &impl Memory, pointer: Pointer) -> Result<Self> {
+    fn try_deref(mem: &(impl Memory + ?Sized), pointer: Pointer) -> Result<Self> {
         let b: [u8; PY_LIST_OBJECT_SIZE] = mem
             .get_vec(pointer.address(), PY_LIST_OBJECT_SIZE)?
             .try_into()
-            .expect("const size");
+            .map_err(|_| Error::Decode)?;
+        let object: bindings::PyListObject = unsafe { std::mem::transmute(b) };
 
         Ok(Self {
             me: pointer,
-            object: unsafe { std::mem::transmute(b) },
+            object,
             _interp: PhantomData,
         })
     }
@@ -818,11 +1548,33 @@ impl<I: Interpreter<Object = PyObject<I>, VarObject = PyVarObject<I>>> ListObjec
         }
     }
 
-    fn items<'a, M: Memory>(&self, mem: &'a M) -> ListItems<'a, I, M> {
+    fn len(&self) -> usize {
+        item_count(self.object.ob_size).unwrap_or(0)
+    }
+
+    fn items<'a, M: Memory + ?Sized>(&self, mem: &'a M) -> ListItems<'a, I, M> {
         let list_pointer = Pointer::new(self.object.ob_item as usize);
-        let length = self.object.ob_size as usize;
+        match item_count(self.object.ob_size) {
+            Ok(length) if self.exceeds_allocated() => {
+                tracing::warn!(
+                    "list at {:#x} has {} items but room for only {}",
+                    self.me.address(),
+                    length,
+                    self.object.allocated
+                );
+                ListItems::new(mem, list_pointer, self.object.allocated as usize)
+            }
+            Ok(length) => ListItems::new(mem, list_pointer, length),
+            Err(error) => ListItems::error(mem, error),
+        }
+    }
+
+    fn allocated(&self) -> isize {
+        self.object.allocated
+    }
 
-        ListItems::new(mem, list_pointer, length)
+    fn exceeds_allocated(&self) -> bool {
+        self.object.allocated >= 0 && self.object.ob_size > self.object.allocated
     }
 }
 
@@ -855,12 +1607,74 @@ impl<I: Interpreter<Object = PyObject<I>>> DictEntry<I> for PyDictEntry<I> {
 pub struct PyDictObject<I> {
     me: Pointer,
     object: bindings::PyDictObject,
+    /// The size of a word on the target, which the fields of `object` have
+    /// been widened or narrowed from.
+    word_size: usize,
     _interp: PhantomData<I>,
 }
 
 pub const PY_DICT_OBJECT_SIZE: usize = std::mem::size_of::<bindings::PyDictObject>();
 
 impl<I> PyDictObject<I> {
+    /// Dereference a dict on a target with `word_size`-byte words, such as a
+    /// 32-bit process read from a 64-bit host. [`TryDeref`] assumes the
+    /// host's word size.
+    ///
+    /// Every field of `PyDictObject`, including those of the entries in
+    /// `ma_smalltable`, is one word, so its layout on the target is that of
+    /// the host bindings with each word resized.
+    pub fn try_deref_with_word_size(
+        mem: &(impl Memory + ?Sized),
+        pointer: Pointer,
+        word_size: usize,
+    ) -> Result<Self> {
+        if word_size == PY_SIZE_T {
+            return Self::try_deref(mem, pointer);
+        }
+
+        let words = read_words(
+            mem,
+            pointer.address(),
+            PY_DICT_OBJECT_SIZE / PY_SIZE_T,
+            word_size,
+        )?;
+        let signed = |index: usize| signed_word(words[index], word_size);
+        let smalltable = offset_of!(bindings::PyDictObject, ma_smalltable) / PY_SIZE_T;
+        let entry = |slot: usize| {
+            let index = smalltable + 3 * slot;
+            bindings::PyDictEntry {
+                me_hash: signed(index),
+                me_key: words[index + 1] as *mut _,
+                me_value: words[index + 2] as *mut _,
+            }
+        };
+        let object = bindings::PyDictObject {
+            ob_refcnt: signed(0),
+            ob_type: words[1] as *mut _,
+            ma_fill: signed(2),
+            ma_used: signed(3),
+            ma_mask: signed(4),
+            ma_table: words[5] as *mut _,
+            // Never called, and a target function pointer is meaningless on
+            // the host anyway.
+            ma_lookup: None,
+            ma_smalltable: std::array::from_fn(entry),
+        };
+
+        Ok(Self {
+            me: pointer,
+            object,
+            word_size,
+            _interp: PhantomData,
+        })
+    }
+
+    /// The offset on the target of the field at `host_offset` in the host
+    /// bindings.
+    fn target_offset(&self, host_offset: usize) -> usize {
+        host_offset / PY_SIZE_T * self.word_size
+    }
+
     pub fn fill(&self) -> isize {
         self.object.ma_fill
     }
@@ -872,18 +1686,80 @@ impl<I> PyDictObject<I> {
     pub fn mask(&self) -> isize {
         self.object.ma_mask
     }
+
+    /// Whether `ma_table` points into the dict object itself, i.e. at the
+    /// embedded `ma_smalltable` that dicts of up to 5 keys use.
+    ///
+    /// A table pointer into the object anywhere but at `ma_smalltable` is
+    /// treated as embedded too, and rejected when reading the entries.
+    pub fn table_is_embedded(&self) -> bool {
+        let table = self.object.ma_table as usize;
+        let me = self.me.address();
+        table
+            .checked_sub(me)
+            .is_some_and(|offset| offset < self.target_offset(PY_DICT_OBJECT_SIZE))
+    }
+
+    /// The slots among the first `max_slots` of the table that hold an
+    /// entry, and whether the table has more slots than that.
+    fn active_slots(
+        &self,
+        mem: &(impl Memory + ?Sized),
+        max_slots: usize,
+    ) -> Result<(Vec<DictSlot>, bool)> {
+        let table_addr: Pointer = Pointer::new(self.object.ma_table as usize);
+
+        let mask: usize = self.mask().try_into().map_err(|_| Error::Decode)?;
+        let mut slots = mask.checked_add(1).ok_or(Error::Decode)?;
+        let capped = slots > max_slots;
+        if capped {
+            tracing::warn!("dict has {} slots, reading only {}", slots, max_slots);
+            slots = max_slots;
+        }
+
+        let table: Vec<DictSlot> = if self.table_is_embedded() {
+            // Already read along with the dict itself.
+            let smalltable = offset_pointer(
+                self.me,
+                self.target_offset(offset_of!(bindings::PyDictObject, ma_smalltable)),
+            )?;
+            if table_addr != smalltable || slots > self.object.ma_smalltable.len() {
+                return Err(Error::Decode);
+            }
+            self.object.ma_smalltable[..slots]
+                .iter()
+                .map(|entry| DictSlot {
+                    hash: entry.me_hash as usize,
+                    key: entry.me_key as usize,
+                    value: entry.me_value as usize,
+                })
+                .collect()
+        } else {
+            read_dict_slots(mem, table_addr.address(), slots, self.word_size)?
+        };
+
+        // Empty slots have a NULL key, and deleted slots a NULL value.
+        let active = table
+            .into_iter()
+            .filter(|slot| slot.key != 0 && slot.value != 0)
+            .collect();
+
+        Ok((active, capped))
+    }
 }
 
 impl<I> TryDeref for PyDictObject<I> {
-    fn try_deref(mem: &impl Memory, pointer: Pointer) -> Result<Self> {
+    fn try_deref(mem: &(impl Memory + ?Sized), pointer: Pointer) -> Result<Self> {
         let b: [u8; PY_DICT_OBJECT_SIZE] = mem
             .get_vec(pointer.address(), PY_DICT_OBJECT_SIZE)?
             .try_into()
-            .expect("const size");
+            .map_err(|_| Error::Decode)?;
+        let object: bindings::PyDictObject = unsafe { std::mem::transmute(b) };
 
         Ok(Self {
             me: pointer,
-            object: unsafe { std::mem::transmute(b) },
+            object,
+            word_size: PY_SIZE_T,
             _interp: PhantomData,
         })
     }
@@ -903,71 +1779,127 @@ impl<I: Interpreter<Object = PyObject<I>, DictEntry = PyDictEntry<I>>> DictObjec
         }
     }
 
-    fn entries(&self, mem: &impl Memory) -> Result<Vec<I::DictEntry>> {
-        const ENTRY_SIZE: usize = std::mem::size_of::<bindings::PyDictEntry>();
+    fn entries(
+        &self,
+        mem: &(impl Memory + ?Sized),
+        max_slots: usize,
+    ) -> Result<(Vec<I::DictEntry>, bool)> {
+        let (table, capped) = self.active_slots(mem, max_slots)?;
+        let entries = table
+            .into_iter()
+            .map(|entry| {
+                Ok(PyDictEntry {
+                    hash: entry.hash,
+                    key: Pointer::new(entry.key).try_deref_me(mem)?,
+                    value: Pointer::new(entry.value).try_deref_me(mem)?,
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok((entries, capped))
+    }
 
-        let table_addr: Pointer = Pointer::new(self.object.ma_table as usize);
+    fn keys_iter<'a, M: Memory + ?Sized>(
+        &self,
+        mem: &'a M,
+        max_slots: usize,
+    ) -> DictItems<'a, I, M> {
+        match self.active_slots(mem, max_slots) {
+            Ok((table, capped)) => DictItems::new(
+                mem,
+                table.iter().map(|entry| Pointer::new(entry.key)).collect(),
+                capped,
+            ),
+            Err(error) => DictItems::error(mem, error),
+        }
+    }
 
-        let mut slots = self.mask() as usize + 1;
-        if slots >= 10_000 {
-            tracing::warn!("dict too big");
-            slots = 10_000;
+    fn values_iter<'a, M: Memory + ?Sized>(
+        &self,
+        mem: &'a M,
+        max_slots: usize,
+    ) -> DictItems<'a, I, M> {
+        match self.active_slots(mem, max_slots) {
+            Ok((table, capped)) => DictItems::new(
+                mem,
+                table
+                    .iter()
+                    .map(|entry| Pointer::new(entry.value))
+                    .collect(),
+                capped,
+            ),
+            Err(error) => DictItems::error(mem, error),
         }
+    }
+}
 
-        let mut entries = Vec::new();
-        for slot in 0..slots {
-            let pointer = table_addr + slot * ENTRY_SIZE;
+#[derive(Copy, Clone, Debug)]
+pub struct PyMethodWrapperObject<I> {
+    me: Pointer,
+    object: bindings::PyMethodWrapperObject,
+    _interp: PhantomData<I>,
+}
 
-            let b: [u8; ENTRY_SIZE] = mem
-                .get_vec(pointer.address(), ENTRY_SIZE)?
-                .try_into()
-                .expect("const size");
+impl<I> TryDeref for PyMethodWrapperObject<I> {
+    fn try_deref(mem: &(impl Memory + ?Sized), pointer: Pointer) -> Result<Self> {
+        let object: bindings::PyMethodWrapperObject = mem.read_struct(pointer.address())?;
 
-            let entry: bindings::PyDictEntry = unsafe { std::mem::transmute(b) };
+        Ok(Self {
+            me: pointer,
+            object,
+            _interp: PhantomData,
+        })
+    }
+}
 
-            let key_pointer = Pointer::new(entry.me_key as usize);
-            let value_pointer = Pointer::new(entry.me_value as usize);
+impl<I: Interpreter<Object = PyObject<I>>> MethodWrapperObject<I> for PyMethodWrapperObject<I> {
+    fn to_object(&self) -> I::Object {
+        PyObject {
+            me: self.me,
+            object: bindings::PyObject {
+                ob_refcnt: self.object.ob_refcnt,
+                ob_type: self.object.ob_type,
+            },
+            _interp: PhantomData,
+        }
+    }
 
-            if key_pointer.null() || value_pointer.null() {
-                continue;
-            }
+    fn callable(&self, mem: &(impl Memory + ?Sized)) -> Result<I::Object> {
+        Pointer::new(self.object.callable as usize).try_deref_me(mem)
+    }
 
-            entries.push(PyDictEntry {
-                hash: entry.me_hash as usize,
-                key: key_pointer.try_deref_me(mem)?,
-                value: value_pointer.try_deref_me(mem)?,
-            });
+    fn callable_name(&self, mem: &(impl Memory + ?Sized)) -> Result<Option<String>> {
+        let callable = self.callable(mem)?;
+        if callable.ob_type(mem)?.name() != "function" {
+            return Ok(None);
         }
 
-        Ok(entries)
+        let function: bindings::PyFunctionObject = mem.read_struct(callable.me().address())?;
+        let name: I::StringObject = Pointer::new(function.func_name as usize).try_deref_me(mem)?;
+        Ok(Some(name.read(mem)?))
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-pub struct PyBoolObject<I> {
+#[derive(Copy, Clone, Debug)]
+pub struct PyMmapObject<I> {
     me: Pointer,
-    object: bindings::PyIntObject,
+    object: bindings::PyMmapObject,
     _interp: PhantomData<I>,
 }
 
-pub const PY_BOOL_OBJECT_SIZE: usize = std::mem::size_of::<bindings::PyIntObject>();
-
-impl<I> TryDeref for PyBoolObject<I> {
-    fn try_deref(mem: &impl Memory, pointer: Pointer) -> Result<Self> {
-        let b: [u8; PY_BOOL_OBJECT_SIZE] = mem
-            .get_vec(pointer.address(), PY_BOOL_OBJECT_SIZE)?
-            .try_into()
-            .expect("const size");
+impl<I> TryDeref for PyMmapObject<I> {
+    fn try_deref(mem: &(impl Memory + ?Sized), pointer: Pointer) -> Result<Self> {
+        let object: bindings::PyMmapObject = mem.read_struct(pointer.address())?;
 
         Ok(Self {
             me: pointer,
-            object: unsafe { std::mem::transmute(b) },
+            object,
             _interp: PhantomData,
         })
     }
 }
 
-impl<I: Interpreter<Object = PyObject<I>>> BoolObject<I> for PyBoolObject<I> {
+impl<I: Interpreter<Object = PyObject<I>>> MmapObject<I> for PyMmapObject<I> {
     fn to_object(&self) -> I::Object {
         PyObject {
             me: self.me,
@@ -975,40 +1907,86 @@ impl<I: Interpreter<Object = PyObject<I>>> BoolObject<I> for PyBoolObject<I> {
                 ob_refcnt: self.object.ob_refcnt,
                 ob_type: self.object.ob_type,
             },
-            _interp: std::marker::PhantomData,
+            _interp: PhantomData,
         }
     }
 
-    fn value(&self) -> bool {
-        self.object.ob_ival != 0
+    fn size(&self) -> usize {
+        self.object.size
+    }
+
+    fn pos(&self) -> usize {
+        self.object.pos
+    }
+
+    fn fd(&self) -> i32 {
+        self.object.fd
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-pub struct PyIntObject<I> {
+#[derive(Copy, Clone, Debug)]
+pub struct PyWrapperDescriptorObject<I> {
     me: Pointer,
-    object: bindings::PyIntObject,
+    object: bindings::PyWrapperDescrObject,
     _interp: PhantomData<I>,
 }
 
-pub const PY_INT_OBJECT_SIZE: usize = std::mem::size_of::<bindings::PyIntObject>();
+impl<I> TryDeref for PyWrapperDescriptorObject<I> {
+    fn try_deref(mem: &(impl Memory + ?Sized), pointer: Pointer) -> Result<Self> {
+        let object: bindings::PyWrapperDescrObject = mem.read_struct(pointer.address())?;
 
-impl<I> TryDeref for PyIntObject<I> {
-    fn try_deref(mem: &impl Memory, pointer: Pointer) -> Result<Self> {
-        let b: [u8; PY_INT_OBJECT_SIZE] = mem
-            .get_vec(pointer.address(), PY_INT_OBJECT_SIZE)?
-            .try_into()
-            .expect("const size");
+        Ok(Self {
+            me: pointer,
+            object,
+            _interp: PhantomData,
+        })
+    }
+}
+
+impl<I: Interpreter<Object = PyObject<I>>> WrapperDescriptorObject<I>
+    for PyWrapperDescriptorObject<I>
+{
+    fn to_object(&self) -> I::Object {
+        PyObject {
+            me: self.me,
+            object: bindings::PyObject {
+                ob_refcnt: self.object.ob_refcnt,
+                ob_type: self.object.ob_type,
+            },
+            _interp: PhantomData,
+        }
+    }
+
+    fn owner(&self, mem: &(impl Memory + ?Sized)) -> Result<I::Object> {
+        Pointer::new(self.object.d_type as usize).try_deref_me(mem)
+    }
+
+    fn name(&self, mem: &(impl Memory + ?Sized)) -> Result<String> {
+        let name: I::StringObject = Pointer::new(self.object.d_name as usize).try_deref_me(mem)?;
+        name.read(mem)
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct PySlotWrapperObject<I> {
+    me: Pointer,
+    object: bindings::wrapperobject,
+    _interp: PhantomData<I>,
+}
+
+impl<I> TryDeref for PySlotWrapperObject<I> {
+    fn try_deref(mem: &(impl Memory + ?Sized), pointer: Pointer) -> Result<Self> {
+        let object: bindings::wrapperobject = mem.read_struct(pointer.address())?;
 
         Ok(Self {
             me: pointer,
-            object: unsafe { std::mem::transmute(b) },
+            object,
             _interp: PhantomData,
         })
     }
 }
 
-impl<I: Interpreter<Object = PyObject<I>>> IntObject<I> for PyIntObject<I> {
+impl<I: Interpreter<Object = PyObject<I>>> SlotWrapperObject<I> for PySlotWrapperObject<I> {
     fn to_object(&self) -> I::Object {
         PyObject {
             me: self.me,
@@ -1016,139 +1994,3452 @@ impl<I: Interpreter<Object = PyObject<I>>> IntObject<I> for PyIntObject<I> {
                 ob_refcnt: self.object.ob_refcnt,
                 ob_type: self.object.ob_type,
             },
-            _interp: std::marker::PhantomData,
+            _interp: PhantomData,
         }
     }
 
-    fn read(&self, _mem: &impl Memory) -> Result<BigInt> {
-        Ok(self.object.ob_ival.into())
+    fn descriptor(&self, mem: &(impl Memory + ?Sized)) -> Result<I::Object> {
+        Pointer::new(self.object.descr as usize).try_deref_me(mem)
+    }
+
+    fn bound_self(&self, mem: &(impl Memory + ?Sized)) -> Result<I::Object> {
+        Pointer::new(self.object.self_ as usize).try_deref_me(mem)
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-pub struct PyFloatObject<I> {
+#[derive(Copy, Clone)]
+pub struct PyCodeObject<I> {
     me: Pointer,
-    object: bindings::PyFloatObject,
+    object: python27_sys::PyCodeObject,
     _interp: PhantomData<I>,
 }
 
-pub const PY_FLOAT_OBJECT_SIZE: usize = std::mem::size_of::<bindings::PyFloatObject>();
+impl<I> std::fmt::Debug for PyCodeObject<I> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PyCodeObject")
+            .field("me", &self.me)
+            .field("co_firstlineno", &self.object.co_firstlineno)
+            .finish()
+    }
+}
 
-impl<I> TryDeref for PyFloatObject<I> {
-    fn try_deref(mem: &impl Memory, pointer: Pointer) -> Result<Self> {
-        let b: [u8; PY_FLOAT_OBJECT_SIZE] = mem
-            .get_vec(pointer.address(), PY_FLOAT_OBJECT_SIZE)?
+pub const PY_CODE_OBJECT_SIZE: usize = std::mem::size_of::<python27_sys::PyCodeObject>();
+
+impl<I> TryDeref for PyCodeObject<I> {
+    fn try_deref(mem: &(impl Memory + ?Sized), pointer: Pointer) -> Result<Self> {
+        let b: [u8; PY_CODE_OBJECT_SIZE] = mem
+            .get_vec(pointer.address(), PY_CODE_OBJECT_SIZE)?
             .try_into()
-            .expect("const size");
+            .map_err(|_| Error::Decode)?;
+        let object: python27_sys::PyCodeObject = unsafe { std::mem::transmute(b) };
 
         Ok(Self {
             me: pointer,
-            object: unsafe { std::mem::transmute(b) },
+            object,
             _interp: PhantomData,
         })
     }
 }
 
-impl<I: Interpreter<Object = PyObject<I>>> FloatObject<I> for PyFloatObject<I> {
+impl<I: Interpreter<Object = PyObject<I>>> CodeObject<I> for PyCodeObject<I> {
     fn to_object(&self) -> I::Object {
         PyObject {
             me: self.me,
             object: bindings::PyObject {
                 ob_refcnt: self.object.ob_refcnt,
-                ob_type: self.object.ob_type,
+                ob_type: self.object.ob_type as *mut bindings::_typeobject,
             },
-            _interp: std::marker::PhantomData,
+            _interp: PhantomData,
         }
     }
 
-    fn value(&self) -> f64 {
-        self.object.ob_fval
+    fn name(&self, mem: &(impl Memory + ?Sized)) -> Result<String> {
+        let name: I::StringObject = Pointer::new(self.object.co_name as usize).try_deref_me(mem)?;
+        name.read(mem)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use std::io::{BufRead, BufReader};
-    use std::path::PathBuf;
-    use std::process::{Command, Stdio};
+    fn filename(&self, mem: &(impl Memory + ?Sized)) -> Result<String> {
+        let filename: I::StringObject =
+            Pointer::new(self.object.co_filename as usize).try_deref_me(mem)?;
+        filename.read(mem)
+    }
 
-    use anyhow::bail;
+    fn firstlineno(&self) -> i32 {
+        self.object.co_firstlineno
+    }
 
-    use super::*;
-    use crate::walker::{walk, DataPointer, DecodedData};
+    fn consts(&self, mem: &(impl Memory + ?Sized)) -> Result<I::Object> {
+        Pointer::new(self.object.co_consts as usize).try_deref_me(mem)
+    }
 
-    #[test]
-    fn works() -> std::result::Result<(), anyhow::Error> {
-        let child = Command::new(
-            [env!("CARGO_MANIFEST_DIR"), "test-programs", "python27.py"]
-                .iter()
-                .collect::<PathBuf>(),
-        )
-        .stdin(Stdio::piped())
-        .stdout(Stdio::piped())
-        .stderr(Stdio::null())
-        .spawn()?;
+    fn names(&self, mem: &(impl Memory + ?Sized)) -> Result<I::Object> {
+        Pointer::new(self.object.co_names as usize).try_deref_me(mem)
+    }
 
-        let pid = child.id();
-        let stdout = child.stdout.unwrap();
+    fn varnames(&self, mem: &(impl Memory + ?Sized)) -> Result<I::Object> {
+        Pointer::new(self.object.co_varnames as usize).try_deref_me(mem)
+    }
+}
 
-        let mut line = String::new();
-        BufReader::new(stdout).read_line(&mut line)?;
-        let pointer: usize = line.trim().parse().expect("memory address");
+#[derive(Copy, Clone, Debug)]
+pub struct PyIndexedIteratorObject<I> {
+    me: Pointer,
+    object: bindings::reversedobject,
+    _interp: PhantomData<I>,
+}
 
-        let mem = crate::connect(pid as i32)?;
-        let ptr = Pointer::new(pointer);
+impl<I> TryDeref for PyIndexedIteratorObject<I> {
+    fn try_deref(mem: &(impl Memory + ?Sized), pointer: Pointer) -> Result<Self> {
+        let object: bindings::reversedobject = mem.read_struct(pointer.address())?;
 
-        let graph = walk::<Cpython2_7, _>(&mem, ptr);
+        Ok(Self {
+            me: pointer,
+            object,
+            _interp: PhantomData,
+        })
+    }
+}
 
-        if let Some(DecodedData::List(list)) = graph.get(&DataPointer(pointer)) {
-            assert_eq!(list.len(), 4);
-            match graph.get(&list[0]) {
-                Some(&DecodedData::String(ref str)) => assert_eq!(str, "hello world"),
-                _ => bail!("Expected a string"),
+impl<I: Interpreter<Object = PyObject<I>>> IndexedIteratorObject<I> for PyIndexedIteratorObject<I> {
+    fn to_object(&self) -> I::Object {
+        PyObject {
+            me: self.me,
+            object: bindings::PyObject {
+                ob_refcnt: self.object.ob_refcnt,
+                ob_type: self.object.ob_type,
+            },
+            _interp: PhantomData,
+        }
+    }
+
+    fn index(&self) -> isize {
+        self.object.index
+    }
+
+    fn source(&self, mem: &(impl Memory + ?Sized)) -> Result<Option<I::Object>> {
+        let seq = Pointer::new(self.object.seq as usize);
+        if seq.null() {
+            Ok(None)
+        } else {
+            Ok(Some(seq.try_deref_me(mem)?))
+        }
+    }
+}
+
+/// A `collections.defaultdict`. Its `defdictobject` (Modules/_collectionsmodule.c)
+/// is a `PyDictObject` followed by the `default_factory` pointer.
+#[derive(Copy, Clone, Debug)]
+pub struct PyDefaultDictObject<I> {
+    dict: PyDictObject<I>,
+    default_factory: Pointer,
+}
+
+impl<I> TryDeref for PyDefaultDictObject<I> {
+    fn try_deref(mem: &(impl Memory + ?Sized), pointer: Pointer) -> Result<Self> {
+        let dict = PyDictObject::try_deref(mem, pointer)?;
+        let default_factory =
+            mem.get_usize(offset_pointer(pointer, PY_DICT_OBJECT_SIZE)?.address())?;
+
+        Ok(Self {
+            dict,
+            default_factory: Pointer::new(default_factory),
+        })
+    }
+}
+
+impl<I> DefaultDictObject<I> for PyDefaultDictObject<I>
+where
+    I: Interpreter<Object = PyObject<I>, DictObject = PyDictObject<I>, DictEntry = PyDictEntry<I>>,
+{
+    fn to_object(&self) -> I::Object {
+        self.dict.to_object()
+    }
+
+    fn dict(&self) -> &I::DictObject {
+        &self.dict
+    }
+
+    fn default_factory(&self, mem: &(impl Memory + ?Sized)) -> Result<Option<I::Object>> {
+        if self.default_factory.null() {
+            Ok(None)
+        } else {
+            Ok(Some(self.default_factory.try_deref_me(mem)?))
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct PySliceObject<I> {
+    me: Pointer,
+    object: bindings::PySliceObject,
+    _interp: PhantomData<I>,
+}
+
+impl<I> TryDeref for PySliceObject<I> {
+    fn try_deref(mem: &(impl Memory + ?Sized), pointer: Pointer) -> Result<Self> {
+        let object: bindings::PySliceObject = mem.read_struct(pointer.address())?;
+
+        Ok(Self {
+            me: pointer,
+            object,
+            _interp: PhantomData,
+        })
+    }
+}
+
+/// Dereference a slice bound, mapping `None` to `None`. The bounds of a
+/// `slice` are never NULL; omitted bounds are stored as `Py_None`.
+fn slice_bound<I: Interpreter<Object = PyObject<I>>>(
+    mem: &(impl Memory + ?Sized),
+    pointer: *mut bindings::PyObject,
+) -> Result<Option<I::Object>> {
+    let object: I::Object = Pointer::new(pointer as usize).try_deref_me(mem)?;
+    if object.ob_type(mem)?.name() == "NoneType" {
+        Ok(None)
+    } else {
+        Ok(Some(object))
+    }
+}
+
+impl<I: Interpreter<Object = PyObject<I>>> SliceObject<I> for PySliceObject<I> {
+    fn to_object(&self) -> I::Object {
+        PyObject {
+            me: self.me,
+            object: bindings::PyObject {
+                ob_refcnt: self.object.ob_refcnt,
+                ob_type: self.object.ob_type,
+            },
+            _interp: PhantomData,
+        }
+    }
+
+    fn start(&self, mem: &(impl Memory + ?Sized)) -> Result<Option<I::Object>> {
+        slice_bound::<I>(mem, self.object.start)
+    }
+
+    fn stop(&self, mem: &(impl Memory + ?Sized)) -> Result<Option<I::Object>> {
+        slice_bound::<I>(mem, self.object.stop)
+    }
+
+    fn step(&self, mem: &(impl Memory + ?Sized)) -> Result<Option<I::Object>> {
+        slice_bound::<I>(mem, self.object.step)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PyDateTimeObject<I> {
+    me: Pointer,
+    object: bindings::PyObject,
+    value: DateTimeValue,
+    tzinfo: Option<Pointer>,
+    _interp: PhantomData<I>,
+}
+
+impl<I> PyDateTimeObject<I> {
+    /// Dereference a `datetime.date` object.
+    pub fn try_deref_date(mem: &(impl Memory + ?Sized), pointer: Pointer) -> Result<Self> {
+        let object: bindings::PyDateTime_Date = mem.read_struct(pointer.address())?;
+        let data = object.data;
+
+        Ok(Self {
+            me: pointer,
+            object: bindings::PyObject {
+                ob_refcnt: object.ob_refcnt,
+                ob_type: object.ob_type,
+            },
+            value: DateTimeValue::Date {
+                year: u16::from_be_bytes([data[0], data[1]]),
+                month: data[2],
+                day: data[3],
+            },
+            tzinfo: None,
+            _interp: PhantomData,
+        })
+    }
+
+    /// Dereference a `datetime.datetime` object. Its `tzinfo` follows the
+    /// packed fields only if `hastzinfo` is set.
+    pub fn try_deref_datetime(mem: &(impl Memory + ?Sized), pointer: Pointer) -> Result<Self> {
+        let object: bindings::_PyDateTime_BaseDateTime = mem.read_struct(pointer.address())?;
+        let data = object.data;
+        let tzinfo = if object.hastzinfo != 0 {
+            let address = offset_pointer(
+                pointer,
+                std::mem::size_of::<bindings::_PyDateTime_BaseDateTime>(),
+            )?;
+            Some(Pointer::new(mem.get_usize(address.address())?)).filter(|tzinfo| !tzinfo.null())
+        } else {
+            None
+        };
+
+        Ok(Self {
+            me: pointer,
+            object: bindings::PyObject {
+                ob_refcnt: object.ob_refcnt,
+                ob_type: object.ob_type,
+            },
+            value: DateTimeValue::DateTime {
+                year: u16::from_be_bytes([data[0], data[1]]),
+                month: data[2],
+                day: data[3],
+                hour: data[4],
+                minute: data[5],
+                second: data[6],
+                microsecond: u32::from_be_bytes([0, data[7], data[8], data[9]]),
+            },
+            tzinfo,
+            _interp: PhantomData,
+        })
+    }
+
+    /// Dereference a `datetime.timedelta` object.
+    pub fn try_deref_timedelta(mem: &(impl Memory + ?Sized), pointer: Pointer) -> Result<Self> {
+        let object: bindings::PyDateTime_Delta = mem.read_struct(pointer.address())?;
+
+        Ok(Self {
+            me: pointer,
+            object: bindings::PyObject {
+                ob_refcnt: object.ob_refcnt,
+                ob_type: object.ob_type,
+            },
+            value: DateTimeValue::TimeDelta {
+                days: object.days,
+                seconds: object.seconds,
+                microseconds: object.microseconds,
+            },
+            tzinfo: None,
+            _interp: PhantomData,
+        })
+    }
+}
+
+impl<I: Interpreter<Object = PyObject<I>>> DateTimeObject<I> for PyDateTimeObject<I> {
+    fn to_object(&self) -> I::Object {
+        PyObject {
+            me: self.me,
+            object: self.object,
+            _interp: PhantomData,
+        }
+    }
+
+    fn value(&self) -> DateTimeValue {
+        self.value.clone()
+    }
+
+    fn tzinfo(&self, mem: &(impl Memory + ?Sized)) -> Result<Option<I::Object>> {
+        self.tzinfo
+            .map(|tzinfo| tzinfo.try_deref_me(mem))
+            .transpose()
+    }
+}
+
+/// The string `setobject.c` marks deleted set entries with.
+const SET_DUMMY_KEY: &str = "<dummy key>";
+
+#[derive(Debug, Clone, Copy)]
+pub struct PySetObject<I> {
+    me: Pointer,
+    object: bindings::PySetObject,
+    _interp: PhantomData<I>,
+}
+
+pub const PY_SET_OBJECT_SIZE: usize = std::mem::size_of::<bindings::PySetObject>();
+
+impl<I> PySetObject<I> {
+    pub fn fill(&self) -> isize {
+        self.object.fill
+    }
+
+    pub fn used(&self) -> isize {
+        self.object.used
+    }
+
+    pub fn mask(&self) -> isize {
+        self.object.mask
+    }
+}
+
+impl<I> TryDeref for PySetObject<I> {
+    fn try_deref(mem: &(impl Memory + ?Sized), pointer: Pointer) -> Result<Self> {
+        let b: [u8; PY_SET_OBJECT_SIZE] = mem
+            .get_vec(pointer.address(), PY_SET_OBJECT_SIZE)?
+            .try_into()
+            .map_err(|_| Error::Decode)?;
+        let object: bindings::PySetObject = unsafe { std::mem::transmute(b) };
+
+        Ok(Self {
+            me: pointer,
+            object,
+            _interp: PhantomData,
+        })
+    }
+}
+
+impl<I: Interpreter<Object = PyObject<I>>> SetObject<I> for PySetObject<I> {
+    fn to_object(&self) -> I::Object {
+        PyObject {
+            me: self.me,
+            object: bindings::PyObject {
+                ob_refcnt: self.object.ob_refcnt,
+                ob_type: self.object.ob_type,
+            },
+            _interp: PhantomData,
+        }
+    }
+
+    fn members(
+        &self,
+        mem: &(impl Memory + ?Sized),
+        max_slots: usize,
+    ) -> Result<(Vec<I::Object>, bool)> {
+        const ENTRY_SIZE: usize = std::mem::size_of::<bindings::setentry>();
+        let key_offset = offset_of!(bindings::setentry, key);
+
+        let table_addr: Pointer = Pointer::new(self.object.table as usize);
+
+        let mask: usize = self.mask().try_into().map_err(|_| Error::Decode)?;
+        let mut slots = mask.checked_add(1).ok_or(Error::Decode)?;
+        let capped = slots > max_slots;
+        if capped {
+            tracing::warn!("set has {} slots, reading only {}", slots, max_slots);
+            slots = max_slots;
+        }
+        // Fail rather than wrap around if the table address is corrupt.
+        slots
+            .checked_mul(ENTRY_SIZE)
+            .and_then(|size| table_addr.checked_add(size))
+            .ok_or(Error::Decode)?;
+
+        let mut keys = Vec::new();
+        for entry in mem.get_records::<ENTRY_SIZE>(table_addr.address(), slots)? {
+            let key = &entry[key_offset..key_offset + PY_SIZE_T];
+            let key_pointer = Pointer::new(usize::from_ne_bytes(
+                key.try_into().map_err(|_| Error::Decode)?,
+            ));
+            if !key_pointer.null() {
+                keys.push(key_pointer);
+            }
+        }
+
+        // Deleted entries point at a shared dummy string rather than NULL.
+        // They only need filtering out if there are any.
+        let has_dummies = keys.len() > self.used().max(0) as usize;
+        let mut members = Vec::with_capacity(keys.len());
+        for key_pointer in keys {
+            let key: I::Object = key_pointer.try_deref_me(mem)?;
+            if has_dummies && is_set_dummy::<I>(mem, &key)? {
+                continue;
+            }
+            members.push(key);
+        }
+
+        Ok((members, capped))
+    }
+}
+
+fn is_set_dummy<I: Interpreter<Object = PyObject<I>>>(
+    mem: &(impl Memory + ?Sized),
+    key: &I::Object,
+) -> Result<bool> {
+    if key.ob_type(mem)?.name() != "str" {
+        return Ok(false);
+    }
+    let string: I::StringObject = key.me().try_deref_me(mem)?;
+    Ok(string.read(mem)? == SET_DUMMY_KEY)
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PyCapsuleObject<I> {
+    me: Pointer,
+    object: bindings::PyCapsule,
+    _interp: PhantomData<I>,
+}
+
+impl<I> TryDeref for PyCapsuleObject<I> {
+    fn try_deref(mem: &(impl Memory + ?Sized), pointer: Pointer) -> Result<Self> {
+        let object: bindings::PyCapsule = mem.read_struct(pointer.address())?;
+
+        Ok(Self {
+            me: pointer,
+            object,
+            _interp: PhantomData,
+        })
+    }
+}
+
+impl<I: Interpreter<Object = PyObject<I>>> CapsuleObject<I> for PyCapsuleObject<I> {
+    fn to_object(&self) -> I::Object {
+        PyObject {
+            me: self.me,
+            object: bindings::PyObject {
+                ob_refcnt: self.object.ob_refcnt,
+                ob_type: self.object.ob_type,
+            },
+            _interp: PhantomData,
+        }
+    }
+
+    fn name(&self, mem: &(impl Memory + ?Sized)) -> Result<Option<String>> {
+        let name = Pointer::new(self.object.name as usize);
+        if name.null() {
+            Ok(None)
+        } else {
+            Ok(Some(name.deref_c_str(mem, Some(256))?))
+        }
+    }
+
+    fn pointer(&self) -> usize {
+        self.object.pointer as usize
+    }
+
+    fn context(&self) -> usize {
+        self.object.context as usize
+    }
+}
+
+/// Arrays with more elements than this fail to decode instead of being read in
+/// full, so that a corrupt `ob_size` cannot request an arbitrarily large read.
+pub const MAX_ARRAY_LEN: usize = 1 << 24;
+
+#[derive(Debug, Clone, Copy)]
+pub struct PyArrayObject<I> {
+    me: Pointer,
+    object: bindings::arrayobject,
+    _interp: PhantomData<I>,
+}
+
+impl<I> TryDeref for PyArrayObject<I> {
+    fn try_deref(mem: &(impl Memory + ?Sized), pointer: Pointer) -> Result<Self> {
+        let object: bindings::arrayobject = mem.read_struct(pointer.address())?;
+
+        Ok(Self {
+            me: pointer,
+            object,
+            _interp: PhantomData,
+        })
+    }
+}
+
+impl<I> PyArrayObject<I> {
+    fn len(&self) -> Result<usize> {
+        let len = item_count(self.object.ob_size)?;
+        if len > item_count(self.object.allocated)? {
+            return Err(Error::Decode);
+        }
+        Ok(len)
+    }
+
+    fn descr(&self, mem: &(impl Memory + ?Sized)) -> Result<bindings::arraydescr> {
+        mem.read_struct(Pointer::new(self.object.ob_descr as usize).address_checked()?)
+    }
+
+    fn read_items(&self, mem: &(impl Memory + ?Sized), count: usize) -> Result<ArrayValues> {
+        let descr = self.descr(mem)?;
+        let typecode = array_typecode(&descr)?;
+        let itemsize: usize = descr.itemsize.try_into().map_err(|_| Error::Decode)?;
+        // Empty arrays have a null `ob_item`.
+        let bytes = if count == 0 {
+            Vec::new()
+        } else {
+            mem.get_vec(
+                self.object.ob_item as usize,
+                count.checked_mul(itemsize).ok_or(Error::Decode)?,
+            )?
+        };
+        decode_array_items(typecode, itemsize, &bytes)
+    }
+}
+
+impl<I: Interpreter<Object = PyObject<I>, VarObject = PyVarObject<I>>> ArrayObject<I>
+    for PyArrayObject<I>
+{
+    fn to_var_object(&self) -> I::VarObject {
+        PyVarObject {
+            me: self.me,
+            object: bindings::PyVarObject {
+                ob_refcnt: self.object.ob_refcnt,
+                ob_type: self.object.ob_type,
+                ob_size: self.object.ob_size,
+            },
+            _interp: PhantomData,
+        }
+    }
+
+    fn typecode(&self, mem: &(impl Memory + ?Sized)) -> Result<char> {
+        array_typecode(&self.descr(mem)?)
+    }
+
+    fn read(&self, mem: &(impl Memory + ?Sized)) -> Result<ArrayValues> {
+        let len = self.len()?;
+        if len > MAX_ARRAY_LEN {
+            return Err(Error::Decode);
+        }
+        self.read_items(mem, len)
+    }
+
+    fn read_prefix(
+        &self,
+        mem: &(impl Memory + ?Sized),
+        max_len: usize,
+    ) -> Result<(ArrayValues, usize)> {
+        let len = self.len()?;
+        Ok((
+            self.read_items(mem, len.min(max_len).min(MAX_ARRAY_LEN))?,
+            len,
+        ))
+    }
+}
+
+fn array_typecode(descr: &bindings::arraydescr) -> Result<char> {
+    let typecode: u8 = descr.typecode.try_into().map_err(|_| Error::Decode)?;
+    Ok(char::from(typecode))
+}
+
+/// Decode the buffer of an array with the given typecode, checking that
+/// `itemsize` is the size of that typecode's C type.
+fn decode_array_items(typecode: char, itemsize: usize, bytes: &[u8]) -> Result<ArrayValues> {
+    macro_rules! items {
+        ($variant:ident, $from:path, $t:ty) => {{
+            if itemsize != std::mem::size_of::<$t>() {
+                return Err(Error::Decode);
+            }
+            ArrayValues::$variant(
+                bytes
+                    .chunks_exact(itemsize)
+                    .map(|item| {
+                        item.try_into()
+                            .map(|item| $from(<$t>::from_ne_bytes(item)))
+                            .map_err(|_| Error::Decode)
+                    })
+                    .collect::<Result<_>>()?,
+            )
+        }};
+    }
+    use std::os::raw::{c_long, c_ulong};
+
+    Ok(match (typecode, itemsize) {
+        ('c', _) | ('B', _) => items!(Int, BigInt::from, u8),
+        ('b', _) => items!(Int, BigInt::from, i8),
+        ('u', 2) => items!(Int, BigInt::from, u16),
+        ('u', _) => items!(Int, BigInt::from, u32),
+        ('h', _) => items!(Int, BigInt::from, i16),
+        ('H', _) => items!(Int, BigInt::from, u16),
+        ('i', _) => items!(Int, BigInt::from, i32),
+        ('I', _) => items!(Int, BigInt::from, u32),
+        ('l', _) => items!(Int, BigInt::from, c_long),
+        ('L', _) => items!(Int, BigInt::from, c_ulong),
+        ('f', _) => items!(Float, f64::from, f32),
+        ('d', _) => items!(Float, f64::from, f64),
+        _ => return Err(Error::Decode),
+    })
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PyDequeObject<I> {
+    me: Pointer,
+    object: bindings::dequeobject,
+    _interp: PhantomData<I>,
+}
+
+impl<I> TryDeref for PyDequeObject<I> {
+    fn try_deref(mem: &(impl Memory + ?Sized), pointer: Pointer) -> Result<Self> {
+        let object: bindings::dequeobject = mem.read_struct(pointer.address())?;
+
+        Ok(Self {
+            me: pointer,
+            object,
+            _interp: PhantomData,
+        })
+    }
+}
+
+impl<I: Interpreter<Object = PyObject<I>>> DequeObject<I> for PyDequeObject<I> {
+    fn to_object(&self) -> I::Object {
+        PyObject {
+            me: self.me,
+            object: bindings::PyObject {
+                ob_refcnt: self.object.ob_refcnt,
+                ob_type: self.object.ob_type,
+            },
+            _interp: PhantomData,
+        }
+    }
+
+    fn items(&self, mem: &(impl Memory + ?Sized)) -> Result<Vec<I::Object>> {
+        // Elements fill `leftblock` from `leftindex` and continue through the
+        // `rightlink`s; `len` bounds the walk, so the block list is never
+        // followed past the elements even if its links are corrupt. A block
+        // list that loops back on itself or needs more blocks than `len`
+        // elements can fill fails to decode.
+        let len = item_count(self.object.len)?;
+        let mut index = item_count(self.object.leftindex)?;
+        if len > MAX_ARRAY_LEN || index >= bindings::DEQUE_BLOCKLEN {
+            return Err(Error::Decode);
+        }
+        let max_blocks = (index + len) / bindings::DEQUE_BLOCKLEN + 1;
+
+        let mut block = Pointer::new(self.object.leftblock as usize);
+        let mut visited = std::collections::HashSet::new();
+        let mut items = Vec::with_capacity(len.min(10_000));
+        while items.len() < len {
+            if visited.len() == max_blocks || !visited.insert(block.address()) {
+                return Err(Error::Decode);
+            }
+            let data: bindings::block = mem.read_struct(block.address_checked()?)?;
+            for &item in &data.data[index..] {
+                if items.len() == len {
+                    break;
+                }
+                items.push(Pointer::new(item as usize).try_deref_me(mem)?);
+            }
+            block = Pointer::new(data.rightlink as usize);
+            index = 0;
+        }
+
+        Ok(items)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PyBoolObject<I> {
+    me: Pointer,
+    object: bindings::PyIntObject,
+    _interp: PhantomData<I>,
+}
+
+pub const PY_BOOL_OBJECT_SIZE: usize = std::mem::size_of::<bindings::PyIntObject>();
+
+impl<I> TryDeref for PyBoolObject<I> {
+    fn try_deref(mem: &(impl Memory + ?Sized), pointer: Pointer) -> Result<Self> {
+        let b: [u8; PY_BOOL_OBJECT_SIZE] = mem
+            .get_vec(pointer.address(), PY_BOOL_OBJECT_SIZE)?
+            .try_into()
+            .map_err(|_| Error::Decode)?;
+        let object: bindings::PyIntObject = unsafe { std::mem::transmute(b) };
+
+        Ok(Self {
+            me: pointer,
+            object,
+            _interp: PhantomData,
+        })
+    }
+}
+
+impl<I: Interpreter<Object = PyObject<I>>> BoolObject<I> for PyBoolObject<I> {
+    fn to_object(&self) -> I::Object {
+        PyObject {
+            me: self.me,
+            object: bindings::PyObject {
+                ob_refcnt: self.object.ob_refcnt,
+                ob_type: self.object.ob_type,
+            },
+            _interp: std::marker::PhantomData,
+        }
+    }
+
+    fn value(&self) -> bool {
+        self.object.ob_ival != 0
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PyIntObject<I> {
+    me: Pointer,
+    object: bindings::PyIntObject,
+    _interp: PhantomData<I>,
+}
+
+pub const PY_INT_OBJECT_SIZE: usize = std::mem::size_of::<bindings::PyIntObject>();
+
+impl<I> TryDeref for PyIntObject<I> {
+    fn try_deref(mem: &(impl Memory + ?Sized), pointer: Pointer) -> Result<Self> {
+        let b: [u8; PY_INT_OBJECT_SIZE] = mem
+            .get_vec(pointer.address(), PY_INT_OBJECT_SIZE)?
+            .try_into()
+            .map_err(|_| Error::Decode)?;
+        let object: bindings::PyIntObject = unsafe { std::mem::transmute(b) };
+
+        Ok(Self {
+            me: pointer,
+            object,
+            _interp: PhantomData,
+        })
+    }
+}
+
+impl<I: Interpreter<Object = PyObject<I>>> IntObject<I> for PyIntObject<I> {
+    fn to_object(&self) -> I::Object {
+        PyObject {
+            me: self.me,
+            object: bindings::PyObject {
+                ob_refcnt: self.object.ob_refcnt,
+                ob_type: self.object.ob_type,
+            },
+            _interp: std::marker::PhantomData,
+        }
+    }
+
+    fn read(&self, _mem: &(impl Memory + ?Sized)) -> Result<BigInt> {
+        Ok(self.object.ob_ival.into())
+    }
+}
+
+/// Longs with more digits than this fail to decode instead of being read in
+/// full, so that a corrupt `ob_size` cannot request an arbitrarily large read.
+pub const MAX_LONG_DIGITS: usize = 1 << 20;
+
+/// The bits of magnitude stored per `ob_digit` entry of a long whose type has
+/// `tp_itemsize`: CPython stores 15-bit digits in 2 bytes and 30-bit digits in
+/// 4, depending on how it was configured.
+fn long_digit_bits(tp_itemsize: isize) -> Result<u32> {
+    match tp_itemsize {
+        2 => Ok(15),
+        4 => Ok(30),
+        _ => Err(Error::Decode),
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PyLongObject<I> {
+    me: Pointer,
+    object: bindings::PyVarObject,
+    _interp: PhantomData<I>,
+}
+
+impl<I> TryDeref for PyLongObject<I> {
+    fn try_deref(mem: &(impl Memory + ?Sized), pointer: Pointer) -> Result<Self> {
+        let object: bindings::PyVarObject = mem.read_struct(pointer.address())?;
+
+        Ok(Self {
+            me: pointer,
+            object,
+            _interp: PhantomData,
+        })
+    }
+}
+
+impl<I: Interpreter<Object = PyObject<I>, VarObject = PyVarObject<I>>> LongObject<I>
+    for PyLongObject<I>
+{
+    fn to_var_object(&self) -> I::VarObject {
+        PyVarObject {
+            me: self.me,
+            object: self.object,
+            _interp: std::marker::PhantomData,
+        }
+    }
+
+    fn read(&self, mem: &(impl Memory + ?Sized), type_object: &I::TypeObject) -> Result<BigInt> {
+        // `ob_size` holds the digit count, negated for negative values.
+        let count = item_count(self.object.ob_size.checked_abs().ok_or(Error::Decode)?)?;
+        if count > MAX_LONG_DIGITS {
+            return Err(Error::Decode);
+        }
+        let digit_bits = long_digit_bits(type_object.tp_itemsize())?;
+        let address = offset_pointer(self.me, PY_VAR_OBJECT_SIZE)?.address();
+        let digits: Vec<u32> = if digit_bits == 15 {
+            mem.get_records::<2>(address, count)?
+                .into_iter()
+                .map(|digit| u16::from_ne_bytes(digit).into())
+                .collect()
+        } else {
+            mem.get_records::<4>(address, count)?
+                .into_iter()
+                .map(u32::from_ne_bytes)
+                .collect()
+        };
+
+        Ok(bigint_from_digits(
+            &digits,
+            digit_bits,
+            self.object.ob_size < 0,
+        ))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PyFloatObject<I> {
+    me: Pointer,
+    object: bindings::PyFloatObject,
+    _interp: PhantomData<I>,
+}
+
+pub const PY_FLOAT_OBJECT_SIZE: usize = std::mem::size_of::<bindings::PyFloatObject>();
+
+impl<I> TryDeref for PyFloatObject<I> {
+    fn try_deref(mem: &(impl Memory + ?Sized), pointer: Pointer) -> Result<Self> {
+        let b: [u8; PY_FLOAT_OBJECT_SIZE] = mem
+            .get_vec(pointer.address(), PY_FLOAT_OBJECT_SIZE)?
+            .try_into()
+            .map_err(|_| Error::Decode)?;
+        let object: bindings::PyFloatObject = unsafe { std::mem::transmute(b) };
+
+        Ok(Self {
+            me: pointer,
+            object,
+            _interp: PhantomData,
+        })
+    }
+}
+
+impl<I: Interpreter<Object = PyObject<I>>> FloatObject<I> for PyFloatObject<I> {
+    fn to_object(&self) -> I::Object {
+        PyObject {
+            me: self.me,
+            object: bindings::PyObject {
+                ob_refcnt: self.object.ob_refcnt,
+                ob_type: self.object.ob_type,
+            },
+            _interp: std::marker::PhantomData,
+        }
+    }
+
+    fn value(&self) -> f64 {
+        self.object.ob_fval
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PyPropertyObject<I> {
+    me: Pointer,
+    object: bindings::propertyobject,
+    _interp: PhantomData<I>,
+}
+
+pub const PY_PROPERTY_OBJECT_SIZE: usize = std::mem::size_of::<bindings::propertyobject>();
+
+impl<I> TryDeref for PyPropertyObject<I> {
+    fn try_deref(mem: &(impl Memory + ?Sized), pointer: Pointer) -> Result<Self> {
+        let b: [u8; PY_PROPERTY_OBJECT_SIZE] = mem
+            .get_vec(pointer.address(), PY_PROPERTY_OBJECT_SIZE)?
+            .try_into()
+            .map_err(|_| Error::Decode)?;
+
+        let object: bindings::propertyobject = unsafe { std::mem::transmute(b) };
+
+        Ok(Self {
+            me: pointer,
+            object,
+            _interp: PhantomData,
+        })
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct PyTracebackObject<I> {
+    me: Pointer,
+    object: python27_sys::PyTracebackObject,
+    _interp: PhantomData<I>,
+}
+
+impl<I> std::fmt::Debug for PyTracebackObject<I> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PyTracebackObject")
+            .field("me", &self.me)
+            .field("tb_lineno", &self.object.tb_lineno)
+            .finish()
+    }
+}
+
+pub const PY_TRACEBACK_OBJECT_SIZE: usize = std::mem::size_of::<python27_sys::PyTracebackObject>();
+
+impl<I> TryDeref for PyTracebackObject<I> {
+    fn try_deref(mem: &(impl Memory + ?Sized), pointer: Pointer) -> Result<Self> {
+        let b: [u8; PY_TRACEBACK_OBJECT_SIZE] = mem
+            .get_vec(pointer.address(), PY_TRACEBACK_OBJECT_SIZE)?
+            .try_into()
+            .map_err(|_| Error::Decode)?;
+        let object: python27_sys::PyTracebackObject = unsafe { std::mem::transmute(b) };
+
+        Ok(Self {
+            me: pointer,
+            object,
+            _interp: PhantomData,
+        })
+    }
+}
+
+impl<I: Interpreter<Object = PyObject<I>, TracebackObject = Self>> TracebackObject<I>
+    for PyTracebackObject<I>
+{
+    fn to_object(&self) -> I::Object {
+        PyObject {
+            me: self.me,
+            object: bindings::PyObject {
+                ob_refcnt: self.object.ob_refcnt,
+                ob_type: self.object.ob_type as *mut bindings::_typeobject,
+            },
+            _interp: std::marker::PhantomData,
+        }
+    }
+
+    fn next(&self, mem: &(impl Memory + ?Sized)) -> Result<Option<I::TracebackObject>> {
+        let next_ptr = Pointer::new(self.object.tb_next as usize);
+        if next_ptr.null() {
+            Ok(None)
+        } else {
+            Ok(Some(next_ptr.try_deref_me(mem)?))
+        }
+    }
+
+    fn frame(&self, mem: &(impl Memory + ?Sized)) -> Result<I::Object> {
+        Pointer::new(self.object.tb_frame as usize).try_deref_me(mem)
+    }
+
+    fn lineno(&self) -> i32 {
+        self.object.tb_lineno
+    }
+
+    fn lasti(&self) -> i32 {
+        self.object.tb_lasti
+    }
+}
+
+/// The namespace dict of the module object at `module`, e.g. `__main__`, so
+/// that the module's globals can be walked.
+///
+/// To find `__main__` in a target, start from the `sys.modules` dict (e.g.
+/// `PyInterpreterState::modules`, or any object known to reference it), walk
+/// it, and take the value of its `"__main__"` key. [`crate::walker::resolve_path`]
+/// can also follow a path of dict keys and attributes to it.
+pub fn main_globals<I, M>(mem: &M, module: Pointer) -> Result<Pointer>
+where
+    I: Interpreter,
+    M: Memory + ?Sized,
+{
+    let object: I::Object = module.try_deref_me(mem)?;
+    if object.ob_type(mem)?.name() != "module" {
+        return Err(Error::Decode);
+    }
+
+    let md_dict = mem.get_usize(
+        offset_pointer(module, offset_of!(bindings::PyModuleObject, md_dict))?.address(),
+    )?;
+    let dict = Pointer::new(md_dict);
+    if dict.null() {
+        return Err(Error::NullPointer);
+    }
+    Ok(dict)
+}
+
+/// Dereference an optional object pointer, mapping NULL to `None`.
+fn optional_object<I: Interpreter>(
+    mem: &(impl Memory + ?Sized),
+    pointer: *mut bindings::PyObject,
+) -> Result<Option<I::Object>> {
+    let pointer = Pointer::new(pointer as usize);
+    if pointer.null() {
+        Ok(None)
+    } else {
+        Ok(Some(pointer.try_deref_me(mem)?))
+    }
+}
+
+impl<I: Interpreter<Object = PyObject<I>>> PropertyObject<I> for PyPropertyObject<I> {
+    fn to_object(&self) -> I::Object {
+        PyObject {
+            me: self.me,
+            object: bindings::PyObject {
+                ob_refcnt: self.object.ob_refcnt,
+                ob_type: self.object.ob_type,
+            },
+            _interp: std::marker::PhantomData,
+        }
+    }
+
+    fn fget(&self, mem: &(impl Memory + ?Sized)) -> Result<Option<I::Object>> {
+        optional_object::<I>(mem, self.object.prop_get)
+    }
+
+    fn fset(&self, mem: &(impl Memory + ?Sized)) -> Result<Option<I::Object>> {
+        optional_object::<I>(mem, self.object.prop_set)
+    }
+
+    fn fdel(&self, mem: &(impl Memory + ?Sized)) -> Result<Option<I::Object>> {
+        optional_object::<I>(mem, self.object.prop_del)
+    }
+
+    fn doc(&self, mem: &(impl Memory + ?Sized)) -> Result<Option<I::Object>> {
+        optional_object::<I>(mem, self.object.prop_doc)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PyExceptionObject<I> {
+    me: Pointer,
+    object: bindings::PyBaseExceptionObject,
+    _interp: PhantomData<I>,
+}
+
+pub const PY_EXCEPTION_OBJECT_SIZE: usize = std::mem::size_of::<bindings::PyBaseExceptionObject>();
+
+impl<I> TryDeref for PyExceptionObject<I> {
+    fn try_deref(mem: &(impl Memory + ?Sized), pointer: Pointer) -> Result<Self> {
+        let b: [u8; PY_EXCEPTION_OBJECT_SIZE] = mem
+            .get_vec(pointer.address(), PY_EXCEPTION_OBJECT_SIZE)?
+            .try_into()
+            .map_err(|_| Error::Decode)?;
+
+        let object: bindings::PyBaseExceptionObject = unsafe { std::mem::transmute(b) };
+
+        Ok(Self {
+            me: pointer,
+            object,
+            _interp: PhantomData,
+        })
+    }
+}
+
+impl<I: Interpreter<Object = PyObject<I>>> ExceptionObject<I> for PyExceptionObject<I> {
+    fn to_object(&self) -> I::Object {
+        PyObject {
+            me: self.me,
+            object: bindings::PyObject {
+                ob_refcnt: self.object.ob_refcnt,
+                ob_type: self.object.ob_type,
+            },
+            _interp: std::marker::PhantomData,
+        }
+    }
+
+    fn args(&self, mem: &(impl Memory + ?Sized)) -> Result<I::TupleObject> {
+        Pointer::new(self.object.args as usize).try_deref_me(mem)
+    }
+
+    fn message(&self, mem: &(impl Memory + ?Sized)) -> Result<Option<I::Object>> {
+        optional_object::<I>(mem, self.object.message)
+    }
+
+    fn attributes(&self, mem: &(impl Memory + ?Sized)) -> Result<Option<I::DictObject>> {
+        let dict_ptr = Pointer::new(self.object.dict as usize);
+        if dict_ptr.null() {
+            Ok(None)
+        } else {
+            Ok(Some(dict_ptr.try_deref_me(mem)?))
+        }
+    }
+}
+
+/// Both `memoryview` objects and Python 2 `buffer` objects expose a buffer of
+/// another object; they are decoded into this common representation.
+#[derive(Debug, Clone, Copy)]
+pub struct PyMemoryViewObject<I> {
+    me: Pointer,
+    object: bindings::PyObject,
+    base: Pointer,
+    buf: Pointer,
+    len: isize,
+    readonly: bool,
+    format: Pointer,
+    _interp: PhantomData<I>,
+}
+
+pub const PY_MEMORY_VIEW_OBJECT_SIZE: usize = std::mem::size_of::<bindings::PyMemoryViewObject>();
+pub const PY_BUFFER_OBJECT_SIZE: usize = std::mem::size_of::<bindings::PyBufferObject>();
+
+impl<I> PyMemoryViewObject<I> {
+    /// Dereference a Python 2 `buffer` object.
+    pub fn try_deref_buffer(mem: &(impl Memory + ?Sized), pointer: Pointer) -> Result<Self> {
+        let b: [u8; PY_BUFFER_OBJECT_SIZE] = mem
+            .get_vec(pointer.address(), PY_BUFFER_OBJECT_SIZE)?
+            .try_into()
+            .map_err(|_| Error::Decode)?;
+
+        let object: bindings::PyBufferObject = unsafe { std::mem::transmute(b) };
+
+        Ok(Self {
+            me: pointer,
+            object: bindings::PyObject {
+                ob_refcnt: object.ob_refcnt,
+                ob_type: object.ob_type,
+            },
+            base: Pointer::new(object.b_base as usize),
+            buf: Pointer::new(object.b_ptr as usize),
+            len: object.b_size,
+            readonly: object.b_readonly != 0,
+            format: Pointer::new(0),
+            _interp: PhantomData,
+        })
+    }
+}
+
+impl<I> TryDeref for PyMemoryViewObject<I> {
+    fn try_deref(mem: &(impl Memory + ?Sized), pointer: Pointer) -> Result<Self> {
+        let b: [u8; PY_MEMORY_VIEW_OBJECT_SIZE] = mem
+            .get_vec(pointer.address(), PY_MEMORY_VIEW_OBJECT_SIZE)?
+            .try_into()
+            .map_err(|_| Error::Decode)?;
+
+        let object: bindings::PyMemoryViewObject = unsafe { std::mem::transmute(b) };
+
+        Ok(Self {
+            me: pointer,
+            object: bindings::PyObject {
+                ob_refcnt: object.ob_refcnt,
+                ob_type: object.ob_type,
+            },
+            base: Pointer::new(object.view.obj as usize),
+            buf: Pointer::new(object.view.buf as usize),
+            len: object.view.len,
+            readonly: object.view.readonly != 0,
+            format: Pointer::new(object.view.format as usize),
+            _interp: PhantomData,
+        })
+    }
+}
+
+impl<I: Interpreter<Object = PyObject<I>>> MemoryViewObject<I> for PyMemoryViewObject<I> {
+    fn to_object(&self) -> I::Object {
+        PyObject {
+            me: self.me,
+            object: self.object,
+            _interp: std::marker::PhantomData,
+        }
+    }
+
+    fn base(&self, mem: &(impl Memory + ?Sized)) -> Result<Option<I::Object>> {
+        if self.base.null() {
+            Ok(None)
+        } else {
+            Ok(Some(self.base.try_deref_me(mem)?))
+        }
+    }
+
+    fn buf(&self) -> Pointer {
+        self.buf
+    }
+
+    fn len(&self) -> isize {
+        self.len
+    }
+
+    fn readonly(&self) -> bool {
+        self.readonly
+    }
+
+    fn format(&self, mem: &(impl Memory + ?Sized)) -> Result<Option<String>> {
+        if self.format.null() {
+            Ok(None)
+        } else {
+            Ok(Some(self.format.deref_c_str(mem, Some(64))?))
+        }
+    }
+}
+
+/// The frame a thread is executing, read from its `PyThreadState`, or `None`
+/// if the thread is not running Python code.
+///
+/// The thread holding the GIL is found through the exported global
+/// `_PyThreadState_Current`, at the symbol's offset in the `python` binary (or
+/// `libpython2.7.so`) plus the address it is loaded at. It is NULL while the
+/// GIL is released, e.g. during blocking I/O, but the thread state keeps its
+/// frame; all thread states are linked from `interp_head->tstate_head` via
+/// `next`.
+pub fn current_frame<M: Memory + ?Sized>(
+    mem: &M,
+    thread_state: Pointer,
+) -> Result<Option<Pointer>> {
+    thread_state.address_checked()?;
+    let frame = offset_pointer(thread_state, offset_of!(python27_sys::PyThreadState, frame))?;
+    let frame = mem.get_usize(frame.address())?;
+
+    Ok(Some(Pointer::new(frame)).filter(|frame| !frame.null()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader};
+    use std::path::PathBuf;
+    use std::process::{Command, Stdio};
+
+    use anyhow::bail;
+
+    use super::*;
+    use crate::interpreter::DEFAULT_MAX_DICT_SLOTS;
+    use crate::memory::CountingMemory;
+    use crate::testing::{spawn_python27, FakeHeap};
+    use crate::walker::{
+        try_read_buffer, walk, walk_with_options, DataPointer, DecodedData, WalkOptions,
+    };
+
+    #[test]
+    #[allow(clippy::needless_borrowed_reference)]
+    fn works() -> std::result::Result<(), anyhow::Error> {
+        let child = Command::new(
+            [env!("CARGO_MANIFEST_DIR"), "test-programs", "python27.py"]
+                .iter()
+                .collect::<PathBuf>(),
+        )
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+        let pid = child.id();
+        let stdout = child.stdout.unwrap();
+
+        let mut line = String::new();
+        BufReader::new(stdout).read_line(&mut line)?;
+        let pointer: usize = line.trim().parse().expect("memory address");
+
+        let mem = crate::connect(pid as i32)?;
+        let ptr = Pointer::new(pointer);
+
+        let graph = walk::<Cpython2_7, _>(&mem, ptr);
+
+        if let Some(DecodedData::List(list)) = graph.get(&DataPointer(pointer)) {
+            assert_eq!(list.len(), 4);
+            match graph.get(&list[0]) {
+                Some(&DecodedData::String(ref str)) => assert_eq!(str, "hello world"),
+                _ => bail!("Expected a string"),
+            }
+            match graph.get(&list[1]) {
+                Some(&DecodedData::Int(ref int)) => assert_eq!(int, &num_bigint::BigInt::from(42)),
+                _ => bail!("Expected an int"),
+            }
+            match graph.get(&list[2]) {
+                Some(&DecodedData::Instance {
+                    ref instance_class_name,
+                    ref attributes,
+                    ..
+                }) => {
+                    assert_eq!(instance_class_name, "Something");
+                    match attributes
+                        .get("anything")
+                        .and_then(|pointer| graph.get(pointer))
+                    {
+                        Some(DecodedData::String(str)) => assert_eq!(str, "I'm here!"),
+                        _ => bail!("Expected an attribute"),
+                    }
+                }
+                _ => bail!("Expected an instance"),
+            }
+            match graph.get(&list[3]) {
+                Some(&DecodedData::Tuple(ref items)) => {
+                    assert_eq!(items.len(), 2);
+
+                    match graph.get(&items[0]) {
+                        Some(&DecodedData::Int(ref int)) => {
+                            assert_eq!(int, &num_bigint::BigInt::from(22))
+                        }
+                        _ => bail!("Expected an int"),
+                    }
+                    match graph.get(&items[1]) {
+                        Some(&DecodedData::Int(ref int)) => {
+                            assert_eq!(int, &num_bigint::BigInt::from(1000))
+                        }
+                        _ => bail!("Expected an int"),
+                    }
+                }
+                _ => bail!("Expected a tuple"),
+            }
+        } else {
+            bail!("Expected a list")
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn bytes() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27("entry = b'\\x00\\xffbytes'")?;
+        let mem = crate::connect(child.pid)?;
+
+        let bytes: PyBytesObject<Cpython2_7> = Pointer::new(child.pointer).try_deref_me(&mem)?;
+        assert_eq!(bytes.read(&mem)?, b"\x00\xffbytes");
+
+        Ok(())
+    }
+
+    #[test]
+    fn property() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27(
+            r#"
+            class Foo(object):
+                @property
+                def value(self):
+                    return 1
+            entry = (Foo.__dict__['value'], Foo.__dict__['value'].fget)
+            "#,
+        )?;
+        let mem = crate::connect(child.pid)?;
+
+        let graph = walk::<Cpython2_7, _>(&mem, Pointer::new(child.pointer));
+        let items = match graph.get(&DataPointer(child.pointer)) {
+            Some(DecodedData::Tuple(items)) => items,
+            _ => bail!("Expected a tuple"),
+        };
+        match graph.get(&items[0]) {
+            Some(DecodedData::Property { fget, fset, fdel }) => {
+                assert_eq!(fget, &Some(items[1]));
+                assert_eq!(fset, &None);
+                assert_eq!(fdel, &None);
+            }
+            _ => bail!("Expected a property"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn mro_names() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27("entry = ValueError('bad value')")?;
+        let mem = crate::connect(child.pid)?;
+
+        let options = WalkOptions {
+            resolve_mro_names: true,
+            ..WalkOptions::default()
+        };
+        let graph =
+            walk_with_options::<Cpython2_7, _>(&mem, Pointer::new(child.pointer), &options, None)
+                .graph;
+        match graph.get(&DataPointer(child.pointer)) {
+            Some(DecodedData::Exception { mro_names, .. }) => {
+                assert_eq!(mro_names[0], "exceptions.ValueError");
+                assert!(mro_names.iter().any(|name| name == "exceptions.Exception"));
+                assert!(mro_names
+                    .iter()
+                    .any(|name| name == "exceptions.BaseException"));
+                assert_eq!(mro_names.last().map(String::as_str), Some("object"));
+            }
+            _ => bail!("Expected an exception"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn tp_base() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27("entry = True")?;
+        let mem = crate::connect(child.pid)?;
+
+        let object: PyObject<Cpython2_7> = Pointer::new(child.pointer).try_deref_me(&mem)?;
+        let mut names = Vec::new();
+        let mut base = Some(object.ob_type(&mem)?);
+        while let Some(type_object) = base {
+            names.push(type_object.name().to_owned());
+            base = type_object.tp_base(&mem)?;
+        }
+        assert_eq!(names, ["bool", "int", "object"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn mro_names_without_mro() -> std::result::Result<(), anyhow::Error> {
+        let mut heap = FakeHeap::new(0x1000, 0x1000);
+        let object_address = 0x1080;
+        let types = [0x1100, 0x1300, 0x1500];
+
+        for (idx, (&type_address, name)) in
+            types.iter().zip(["Leaf", "Middle", "object"]).enumerate()
+        {
+            heap.write_type_with(
+                type_address,
+                name,
+                bindings::PyTypeObject {
+                    tp_base: types.get(idx + 1).map_or(0, |&base| base) as *mut _,
+                    tp_flags: python27_sys::Py_TPFLAGS_HEAPTYPE,
+                    ..Default::default()
+                },
+            );
+        }
+        heap.write(
+            object_address,
+            bindings::PyObject {
+                ob_refcnt: 1,
+                ob_type: types[0] as *mut _,
+            },
+        );
+        let mem = heap.memory();
+
+        let options = WalkOptions {
+            resolve_mro_names: true,
+            ..WalkOptions::default()
+        };
+        let graph =
+            walk_with_options::<Cpython2_7, _>(&mem, Pointer::new(object_address), &options, None)
+                .graph;
+        match graph.get(&DataPointer(object_address)) {
+            Some(DecodedData::Object { mro_names, .. }) => {
+                assert_eq!(mro_names, &["Leaf", "Middle", "object"]);
+            }
+            node => bail!("Expected an object, got {:?}", node),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn small_string_layouts() -> std::result::Result<(), anyhow::Error> {
+        let mut heap = FakeHeap::new(0x1000, 0x1000);
+        let sval = offset_of!(bindings::PyStringObject, ob_sval);
+        let header = |ob_size: isize| bindings::PyVarObject {
+            ob_refcnt: 1,
+            ob_type: std::ptr::null_mut(),
+            ob_size,
+        };
+
+        // The standard layout, with `ob_sstate` interned.
+        heap.write(0x1000, header(5));
+        heap.write(0x1000 + sval - 4, 1i32);
+        heap.write(0x1000 + sval, *b"hello\0");
+        // A short string on the standard layout, which the small layout would
+        // also accept as `ob_sstate` is 0.
+        heap.write(0x1100, header(2));
+        heap.write(0x1100 + sval, *b"hi\0");
+        // The small layout, with `ob_sval` 4 bytes earlier and the next object
+        // right behind the terminator.
+        heap.write(0x1200, header(5));
+        heap.write(0x1200 + sval - 4, *b"small\0\x07\x07\x07\x07");
+        // Neither layout has a terminator.
+        heap.write(0x1300, header(3));
+        heap.write(0x1300 + sval - 4, [0x07u8; 16]);
+        let mem = heap.memory();
+
+        let read = |address| -> Result<Vec<u8>> {
+            let string: PySmallStringObject<Cpython2_7SmallString> =
+                Pointer::new(address).try_deref_me(&mem)?;
+            string.read_bytes(&mem)
+        };
+        assert_eq!(read(0x1000)?, b"hello");
+        assert_eq!(read(0x1100)?, b"hi");
+        assert_eq!(read(0x1200)?, b"small");
+        assert!(matches!(read(0x1300), Err(Error::Decode)));
+
+        // The standard layout of the live interpreter is detected as well.
+        let child = spawn_python27("entry = ['a', 'standard string']")?;
+        let mem = crate::connect(child.pid)?;
+        let graph = walk::<Cpython2_7SmallString, _>(&mem, Pointer::new(child.pointer));
+        let mut strings: Vec<&str> = graph
+            .values()
+            .filter_map(|data| match data {
+                DecodedData::String(string) => Some(string.as_str()),
+                _ => None,
+            })
+            .collect();
+        strings.sort_unstable();
+        assert_eq!(strings, ["a", "standard string"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn unreadable_type_name() -> std::result::Result<(), anyhow::Error> {
+        let mut heap = FakeHeap::new(0x1000, 0x1000);
+        let object_address = 0x1000;
+        let type_address = 0x1100;
+        heap.write(
+            object_address,
+            bindings::PyObject {
+                ob_refcnt: 1,
+                ob_type: type_address as *mut _,
+            },
+        );
+        heap.write(
+            type_address,
+            bindings::PyTypeObject {
+                ob_refcnt: 1,
+                tp_name: 0xdead_0000 as *const _,
+                ..Default::default()
+            },
+        );
+        let mem = heap.memory();
+
+        let type_object: PyTypeObject<Cpython2_7> =
+            Pointer::new(type_address).try_deref_me(&mem)?;
+        assert_eq!(type_object.name(), UNREADABLE_TYPE_NAME);
+        assert_eq!(type_object.tp_name(), Pointer::new(0xdead_0000));
+
+        let graph = walk::<Cpython2_7, _>(&mem, Pointer::new(object_address));
+        match graph.get(&DataPointer(object_address)) {
+            Some(DecodedData::Unknown { type_name, .. }) => {
+                assert_eq!(type_name, UNREADABLE_TYPE_NAME)
+            }
+            _ => bail!("Expected an unknown object"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn unreadable_container_table() -> std::result::Result<(), anyhow::Error> {
+        let mut heap = FakeHeap::new(0x1000, 0x1000);
+        let list_address = 0x1000;
+        let dict_address = 0x1100;
+        let list_type_address = 0x1400;
+        let dict_type_address = 0x1600;
+        let unmapped: usize = 0xdead_0000;
+
+        heap.write_type(list_type_address, "list");
+        heap.write_type(dict_type_address, "dict");
+        heap.write(
+            list_address,
+            bindings::PyListObject {
+                ob_refcnt: 1,
+                ob_type: list_type_address as *mut _,
+                ob_size: 2,
+                ob_item: unmapped as *mut _,
+                allocated: 2,
+            },
+        );
+        heap.write(
+            dict_address,
+            bindings::PyDictObject {
+                ob_refcnt: 1,
+                ob_type: dict_type_address as *mut _,
+                ma_fill: 1,
+                ma_used: 1,
+                ma_mask: 7,
+                ma_table: unmapped as *mut _,
+                ..Default::default()
+            },
+        );
+        let mem = heap.memory();
+
+        for address in [list_address, dict_address] {
+            let graph = walk::<Cpython2_7, _>(&mem, Pointer::new(address));
+            match graph.get(&DataPointer(address)) {
+                Some(DecodedData::Error(Error::SegmentationFault(_))) => {}
+                node => bail!("Expected an error, got {:?}", node),
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn negative_dict_mask() -> std::result::Result<(), anyhow::Error> {
+        let mut heap = FakeHeap::new(0x1000, 0x1000);
+        let dict_type_address = 0x1100;
+        let dict_address = 0x1600;
+
+        heap.write_type(dict_type_address, "dict");
+        heap.write(
+            dict_address,
+            bindings::PyDictObject {
+                ob_refcnt: 1,
+                ob_type: dict_type_address as *mut _,
+                ma_fill: 1,
+                ma_used: 1,
+                ma_mask: -1,
+                ma_table: 0x1800 as *mut _,
+                ..Default::default()
+            },
+        );
+        let mem = heap.memory();
+
+        let graph = walk::<Cpython2_7, _>(&mem, Pointer::new(dict_address));
+        match graph.get(&DataPointer(dict_address)) {
+            Some(DecodedData::Error(Error::Decode)) => {}
+            node => bail!("Expected a decode error, got {:?}", node),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn negative_ob_size() -> std::result::Result<(), anyhow::Error> {
+        let mut heap = FakeHeap::new(0x1000, 0x1000);
+        let string_address = 0x1000;
+        let tuple_address = 0x1100;
+        let list_address = 0x1200;
+        let unicode_address = 0x1300;
+        let huge_unicode_address = 0x1400;
+
+        heap.write(
+            string_address,
+            bindings::PyStringObject {
+                ob_refcnt: 1,
+                ob_size: -3,
+                ..Default::default()
+            },
+        );
+        heap.write(
+            tuple_address,
+            bindings::PyTupleObject {
+                ob_refcnt: 1,
+                ob_size: -1,
+                ..Default::default()
+            },
+        );
+        heap.write(
+            list_address,
+            bindings::PyListObject {
+                ob_refcnt: 1,
+                ob_size: -2,
+                ob_item: std::ptr::null_mut(),
+                allocated: 0,
+                ..Default::default()
+            },
+        );
+        heap.write(
+            unicode_address + offset_of!(python27_sys::PyUnicodeObject, length),
+            -4isize,
+        );
+        heap.write(
+            huge_unicode_address + offset_of!(python27_sys::PyUnicodeObject, length),
+            isize::MAX,
+        );
+        let mem = heap.memory();
+
+        let string: PyStringObject<Cpython2_7> = Pointer::new(string_address).try_deref_me(&mem)?;
+        assert!(matches!(string.read_bytes(&mem), Err(Error::Decode)));
+
+        let unicode: PyUnicodeObject<Cpython2_7> =
+            Pointer::new(unicode_address).try_deref_me(&mem)?;
+        assert!(matches!(unicode.read_bytes(&mem), Err(Error::Decode)));
+        assert!(matches!(unicode.read_prefix(&mem, 16), Err(Error::Decode)));
+        let huge: PyUnicodeObject<Cpython2_7> =
+            Pointer::new(huge_unicode_address).try_deref_me(&mem)?;
+        assert!(matches!(
+            huge.read_prefix(&mem, usize::MAX),
+            Err(Error::SizeError)
+        ));
+
+        let tuple: PyTupleObject<Cpython2_7> = Pointer::new(tuple_address).try_deref_me(&mem)?;
+        let items: Vec<_> = tuple.items(&mem).collect();
+        assert!(matches!(items[..], [Err(Error::Decode)]));
+
+        let list: PyListObject<Cpython2_7> = Pointer::new(list_address).try_deref_me(&mem)?;
+        let items: Vec<_> = list.items(&mem).collect();
+        assert!(matches!(items[..], [Err(Error::Decode)]));
+
+        Ok(())
+    }
+
+    #[test]
+    fn sequence_len() -> std::result::Result<(), anyhow::Error> {
+        let mut heap = FakeHeap::new(0x1000, 0x1000);
+        let tuple_address = 0x1000;
+        let list_address = 0x1100;
+        let negative_address = 0x1200;
+        heap.write(
+            tuple_address,
+            bindings::PyTupleObject {
+                ob_refcnt: 1,
+                ob_size: 3,
+                ..Default::default()
+            },
+        );
+        // The item array is unmapped, so reading any item would fail.
+        heap.write(
+            list_address,
+            bindings::PyListObject {
+                ob_refcnt: 1,
+                ob_size: 10_000,
+                ob_item: 0x10_0000 as *mut _,
+                allocated: 10_000,
+                ..Default::default()
+            },
+        );
+        heap.write(
+            negative_address,
+            bindings::PyListObject {
+                ob_refcnt: 1,
+                ob_size: -2,
+                ..Default::default()
+            },
+        );
+        let mem = CountingMemory::new(heap.memory());
+
+        let tuple: PyTupleObject<Cpython2_7> = Pointer::new(tuple_address).try_deref_me(&mem)?;
+        let list: PyListObject<Cpython2_7> = Pointer::new(list_address).try_deref_me(&mem)?;
+        let negative: PyListObject<Cpython2_7> =
+            Pointer::new(negative_address).try_deref_me(&mem)?;
+        let reads = mem.reads();
+
+        assert_eq!(tuple.len(), 3);
+        assert_eq!(list.len(), 10_000);
+        assert_eq!(negative.len(), 0);
+        assert_eq!(mem.reads(), reads);
+
+        Ok(())
+    }
+
+    #[test]
+    fn set_members_capped() -> std::result::Result<(), anyhow::Error> {
+        let mut heap = FakeHeap::new(0x1000, 0x1000);
+        let key_address = 0x1000;
+        let set_address = 0x1100;
+        let negative_address = 0x1200;
+        let table_address = 0x1400;
+        heap.write(
+            key_address,
+            bindings::PyObject {
+                ob_refcnt: 1,
+                ..Default::default()
+            },
+        );
+        for (address, mask) in [(set_address, 7isize), (negative_address, -1)] {
+            heap.write(address + offset_of!(bindings::PySetObject, used), 8isize);
+            heap.write(address + offset_of!(bindings::PySetObject, mask), mask);
+            heap.write(
+                address + offset_of!(bindings::PySetObject, table),
+                table_address,
+            );
+        }
+        for slot in 0..8 {
+            heap.write(
+                table_address + slot * std::mem::size_of::<bindings::setentry>(),
+                bindings::setentry {
+                    hash: slot as _,
+                    key: key_address as *mut _,
+                },
+            );
+        }
+        let mem = heap.memory();
+
+        let set: PySetObject<Cpython2_7> = Pointer::new(set_address).try_deref_me(&mem)?;
+        let (members, capped) = set.members(&mem, 8)?;
+        assert_eq!(members.len(), 8);
+        assert!(!capped);
+        let (members, capped) = set.members(&mem, 4)?;
+        assert_eq!(members.len(), 4);
+        assert!(capped);
+
+        let negative: PySetObject<Cpython2_7> =
+            Pointer::new(negative_address).try_deref_me(&mem)?;
+        assert!(matches!(negative.members(&mem, 8), Err(Error::Decode)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn dict_offset_word_size() {
+        // 12 bytes of header and 5 of items end at 17, which 4-byte words pad
+        // to 20; the dict pointer is the last word.
+        assert_eq!(dict_offset(-4, 12, 5, 4), Some(16));
+        // Padding to 8-byte words instead would misplace it.
+        assert_ne!(dict_offset(-4, 12, 5, 8), Some(16));
+        // 24 bytes of header and 5 of items pad to 32 with 8-byte words.
+        assert_eq!(dict_offset(-8, 24, 5, 8), Some(24));
+        // Sizes that are already aligned are not padded.
+        assert_eq!(dict_offset(-4, 12, 4, 4), Some(12));
+        // Positive offsets are from the start of the object.
+        assert_eq!(dict_offset(16, 12, 5, 4), Some(16));
+    }
+
+    #[test]
+    fn dict_offset_overflow() -> std::result::Result<(), anyhow::Error> {
+        assert_eq!(dict_offset(-8, isize::MAX, 8, 8), None);
+        assert_eq!(dict_offset(-8, 24, isize::MAX, 8), None);
+
+        // A negative offset reaching below address 0 fails rather than
+        // wrapping around to the top of the address space.
+        let mut heap = FakeHeap::new(0x1000, 0x1000);
+        let type_address = 0x1100;
+        let object_address = 0x1400;
+        heap.write_type_with(
+            type_address,
+            "corrupt",
+            bindings::PyTypeObject {
+                tp_basicsize: 16,
+                tp_dictoffset: -0x2000,
+                ..Default::default()
+            },
+        );
+        heap.write(
+            object_address,
+            bindings::PyObject {
+                ob_refcnt: 1,
+                ob_type: type_address as *mut _,
+            },
+        );
+        let mem = heap.memory();
+
+        let type_object: PyTypeObject<Cpython2_7> =
+            Pointer::new(type_address).try_deref_me(&mem)?;
+        assert!(matches!(
+            instance_dict::<Cpython2_7>(&mem, Pointer::new(object_address), &type_object),
+            Err(Error::Decode)
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn dict_slots_word_size() -> Result<()> {
+        // Two 32-bit entries, the second of them empty, then a third entry.
+        let words: [u32; 9] = [7, 0x1100, 0x1200, 0, 0, 0, 9, 0x1300, 0x1400];
+        let bytes: Vec<u8> = words.iter().flat_map(|word| word.to_ne_bytes()).collect();
+        let mem = crate::memory::SliceMemory::new(0x1000, &bytes);
+
+        assert_eq!(
+            read_dict_slots(&mem, 0x1000, 3, 4)?,
+            [
+                DictSlot {
+                    hash: 7,
+                    key: 0x1100,
+                    value: 0x1200,
+                },
+                DictSlot {
+                    hash: 0,
+                    key: 0,
+                    value: 0,
+                },
+                DictSlot {
+                    hash: 9,
+                    key: 0x1300,
+                    value: 0x1400,
+                },
+            ]
+        );
+        // Striding by 64-bit entries would read past the table.
+        assert!(read_dict_slots(&mem, 0x1000, 3, 8).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn dict_32_bit() -> std::result::Result<(), anyhow::Error> {
+        let small_address = 0x1000;
+        let table_dict_address = 0x1080;
+        let table_address = 0x1180;
+        let (key, value) = (0x1100, 0x1120);
+
+        // Both dicts are 31 32-bit words: the header, `ma_lookup` and the
+        // 8-entry `ma_smalltable`.
+        let mut words = [0u32; 0x80];
+        let mut write = |address: usize, values: &[u32]| {
+            let start = (address - small_address) / 4;
+            words[start..start + values.len()].copy_from_slice(values);
+        };
+        write(
+            small_address,
+            &[1, 0, 1, 1, 7, small_address as u32 + 28, 0],
+        );
+        // The third slot of the embedded table.
+        write(
+            small_address + 28 + 2 * 12,
+            &[-11i32 as u32, key as u32, value as u32],
+        );
+        write(
+            table_dict_address,
+            &[1, 0, 1, 1, 7, table_address as u32, 0],
+        );
+        write(
+            table_address + 5 * 12,
+            &[-21i32 as u32, value as u32, key as u32],
+        );
+        let bytes: Vec<u8> = words.iter().flat_map(|word| word.to_ne_bytes()).collect();
+        let mem = crate::memory::SliceMemory::new(small_address, &bytes);
+
+        for (address, expected) in [
+            (small_address, (-11isize as usize, key, value)),
+            (table_dict_address, (-21isize as usize, value, key)),
+        ] {
+            let dict = PyDictObject::<Cpython2_7>::try_deref_with_word_size(
+                &mem,
+                Pointer::new(address),
+                4,
+            )?;
+            assert_eq!(dict.mask(), 7);
+            let (entries, capped) = dict.entries(&mem, DEFAULT_MAX_DICT_SLOTS)?;
+            assert!(!capped);
+            let entries: Vec<_> = entries
+                .into_iter()
+                .map(|entry| {
+                    let (hash, key, value) = entry.take();
+                    (hash, key.me().address(), value.me().address())
+                })
+                .collect();
+            assert_eq!(entries, [expected]);
+        }
+
+        // With the host's word size, the same bytes make no sense.
+        let dict = PyDictObject::<Cpython2_7>::try_deref_with_word_size(
+            &mem,
+            Pointer::new(small_address),
+            PY_SIZE_T,
+        )?;
+        assert_ne!(dict.mask(), 7);
+
+        Ok(())
+    }
+
+    #[test]
+    fn null_ob_type() {
+        let mut heap = FakeHeap::new(0x1000, 0x100);
+        heap.write(
+            0x1000,
+            bindings::PyObject {
+                ob_refcnt: 3,
+                ob_type: std::ptr::null_mut(),
+            },
+        );
+        let mem = heap.memory();
+
+        let graph = walk::<Cpython2_7, _>(&mem, Pointer::new(0x1000));
+        assert_eq!(graph.len(), 1);
+        assert!(matches!(
+            graph.get(&DataPointer(0x1000)),
+            Some(DecodedData::FreedOrUninitialized { ob_refcnt: 3 })
+        ));
+    }
+
+    #[test]
+    fn tuple_items() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27("entry = (10, 11, 12, 13, 14)")?;
+        let mem = crate::connect(child.pid)?;
+
+        // The copied struct only holds the first item; the rest are read from
+        // the target, past the end of it.
+        let tuple: PyTupleObject<Cpython2_7> = Pointer::new(child.pointer).try_deref_me(&mem)?;
+        let items = tuple.items(&mem).collect::<Result<Vec<_>>>()?;
+        assert_eq!(items.len(), 5);
+        for (idx, item) in items.iter().enumerate() {
+            let slot =
+                child.pointer + offset_of!(bindings::PyTupleObject, ob_item) + idx * PY_SIZE_T;
+            assert_eq!(item.me().address(), mem.get_usize(slot)?);
+            match item.downcast(&mem)?.as_int() {
+                Some(int) => assert_eq!(int.read(&mem)?, BigInt::from(10 + idx)),
+                None => bail!("Expected an int"),
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn method_wrappers() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27(
+            r#"
+            class Foo(object):
+                @classmethod
+                def m(cls):
+                    pass
+                @staticmethod
+                def s():
+                    pass
+            entry = (Foo.__dict__['m'], Foo.__dict__['s'], staticmethod(len))
+            "#,
+        )?;
+        let mem = crate::connect(child.pid)?;
+
+        let graph = walk::<Cpython2_7, _>(&mem, Pointer::new(child.pointer));
+        let items = match graph.get(&DataPointer(child.pointer)) {
+            Some(DecodedData::Tuple(items)) => items,
+            _ => bail!("Expected a tuple"),
+        };
+        match graph.get(&items[0]) {
+            Some(DecodedData::ClassMethod { callable, name }) => {
+                assert_eq!(name.as_deref(), Some("m"));
+                assert!(graph.contains_key(callable));
+            }
+            node => bail!("Expected a classmethod, got {:?}", node),
+        }
+        match graph.get(&items[1]) {
+            Some(DecodedData::StaticMethod { name, .. }) => assert_eq!(name.as_deref(), Some("s")),
+            node => bail!("Expected a staticmethod, got {:?}", node),
+        }
+        // Builtins are not Python functions.
+        match graph.get(&items[2]) {
+            Some(DecodedData::StaticMethod { name, .. }) => assert_eq!(name, &None),
+            node => bail!("Expected a staticmethod, got {:?}", node),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn mmap() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27(
+            r#"
+            import mmap
+            entry = mmap.mmap(-1, 4096)
+            entry.seek(100)
+            "#,
+        )?;
+        let mem = crate::connect(child.pid)?;
+
+        let graph = walk::<Cpython2_7, _>(&mem, Pointer::new(child.pointer));
+        match graph.get(&DataPointer(child.pointer)) {
+            Some(DecodedData::Mmap { size, pos, fd }) => {
+                assert_eq!(*size, 4096);
+                assert_eq!(*pos, 100);
+                assert_eq!(*fd, -1);
+            }
+            node => bail!("Expected an mmap, got {:?}", node),
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "datetime")]
+    #[test]
+    fn datetime() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27(
+            r#"
+            import datetime
+            class Utc(datetime.tzinfo):
+                pass
+            entry = (
+                datetime.datetime(2021, 3, 4, 5, 6, 7, 890123),
+                datetime.date(1999, 12, 31),
+                datetime.timedelta(days=-2, seconds=3, microseconds=4),
+                datetime.datetime(2021, 3, 4, tzinfo=Utc()),
+            )
+            "#,
+        )?;
+        let mem = crate::connect(child.pid)?;
+
+        let graph = walk::<Cpython2_7, _>(&mem, Pointer::new(child.pointer));
+        let items = match graph.get(&DataPointer(child.pointer)) {
+            Some(DecodedData::Tuple(items)) => items,
+            node => bail!("Expected a tuple, got {:?}", node),
+        };
+        let values: Vec<_> = items
+            .iter()
+            .map(|item| match graph.get(item) {
+                Some(DecodedData::DateTime { value, tzinfo }) => Ok((value.clone(), *tzinfo)),
+                node => bail!("Expected a datetime object, got {:?}", node),
+            })
+            .collect::<std::result::Result<_, _>>()?;
+
+        assert_eq!(
+            values[0],
+            (
+                DateTimeValue::DateTime {
+                    year: 2021,
+                    month: 3,
+                    day: 4,
+                    hour: 5,
+                    minute: 6,
+                    second: 7,
+                    microsecond: 890123,
+                },
+                None
+            )
+        );
+        assert_eq!(
+            values[1],
+            (
+                DateTimeValue::Date {
+                    year: 1999,
+                    month: 12,
+                    day: 31,
+                },
+                None
+            )
+        );
+        assert_eq!(
+            values[2],
+            (
+                DateTimeValue::TimeDelta {
+                    days: -2,
+                    seconds: 3,
+                    microseconds: 4,
+                },
+                None
+            )
+        );
+        match graph.get(&values[3].1.expect("aware datetime has a tzinfo")) {
+            Some(DecodedData::Object {
+                object_type_name, ..
+            }) => assert_eq!(object_type_name, "Utc"),
+            node => bail!("Expected the tzinfo instance, got {:?}", node),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn indexed_iterators() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27(
+            "entry = (reversed([1, 2, 3]), reversed((1, 2)), enumerate(['a', 'b'], 5))",
+        )?;
+        let mem = crate::connect(child.pid)?;
+
+        let graph = walk::<Cpython2_7, _>(&mem, Pointer::new(child.pointer));
+        let items = match graph.get(&DataPointer(child.pointer)) {
+            Some(DecodedData::Tuple(items)) => items,
+            node => bail!("Expected a tuple, got {:?}", node),
+        };
+
+        match graph.get(&items[0]) {
+            Some(DecodedData::Reversed {
+                index,
+                source: Some(source),
+            }) => {
+                assert_eq!(*index, 2);
+                assert!(
+                    matches!(graph.get(source), Some(DecodedData::List(items)) if items.len() == 3)
+                );
+            }
+            node => bail!("Expected a reversed iterator, got {:?}", node),
+        }
+        match graph.get(&items[1]) {
+            Some(DecodedData::Reversed {
+                index,
+                source: Some(source),
+            }) => {
+                assert_eq!(*index, 1);
+                assert!(matches!(graph.get(source), Some(DecodedData::Tuple(_))));
+            }
+            node => bail!("Expected a reversed iterator, got {:?}", node),
+        }
+        match graph.get(&items[2]) {
+            Some(DecodedData::Enumerate { index, source }) => {
+                assert_eq!(*index, 5);
+                assert!(graph.contains_key(source));
+            }
+            node => bail!("Expected an enumerate iterator, got {:?}", node),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn ordered_and_default_dicts() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27(
+            r#"
+            from collections import OrderedDict, defaultdict
+            ordered = OrderedDict()
+            for i, key in enumerate(['zebra', 'apple', 'mango', 'kiwi', 'fig', 'date']):
+                ordered[key] = i
+            del ordered['zebra']
+            ordered['zebra'] = 6
+            entry = (ordered, defaultdict(list, {'key': [1]}))
+            "#,
+        )?;
+        let mem = crate::connect(child.pid)?;
+
+        let graph = walk::<Cpython2_7, _>(&mem, Pointer::new(child.pointer));
+        let items = match graph.get(&DataPointer(child.pointer)) {
+            Some(DecodedData::Tuple(items)) => items,
+            node => bail!("Expected a tuple, got {:?}", node),
+        };
+
+        let entries = match graph.get(&items[0]) {
+            Some(DecodedData::OrderedDict(entries)) => entries,
+            node => bail!("Expected an OrderedDict, got {:?}", node),
+        };
+        let order: Vec<(&str, BigInt)> = entries
+            .iter()
+            .map(|(key, value)| match (graph.get(key), graph.get(value)) {
+                (Some(DecodedData::String(key)), Some(DecodedData::Int(value))) => {
+                    Ok((key.as_str(), value.clone()))
+                }
+                entry => Err(anyhow::anyhow!("Unexpected entry {:?}", entry)),
+            })
+            .collect::<std::result::Result<_, _>>()?;
+        let expected = ["apple", "mango", "kiwi", "fig", "date", "zebra"];
+        assert_eq!(
+            order,
+            expected
+                .iter()
+                .zip([1, 2, 3, 4, 5, 6])
+                .map(|(key, value)| (*key, BigInt::from(value)))
+                .collect::<Vec<_>>()
+        );
+
+        match graph.get(&items[1]) {
+            Some(DecodedData::DefaultDict {
+                entries,
+                partial,
+                default_factory: Some(default_factory),
+            }) => {
+                assert_eq!(entries.len(), 1);
+                assert!(!partial);
+                assert!(matches!(
+                    graph.get(default_factory),
+                    Some(DecodedData::Type(name)) if name == "list"
+                ));
+            }
+            node => bail!("Expected a defaultdict, got {:?}", node),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn slice() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27("entry = (slice(1, 10, 2), slice(3))")?;
+        let mem = crate::connect(child.pid)?;
+
+        let graph = walk::<Cpython2_7, _>(&mem, Pointer::new(child.pointer));
+        let items = match graph.get(&DataPointer(child.pointer)) {
+            Some(DecodedData::Tuple(items)) => items,
+            node => bail!("Expected a tuple, got {:?}", node),
+        };
+        let int = |pointer: &Option<DataPointer>| {
+            pointer
+                .and_then(|p| graph.get(&p))
+                .and_then(DecodedData::as_int)
+                .cloned()
+        };
+
+        match graph.get(&items[0]) {
+            Some(DecodedData::Slice { start, stop, step }) => {
+                assert_eq!(int(start), Some(BigInt::from(1)));
+                assert_eq!(int(stop), Some(BigInt::from(10)));
+                assert_eq!(int(step), Some(BigInt::from(2)));
+            }
+            node => bail!("Expected a slice, got {:?}", node),
+        }
+        match graph.get(&items[1]) {
+            Some(DecodedData::Slice { start, stop, step }) => {
+                assert_eq!(*start, None);
+                assert_eq!(int(stop), Some(BigInt::from(3)));
+                assert_eq!(*step, None);
+            }
+            node => bail!("Expected a slice, got {:?}", node),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn capsule() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27(
+            r#"
+            import ctypes, datetime
+            new_capsule = ctypes.pythonapi.PyCapsule_New
+            new_capsule.restype = ctypes.py_object
+            new_capsule.argtypes = [ctypes.c_void_p, ctypes.c_void_p, ctypes.c_void_p]
+            name = ctypes.create_string_buffer("walker.test")
+            entry = (
+                new_capsule(0x1234, ctypes.addressof(name), None),
+                new_capsule(0x5678, None, None),
+                datetime.datetime_CAPI,
+            )
+            "#,
+        )?;
+        let mem = crate::connect(child.pid)?;
+
+        let graph = walk::<Cpython2_7, _>(&mem, Pointer::new(child.pointer));
+        let items = match graph.get(&DataPointer(child.pointer)) {
+            Some(DecodedData::Tuple(items)) => items,
+            node => bail!("Expected a tuple, got {:?}", node),
+        };
+        match graph.get(&items[0]) {
+            Some(DecodedData::Capsule { name, pointer }) => {
+                assert_eq!(name.as_deref(), Some("walker.test"));
+                assert_eq!(*pointer, 0x1234);
+            }
+            node => bail!("Expected a capsule, got {:?}", node),
+        }
+        match graph.get(&items[1]) {
+            Some(DecodedData::Capsule { name, pointer }) => {
+                assert_eq!(*name, None);
+                assert_eq!(*pointer, 0x5678);
+            }
+            node => bail!("Expected a capsule, got {:?}", node),
+        }
+        match graph.get(&items[2]) {
+            Some(DecodedData::Capsule { name, pointer }) => {
+                assert_eq!(name.as_deref(), Some("datetime.datetime_CAPI"));
+                assert_ne!(*pointer, 0);
+            }
+            node => bail!("Expected a capsule, got {:?}", node),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn array() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27(
+            r#"
+            import array
+            entry = (
+                array.array('i', [1, 2, 3]),
+                array.array('d', [0.5, -2.0]),
+                array.array('c', 'ab'),
+                array.array('L'),
+                array.array('h', range(-50, 50)),
+            )
+            "#,
+        )?;
+        let mem = crate::connect(child.pid)?;
+
+        let output = walk_with_options::<Cpython2_7, _>(
+            &mem,
+            Pointer::new(child.pointer),
+            &WalkOptions {
+                max_value_len: Some(10),
+                ..Default::default()
+            },
+            None,
+        );
+        let arrays: Vec<_> = match output.graph.get(&DataPointer(child.pointer)) {
+            Some(DecodedData::Tuple(items)) => items
+                .iter()
+                .map(|item| match output.graph.get(item) {
+                    Some(DecodedData::Array { typecode, values }) => Ok((*typecode, values)),
+                    node => bail!("Expected an array, got {:?}", node),
+                })
+                .collect::<std::result::Result<_, _>>()?,
+            node => bail!("Expected a tuple, got {:?}", node),
+        };
+        let ints =
+            |values: std::ops::Range<i64>| ArrayValues::Int(values.map(BigInt::from).collect());
+        assert_eq!(arrays[0], ('i', &ints(1..4)));
+        assert_eq!(arrays[1], ('d', &ArrayValues::Float(vec![0.5, -2.0])));
+        assert_eq!(
+            arrays[2],
+            (
+                'c',
+                &ArrayValues::Int(vec![BigInt::from(b'a'), BigInt::from(b'b')])
+            )
+        );
+        assert_eq!(arrays[3], ('L', &ArrayValues::Int(vec![])));
+        assert_eq!(arrays[4], ('h', &ints(-50..-40)));
+
+        let truncated: Vec<_> = output.truncated_values.values().copied().collect();
+        assert_eq!(truncated, [100]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn deque() -> std::result::Result<(), anyhow::Error> {
+        // Enough elements to span several blocks in both directions.
+        let child = spawn_python27(
+            r#"
+            import collections
+            entry = collections.deque(range(100))
+            entry.append(100)
+            for i in range(1, 71):
+                entry.appendleft(-i)
+            "#,
+        )?;
+        let mem = crate::connect(child.pid)?;
+
+        let graph = walk::<Cpython2_7, _>(&mem, Pointer::new(child.pointer));
+        let items = match graph.get(&DataPointer(child.pointer)) {
+            Some(DecodedData::Deque(items)) => items,
+            node => bail!("Expected a deque, got {:?}", node),
+        };
+        let ints: Vec<_> = items
+            .iter()
+            .map(|item| graph.get(item).and_then(DecodedData::as_int).cloned())
+            .collect();
+        let expected: Vec<_> = (-70..=100).map(|i| Some(BigInt::from(i))).collect();
+        assert_eq!(ints, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn deque_block_list_bounded() -> std::result::Result<(), anyhow::Error> {
+        let mut heap = FakeHeap::new(0x1000, 0x1000);
+        let item_address = 0x1000;
+        let left_address = 0x1100;
+        let right_address = 0x1300;
+        let looping_address = 0x1a00;
+        let deque_address = 0x1600;
+        let looping_deque_address = 0x1700;
+        let huge_address = 0x1800;
+
+        heap.write(
+            item_address,
+            bindings::PyObject {
+                ob_refcnt: 1,
+                ob_type: item_address as *mut _,
+            },
+        );
+        for (address, rightlink) in [
+            (left_address, right_address),
+            (right_address, 0),
+            (looping_address, looping_address),
+        ] {
+            heap.write(
+                address,
+                bindings::block {
+                    data: [item_address as *mut _; bindings::DEQUE_BLOCKLEN],
+                    rightlink: rightlink as *mut _,
+                    leftlink: std::ptr::null_mut(),
+                },
+            );
+        }
+        for (address, leftblock, len) in [
+            (deque_address, left_address, 4),
+            (looping_deque_address, looping_address, 100),
+            (huge_address, left_address, MAX_ARRAY_LEN as isize + 1),
+        ] {
+            heap.write(
+                address,
+                bindings::dequeobject {
+                    ob_refcnt: 1,
+                    ob_type: std::ptr::null_mut(),
+                    leftblock: leftblock as *mut _,
+                    rightblock: std::ptr::null_mut(),
+                    leftindex: bindings::DEQUE_BLOCKLEN as isize - 2,
+                    rightindex: 0,
+                    len,
+                    state: 0,
+                    maxlen: -1,
+                    weakreflist: std::ptr::null_mut(),
+                },
+            );
+        }
+        let mem = heap.memory();
+
+        let deque: PyDequeObject<Cpython2_7> = Pointer::new(deque_address).try_deref_me(&mem)?;
+        assert_eq!(deque.items(&mem)?.len(), 4);
+        for address in [looping_deque_address, huge_address] {
+            let deque: PyDequeObject<Cpython2_7> = Pointer::new(address).try_deref_me(&mem)?;
+            assert!(deque.items(&mem).is_err());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn long() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27("entry = (0L, 7L, -(2 ** 100), 3 ** 50)")?;
+        let mem = crate::connect(child.pid)?;
+
+        let graph = walk::<Cpython2_7, _>(&mem, Pointer::new(child.pointer));
+        let items = match graph.get(&DataPointer(child.pointer)) {
+            Some(DecodedData::Tuple(items)) => items,
+            node => bail!("Expected a tuple, got {:?}", node),
+        };
+        let ints: Vec<_> = items
+            .iter()
+            .map(|item| graph.get(item).and_then(DecodedData::as_int).cloned())
+            .collect();
+
+        assert_eq!(
+            ints,
+            vec![
+                Some(BigInt::from(0)),
+                Some(BigInt::from(7)),
+                Some(-(BigInt::from(1) << 100usize)),
+                Some(BigInt::from(3).pow(50)),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn current_frame() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27(
+            r#"
+            import ctypes
+            get_thread_state = ctypes.pythonapi.PyThreadState_Get
+            get_thread_state.restype = ctypes.c_void_p
+            def blocked():
+                entry = get_thread_state()
+                print(id(entry))
+                sys.stdout.flush()
+                sys.stdin.readline()
+            blocked()
+            entry = None
+            "#,
+        )?;
+        let mem = crate::connect(child.pid)?;
+
+        let entry: PyObject<Cpython2_7> = Pointer::new(child.pointer).try_deref_me(&mem)?;
+        let thread_state = match entry.ob_type(&mem)?.downcast(&mem, entry)?.as_int() {
+            Some(int) => int.read(&mem)?,
+            None => bail!("Expected the thread state address as an int"),
+        };
+        let thread_state = Pointer::new(thread_state.try_into()?);
+
+        let frame = match super::current_frame(&mem, thread_state)? {
+            Some(frame) => frame,
+            None => bail!("Expected a frame"),
+        };
+        let code =
+            mem.get_usize(frame.address() + offset_of!(python27_sys::PyFrameObject, f_code))?;
+        let code: PyCodeObject<Cpython2_7> = Pointer::new(code).try_deref_me(&mem)?;
+        assert_eq!(code.name(&mem)?, "blocked");
+
+        Ok(())
+    }
+
+    #[test]
+    fn list_exceeding_allocated() -> std::result::Result<(), anyhow::Error> {
+        let mut heap = FakeHeap::new(0x1000, 0x1000);
+        let list_type_address = 0x1100;
+        let float_type_address = 0x1300;
+        let list_address = 0x1600;
+        let floats_address = 0x1700;
+        // The item array ends where readable memory does.
+        let items_address = 0x2000 - 2 * PY_SIZE_T;
+
+        heap.write_type(list_type_address, "list");
+        heap.write_type(float_type_address, "float");
+        heap.write(
+            list_address,
+            bindings::PyListObject {
+                ob_refcnt: 1,
+                ob_type: list_type_address as *mut _,
+                ob_size: 1000,
+                ob_item: items_address as *mut _,
+                allocated: 2,
+            },
+        );
+        for idx in 0..2 {
+            let float_address = floats_address + idx * 0x20;
+            heap.write(items_address + idx * PY_SIZE_T, float_address);
+            heap.write(
+                float_address,
+                bindings::PyFloatObject {
+                    ob_refcnt: 1,
+                    ob_type: float_type_address as *mut _,
+                    ob_fval: idx as f64,
+                },
+            );
+        }
+        let mem = heap.memory();
+
+        let output = walk_with_options::<Cpython2_7, _>(
+            &mem,
+            Pointer::new(list_address),
+            &WalkOptions::default(),
+            None,
+        );
+        match output.graph.get(&DataPointer(list_address)) {
+            Some(DecodedData::List(items)) => assert_eq!(items.len(), 2),
+            node => bail!("Expected a list, got {:?}", node),
+        }
+        assert!(!output
+            .graph
+            .values()
+            .any(|data| matches!(data, DecodedData::Error(_))));
+        assert!(output.suspicious.contains(&DataPointer(list_address)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn tuple_inconsistent_with_type() -> std::result::Result<(), anyhow::Error> {
+        let mut heap = FakeHeap::new(0x1000, 0x1000);
+        let tuple_type_address = 0x1100;
+        let corrupt_type_address = 0x1300;
+        let tuple_address = 0x1600;
+        let corrupt_address = 0x1700;
+        let huge_address = 0x1800;
+        let header = offset_of!(bindings::PyTupleObject, ob_item) as isize;
+
+        for (type_address, basicsize) in [(tuple_type_address, header), (corrupt_type_address, 16)]
+        {
+            heap.write_type_with(
+                type_address,
+                "tuple",
+                bindings::PyTypeObject {
+                    tp_basicsize: basicsize,
+                    tp_itemsize: PY_SIZE_T as isize,
+                    ..Default::default()
+                },
+            );
+        }
+        for (address, type_address, ob_size) in [
+            (tuple_address, tuple_type_address, 1),
+            (corrupt_address, corrupt_type_address, 1),
+            (huge_address, tuple_type_address, isize::MAX),
+        ] {
+            heap.write(
+                address,
+                bindings::PyTupleObject {
+                    ob_refcnt: 1,
+                    ob_type: type_address as *mut _,
+                    ob_size,
+                    ob_item: [tuple_address as *mut _],
+                },
+            );
+        }
+        let mem = heap.memory();
+
+        let tuple_type: PyTypeObject<Cpython2_7> =
+            Pointer::new(tuple_type_address).try_deref_me(&mem)?;
+        let huge: PyTupleObject<Cpython2_7> = Pointer::new(huge_address).try_deref_me(&mem)?;
+        assert!(huge.inconsistent_with_type(&tuple_type));
+
+        for (address, suspicious) in [(tuple_address, false), (corrupt_address, true)] {
+            let output = walk_with_options::<Cpython2_7, _>(
+                &mem,
+                Pointer::new(address),
+                &WalkOptions::default(),
+                None,
+            );
+            match output.graph.get(&DataPointer(address)) {
+                Some(DecodedData::Tuple(items)) => assert_eq!(items.len(), 1),
+                node => bail!("Expected a tuple, got {:?}", node),
             }
-            match graph.get(&list[1]) {
-                Some(&DecodedData::Int(ref int)) => assert_eq!(int, &num_bigint::BigInt::from(42)),
-                _ => bail!("Expected an int"),
+            assert_eq!(
+                output.suspicious.contains(&DataPointer(address)),
+                suspicious
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn long_digit_widths() -> std::result::Result<(), anyhow::Error> {
+        let mut heap = FakeHeap::new(0x1000, 0x1000);
+        let narrow_type_address = 0x1100;
+        let wide_type_address = 0x1300;
+        let narrow_address = 0x1600;
+        let wide_address = 0x1700;
+        let huge_address = 0x1800;
+
+        for (type_address, itemsize) in [(narrow_type_address, 2), (wide_type_address, 4)] {
+            heap.write_type_with(
+                type_address,
+                "long",
+                bindings::PyTypeObject {
+                    tp_basicsize: PY_VAR_OBJECT_SIZE as isize,
+                    tp_itemsize: itemsize,
+                    ..Default::default()
+                },
+            );
+        }
+        for (address, type_address, ob_size) in [
+            (narrow_address, narrow_type_address, -2),
+            (wide_address, wide_type_address, 2),
+            (huge_address, wide_type_address, isize::MAX),
+        ] {
+            heap.write(
+                address,
+                bindings::PyVarObject {
+                    ob_refcnt: 1,
+                    ob_type: type_address as *mut _,
+                    ob_size,
+                },
+            );
+        }
+        heap.write(narrow_address + PY_VAR_OBJECT_SIZE, [5u16, 1]);
+        heap.write(wide_address + PY_VAR_OBJECT_SIZE, [5u32, 1]);
+        let mem = heap.memory();
+
+        let narrow_type: PyTypeObject<Cpython2_7> =
+            Pointer::new(narrow_type_address).try_deref_me(&mem)?;
+        let wide_type: PyTypeObject<Cpython2_7> =
+            Pointer::new(wide_type_address).try_deref_me(&mem)?;
+        let narrow: PyLongObject<Cpython2_7> = Pointer::new(narrow_address).try_deref_me(&mem)?;
+        let wide: PyLongObject<Cpython2_7> = Pointer::new(wide_address).try_deref_me(&mem)?;
+        let huge: PyLongObject<Cpython2_7> = Pointer::new(huge_address).try_deref_me(&mem)?;
+
+        assert_eq!(
+            narrow.read(&mem, &narrow_type)?,
+            BigInt::from(-((1 << 15) + 5))
+        );
+        assert_eq!(wide.read(&mem, &wide_type)?, BigInt::from((1i64 << 30) + 5));
+        assert!(huge.read(&mem, &wide_type).is_err());
+
+        Ok(())
+    }
+
+    /// A backend that reads partially across the end of mapped memory,
+    /// returning the readable prefix instead of failing.
+    struct ShortReadMemory<'a>(crate::memory::SliceMemory<'a>);
+
+    impl Memory for ShortReadMemory<'_> {
+        fn get_vec(&self, address: usize, size: usize) -> Result<Vec<u8>> {
+            let readable = (0..=size)
+                .rev()
+                .find(|&size| self.0.is_readable(address, size))
+                .unwrap_or(0);
+            self.0.get_vec(address, readable)
+        }
+    }
+
+    #[test]
+    fn short_read() -> std::result::Result<(), anyhow::Error> {
+        let mut heap = FakeHeap::new(0x1000, 0x1000);
+        let tuple_type_address = 0x1100;
+        let int_type_address = 0x1300;
+        let tuple_address = 0x1600;
+        // The int's header is readable, but its value lies past the end.
+        let int_address = 0x2000 - std::mem::size_of::<bindings::PyObject>();
+
+        heap.write_type(tuple_type_address, "tuple");
+        heap.write_type(int_type_address, "int");
+        heap.write(
+            tuple_address,
+            bindings::PyTupleObject {
+                ob_refcnt: 1,
+                ob_type: tuple_type_address as *mut _,
+                ob_size: 1,
+                ob_item: [int_address as *mut _],
+            },
+        );
+        heap.write(
+            int_address,
+            bindings::PyObject {
+                ob_refcnt: 1,
+                ob_type: int_type_address as *mut _,
+            },
+        );
+        let mem = ShortReadMemory(heap.memory());
+
+        assert_eq!(mem.get_vec(int_address, PY_INT_OBJECT_SIZE)?.len(), 16);
+        assert!(matches!(
+            PyIntObject::<Cpython2_7>::try_deref(&mem, Pointer::new(int_address)),
+            Err(Error::Decode)
+        ));
+
+        let graph = walk::<Cpython2_7, _>(&mem, Pointer::new(tuple_address));
+        match graph.get(&DataPointer(tuple_address)) {
+            Some(DecodedData::Tuple(items)) => assert_eq!(items, &[DataPointer(int_address)]),
+            node => bail!("Expected a tuple, got {:?}", node),
+        }
+        assert!(matches!(
+            graph.get(&DataPointer(int_address)),
+            Some(DecodedData::Error(Error::Decode))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn slot_wrappers() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27(
+            r#"
+            class Foo(object):
+                pass
+            obj = Foo()
+            entry = (obj.__str__, obj)
+            "#,
+        )?;
+        let mem = crate::connect(child.pid)?;
+
+        let graph = walk::<Cpython2_7, _>(&mem, Pointer::new(child.pointer));
+        let items = match graph.get(&DataPointer(child.pointer)) {
+            Some(DecodedData::Tuple(items)) => items,
+            _ => bail!("Expected a tuple"),
+        };
+        let descriptor = match graph.get(&items[0]) {
+            Some(DecodedData::SlotWrapper {
+                descriptor,
+                bound_self,
+            }) => {
+                assert_eq!(bound_self, &items[1]);
+                descriptor
             }
-            match graph.get(&list[2]) {
-                Some(&DecodedData::Instance {
-                    ref instance_class_name,
-                    ref attributes,
-                    ..
-                }) => {
-                    assert_eq!(instance_class_name, "Something");
-                    match attributes
-                        .get("anything")
-                        .and_then(|pointer| graph.get(pointer))
-                    {
-                        Some(DecodedData::String(str)) => assert_eq!(str, "I'm here!"),
-                        _ => bail!("Expected an attribute"),
-                    }
+            node => bail!("Expected a method-wrapper, got {:?}", node),
+        };
+        match graph.get(descriptor) {
+            Some(DecodedData::WrapperDescriptor { owner, name }) => {
+                assert_eq!(name, "__str__");
+                assert!(
+                    matches!(graph.get(owner), Some(DecodedData::Type(owner)) if owner == "object")
+                );
+            }
+            node => bail!("Expected a wrapper_descriptor, got {:?}", node),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_builtin() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27(
+            r#"
+            class Foo(object):
+                pass
+            entry = (xrange(10), Foo())
+            "#,
+        )?;
+        let mem = crate::connect(child.pid)?;
+
+        let graph = walk::<Cpython2_7, _>(&mem, Pointer::new(child.pointer));
+        let items = match graph.get(&DataPointer(child.pointer)) {
+            Some(DecodedData::Tuple(items)) => items,
+            _ => bail!("Expected a tuple"),
+        };
+        match graph.get(&items[0]) {
+            Some(DecodedData::Unknown {
+                type_name,
+                raw_header,
+            }) => {
+                assert_eq!(type_name, "xrange");
+                assert_eq!(*raw_header, None);
+            }
+            node => bail!("Expected an unknown object, got {:?}", node),
+        }
+
+        // The header is only kept along with the raw capture.
+        let options = WalkOptions {
+            capture_raw: true,
+            ..WalkOptions::default()
+        };
+        let output =
+            walk_with_options::<Cpython2_7, _>(&mem, Pointer::new(child.pointer), &options, None);
+        match output.graph.get(&items[0]) {
+            Some(DecodedData::Unknown {
+                raw_header: Some(header),
+                ..
+            }) => {
+                assert!(!header.is_empty());
+                assert_eq!(header[..], output.raw[&items[0]][..header.len()]);
+                assert_eq!(header[..], mem.get_vec(items[0].0, header.len())?[..]);
+            }
+            node => bail!("Expected an unknown object with a header, got {:?}", node),
+        }
+        assert!(matches!(
+            graph.get(&items[1]),
+            Some(DecodedData::Object { object_type_name, .. }) if object_type_name == "Foo"
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn code_consts() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27(
+            r#"
+            def outer(argument):
+                def inner():
+                    return len
+                return (1, 'two', 3.0)
+            entry = outer.__code__
+            "#,
+        )?;
+        let mem = crate::connect(child.pid)?;
+
+        let graph = walk::<Cpython2_7, _>(&mem, Pointer::new(child.pointer));
+        let string = |pointer| match graph.get(pointer) {
+            Some(DecodedData::String(string)) => Some(string.as_str()),
+            _ => None,
+        };
+        let tuple = |pointer| match graph.get(pointer) {
+            Some(DecodedData::Tuple(items)) => items.as_slice(),
+            _ => &[],
+        };
+
+        let consts = match graph.get(&DataPointer(child.pointer)) {
+            Some(DecodedData::Code {
+                name,
+                firstlineno,
+                consts,
+                varnames,
+                ..
+            }) => {
+                assert_eq!(name, "outer");
+                assert_eq!(*firstlineno, 3);
+                assert_eq!(
+                    tuple(varnames).iter().map(string).collect::<Vec<_>>(),
+                    [Some("argument"), Some("inner")]
+                );
+                tuple(consts)
+            }
+            node => bail!("Expected a code object, got {:?}", node),
+        };
+
+        let inner = consts.iter().find_map(|pointer| match graph.get(pointer) {
+            Some(DecodedData::Code { name, names, .. }) => Some((name, names)),
+            _ => None,
+        });
+        match inner {
+            Some((name, names)) => {
+                assert_eq!(name, "inner");
+                assert_eq!(
+                    tuple(names).iter().map(string).collect::<Vec<_>>(),
+                    [Some("len")]
+                );
+            }
+            None => bail!("Expected the inner code object"),
+        }
+
+        let constant = consts
+            .iter()
+            .find(|pointer| tuple(pointer).len() == 3)
+            .ok_or_else(|| anyhow::anyhow!("Expected the constant tuple"))?;
+        assert_eq!(string(&tuple(constant)[1]), Some("two"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn code_frozenset_const() -> std::result::Result<(), anyhow::Error> {
+        // CPython 2.7 compiles `x in {1, 2, 3}` to `BUILD_SET`; later
+        // versions fold it into a frozenset constant, as built here.
+        let child = spawn_python27(
+            r#"
+            import types
+            def check(x):
+                return x in {1, 2, 3}
+            c = check.__code__
+            entry = types.CodeType(
+                c.co_argcount, c.co_nlocals, c.co_stacksize, c.co_flags, c.co_code,
+                c.co_consts + (frozenset([1, 2, 3]),), c.co_names, c.co_varnames,
+                c.co_filename, c.co_name, c.co_firstlineno, c.co_lnotab)
+            "#,
+        )?;
+        let mem = crate::connect(child.pid)?;
+
+        let graph = walk::<Cpython2_7, _>(&mem, Pointer::new(child.pointer));
+        let consts = match graph.get(&DataPointer(child.pointer)) {
+            Some(DecodedData::Code { consts, .. }) => graph
+                .get(consts)
+                .and_then(DecodedData::as_tuple)
+                .ok_or_else(|| anyhow::anyhow!("Expected a const tuple"))?,
+            node => bail!("Expected a code object, got {:?}", node),
+        };
+        let members = consts
+            .iter()
+            .find_map(|pointer| graph.get(pointer).and_then(DecodedData::as_set))
+            .ok_or_else(|| anyhow::anyhow!("Expected a frozenset const"))?;
+        let mut members: Vec<_> = members
+            .iter()
+            .map(|member| graph.get(member).and_then(DecodedData::as_int).cloned())
+            .collect();
+        members.sort();
+        assert_eq!(members, [1, 2, 3].map(|i| Some(BigInt::from(i))).to_vec());
+
+        Ok(())
+    }
+
+    #[test]
+    fn exception() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27(
+            r#"
+            try:
+                raise ValueError('bad value', 42)
+            except ValueError as error:
+                entry = error
+            "#,
+        )?;
+        let mem = crate::connect(child.pid)?;
+
+        let graph = walk::<Cpython2_7, _>(&mem, Pointer::new(child.pointer));
+        let args = match graph.get(&DataPointer(child.pointer)) {
+            Some(DecodedData::Exception {
+                type_name, args, ..
+            }) => {
+                assert_eq!(type_name, "exceptions.ValueError");
+                args
+            }
+            _ => bail!("Expected an exception"),
+        };
+        match graph.get(args) {
+            Some(DecodedData::Tuple(items)) => {
+                assert_eq!(items.len(), 2);
+                match graph.get(&items[0]) {
+                    Some(DecodedData::String(str)) => assert_eq!(str, "bad value"),
+                    _ => bail!("Expected a string"),
+                }
+                match graph.get(&items[1]) {
+                    Some(DecodedData::Int(int)) => assert_eq!(int, &BigInt::from(42)),
+                    _ => bail!("Expected an int"),
                 }
-                _ => bail!("Expected an instance"),
             }
-            match graph.get(&list[3]) {
-                Some(&DecodedData::Tuple(ref items)) => {
-                    assert_eq!(items.len(), 2);
+            _ => bail!("Expected a tuple"),
+        }
 
-                    match graph.get(&items[0]) {
-                        Some(&DecodedData::Int(ref int)) => {
-                            assert_eq!(int, &num_bigint::BigInt::from(22))
-                        }
-                        _ => bail!("Expected an int"),
-                    }
-                    match graph.get(&items[1]) {
-                        Some(&DecodedData::Int(ref int)) => {
-                            assert_eq!(int, &num_bigint::BigInt::from(1000))
-                        }
-                        _ => bail!("Expected an int"),
-                    }
+        Ok(())
+    }
+
+    #[test]
+    fn var_sized_instance_dict() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27(
+            r#"
+            class Point(tuple):
+                pass
+            entry = Point((1, 2, 3))
+            entry.label = 'origin'
+            "#,
+        )?;
+        let mem = crate::connect(child.pid)?;
+
+        let graph = walk::<Cpython2_7, _>(&mem, Pointer::new(child.pointer));
+        match graph.get(&DataPointer(child.pointer)) {
+            Some(DecodedData::Object {
+                object_type_name,
+                attributes,
+                base_value,
+                ..
+            }) => {
+                assert_eq!(object_type_name, "Point");
+                assert!(
+                    matches!(base_value.as_deref(), Some(DecodedData::Tuple(items)) if items.len() == 3)
+                );
+                match attributes.get("label").and_then(|label| graph.get(label)) {
+                    Some(DecodedData::String(str)) => assert_eq!(str, "origin"),
+                    _ => bail!("Expected a string attribute"),
                 }
-                _ => bail!("Expected a tuple"),
             }
-        } else {
-            bail!("Expected a list")
+            _ => bail!("Expected an object"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn old_style_class_bases() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27(
+            r#"
+            class Left:
+                pass
+            class Right:
+                pass
+            class Both(Left, Right):
+                pass
+            entry = Both
+            "#,
+        )?;
+        let mem = crate::connect(child.pid)?;
+
+        let graph = walk::<Cpython2_7, _>(&mem, Pointer::new(child.pointer));
+        let bases = match graph.get(&DataPointer(child.pointer)) {
+            Some(DecodedData::Class { class_name, bases }) => {
+                assert_eq!(class_name, "Both");
+                bases
+            }
+            _ => bail!("Expected a class"),
+        };
+        let base_names: Vec<&str> = bases
+            .iter()
+            .map(|base| match graph.get(base) {
+                Some(DecodedData::Class { class_name, bases }) => {
+                    assert!(bases.is_empty());
+                    Ok(class_name.as_str())
+                }
+                _ => bail!("Expected a base class"),
+            })
+            .collect::<std::result::Result<_, _>>()?;
+        assert_eq!(base_names, ["Left", "Right"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn traceback() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27(
+            r#"
+            def fail():
+                raise ValueError('bad value')
+            try:
+                fail()
+            except ValueError:
+                entry = sys.exc_info()[2]
+            "#,
+        )?;
+        let mem = crate::connect(child.pid)?;
+
+        let graph = walk::<Cpython2_7, _>(&mem, Pointer::new(child.pointer));
+        // The harness prepends `import sys` and the source starts with a blank
+        // line, so the `fail()` call is on line 6 and the raise on line 4.
+        let next = match graph.get(&DataPointer(child.pointer)) {
+            Some(DecodedData::Traceback {
+                frame,
+                lineno,
+                next,
+            }) => {
+                assert_eq!(*lineno, 6);
+                assert!(matches!(
+                    graph.get(frame),
+                    Some(DecodedData::Unknown { type_name, .. }) if type_name == "frame"
+                ));
+                next.expect("an inner traceback")
+            }
+            _ => bail!("Expected a traceback"),
+        };
+        match graph.get(&next) {
+            Some(DecodedData::Traceback { lineno, next, .. }) => {
+                assert_eq!(*lineno, 4);
+                assert_eq!(*next, None);
+            }
+            _ => bail!("Expected a traceback"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn clone_objects() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27(
+            r#"
+            class Old:
+                pass
+            entry = (
+                None, Old, Old(), 'str', u'unicode', (1,), [1], {1: 2}, True, 1, 1.5,
+                property(), ValueError(), memoryview('view'), sys.exc_info, int,
+            )
+            "#,
+        )?;
+        let mem = crate::connect(child.pid)?;
+
+        // Goes through the `Clone` bound, whether or not the type is `Copy`.
+        fn cloned<T: Clone>(value: &T) -> T {
+            value.clone()
+        }
+
+        let tuple: PyTupleObject<Cpython2_7> = Pointer::new(child.pointer).try_deref_me(&mem)?;
+        for item in tuple.items(&mem) {
+            let object = item?;
+            let type_object = object.ob_type(&mem)?;
+            let typed = type_object.downcast(&mem, object)?;
+
+            let cloned = (cloned(&object), cloned(&type_object), cloned(&typed));
+            assert_eq!(cloned.0.me(), object.me());
+            assert_eq!(cloned.1.name(), type_object.name());
+            assert_eq!(
+                std::mem::discriminant(&cloned.2.object_type()),
+                std::mem::discriminant(&typed.object_type())
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn int_subclass() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27(
+            r#"
+            class Tagged(int):
+                pass
+            entry = Tagged(7)
+            entry.tag = 'seven'
+            "#,
+        )?;
+        let mem = crate::connect(child.pid)?;
+
+        let graph = walk::<Cpython2_7, _>(&mem, Pointer::new(child.pointer));
+        match graph.get(&DataPointer(child.pointer)) {
+            Some(DecodedData::Object {
+                object_type_name,
+                attributes,
+                base_value,
+                ..
+            }) => {
+                assert_eq!(object_type_name, "Tagged");
+                match base_value.as_deref() {
+                    Some(DecodedData::Int(int)) => assert_eq!(int, &BigInt::from(7)),
+                    _ => bail!("Expected an int value"),
+                }
+                match attributes.get("tag").and_then(|tag| graph.get(tag)) {
+                    Some(DecodedData::String(str)) => assert_eq!(str, "seven"),
+                    _ => bail!("Expected a string attribute"),
+                }
+            }
+            _ => bail!("Expected an object"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn main_module_globals() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27(
+            r#"
+            answer = 42
+            entry = sys.modules['__main__']
+            "#,
+        )?;
+        let mem = crate::connect(child.pid)?;
+
+        let globals = main_globals::<Cpython2_7, _>(&mem, Pointer::new(child.pointer))?;
+        let graph = walk::<Cpython2_7, _>(&mem, globals);
+        let entries = match graph.get(&DataPointer(globals.address())) {
+            Some(DecodedData::Dict { entries, .. }) => entries,
+            _ => bail!("Expected a dict"),
+        };
+        let answer = entries
+            .iter()
+            .find(|(key, _)| matches!(graph.get(key), Some(DecodedData::String(key)) if key == "answer"))
+            .map(|(_, value)| value);
+        match answer.and_then(|answer| graph.get(answer)) {
+            Some(DecodedData::Int(int)) => assert_eq!(int, &BigInt::from(42)),
+            _ => bail!("Expected the global `answer`"),
+        }
+
+        // The globals dict is not a module.
+        assert!(matches!(
+            main_globals::<Cpython2_7, _>(&mem, globals),
+            Err(Error::Decode)
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn small_and_large_dict_tables() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27(
+            r#"
+            entry = ({1: 2, 3: 4}, dict((i, -i) for i in range(100)))
+            "#,
+        )?;
+        let mem = crate::connect(child.pid)?;
+
+        fn int(mem: &impl Memory, object: &PyObject<Cpython2_7>) -> anyhow::Result<BigInt> {
+            match object.ob_type(mem)?.downcast(mem, *object)?.as_int() {
+                Some(int) => Ok(int.read(mem)?),
+                None => bail!("Expected an int"),
+            }
+        }
+
+        let tuple: PyTupleObject<Cpython2_7> = Pointer::new(child.pointer).try_deref_me(&mem)?;
+        let items = tuple.items(&mem).collect::<Result<Vec<_>>>()?;
+        let small: PyDictObject<Cpython2_7> = items[0].me().try_deref_me(&mem)?;
+        let large: PyDictObject<Cpython2_7> = items[1].me().try_deref_me(&mem)?;
+
+        assert!(small.table_is_embedded());
+        assert!(!large.table_is_embedded());
+
+        let (small, capped) = small.entries(&mem, DEFAULT_MAX_DICT_SLOTS)?;
+        assert!(!capped);
+        assert_eq!(small.len(), 2);
+        for entry in &small {
+            assert_eq!(int(&mem, entry.value())?, int(&mem, entry.key())? + 1);
+        }
+
+        // One read for the whole table, and one for each key and value.
+        let counting = CountingMemory::new(&mem);
+        let (large, _) = large.entries(&counting, DEFAULT_MAX_DICT_SLOTS)?;
+        assert_eq!(large.len(), 100);
+        assert_eq!(counting.reads(), 1 + 2 * 100);
+        for entry in &large {
+            assert_eq!(int(&mem, entry.value())?, -int(&mem, entry.key())?);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn dict_keys_only() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27("entry = {'a': 1.5, 'b': 2.5, 'c': 3.5}")?;
+        let mem = crate::connect(child.pid)?;
+        let dict: PyDictObject<Cpython2_7> = Pointer::new(child.pointer).try_deref_me(&mem)?;
+
+        // The table is embedded, so only the keys themselves are read.
+        let counting = CountingMemory::new(&mem);
+        let keys = dict
+            .keys_iter(&counting, DEFAULT_MAX_DICT_SLOTS)
+            .collect::<Result<Vec<_>>>()?;
+        assert_eq!(counting.reads(), 3);
+
+        let mut keys = keys
+            .into_iter()
+            .map(
+                |key| match key.ob_type(&mem)?.downcast(&mem, key)?.as_string() {
+                    Some(string) => Ok(string.read(&mem)?),
+                    None => bail!("Expected a string key"),
+                },
+            )
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        keys.sort_unstable();
+        assert_eq!(keys, ["a", "b", "c"]);
+
+        let values = dict.values_iter(&mem, DEFAULT_MAX_DICT_SLOTS);
+        assert!(!values.capped());
+        assert_eq!(values.count(), 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn partial_dict() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27("entry = dict((i, None) for i in range(12000))")?;
+        let mem = crate::connect(child.pid)?;
+
+        let dict = |max_dict_slots| {
+            let options = WalkOptions {
+                max_dict_slots,
+                ..WalkOptions::default()
+            };
+            walk_with_options::<Cpython2_7, _>(&mem, Pointer::new(child.pointer), &options, None)
+                .graph
+                .remove(&DataPointer(child.pointer))
+        };
+
+        match dict(None) {
+            Some(DecodedData::Dict { entries, partial }) => {
+                assert_eq!(entries.len(), 12000);
+                assert!(!partial);
+            }
+            node => bail!("Expected a dict, got {:?}", node),
+        }
+        match dict(Some(10_000)) {
+            Some(DecodedData::Dict { entries, partial }) => {
+                assert!(entries.len() < 12000);
+                assert!(partial);
+            }
+            node => bail!("Expected a dict, got {:?}", node),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn unicode() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27(r#"entry = u'h\xe9llo w\xf6rld \u2603 \U0001f600'"#)?;
+        let mem = crate::connect(child.pid)?;
+
+        let unicode: PyUnicodeObject<Cpython2_7> =
+            Pointer::new(child.pointer).try_deref_me(&mem)?;
+        assert_eq!(
+            unicode.read(&mem)?,
+            "h\u{e9}llo w\u{f6}rld \u{2603} \u{1f600}"
+        );
+        assert_eq!(
+            unicode.read_bytes(&mem)?.len(),
+            unicode.size() as usize * PY_UNICODE_SIZE
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn buffer() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27("entry = (bytearray(b'hello world'), bytearray(), {})")?;
+        let mem = crate::connect(child.pid)?;
+
+        let graph = walk::<Cpython2_7, _>(&mem, Pointer::new(child.pointer));
+        let items = match graph.get(&DataPointer(child.pointer)) {
+            Some(DecodedData::Tuple(items)) => items,
+            node => bail!("Expected a tuple, got {:?}", node),
+        };
+        assert!(matches!(
+            graph.get(&items[0]),
+            Some(DecodedData::Buffer(bytes)) if bytes == b"hello world"
+        ));
+        assert!(matches!(
+            graph.get(&items[1]),
+            Some(DecodedData::Buffer(bytes)) if bytes.is_empty()
+        ));
+
+        // `dict` exports no buffer.
+        let dict = Pointer::new(items[2].0).try_deref_me(&mem)?;
+        assert_eq!(try_read_buffer::<Cpython2_7, _>(&mem, &dict)?, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn memory_view() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27(
+            r#"
+            array = bytearray(b'hello world')
+            entry = (memoryview(array), array, buffer('hello', 1, 3))
+            "#,
+        )?;
+        let mem = crate::connect(child.pid)?;
+
+        let graph = walk::<Cpython2_7, _>(&mem, Pointer::new(child.pointer));
+        let items = match graph.get(&DataPointer(child.pointer)) {
+            Some(DecodedData::Tuple(items)) => items,
+            _ => bail!("Expected a tuple"),
+        };
+        match graph.get(&items[0]) {
+            Some(DecodedData::MemoryView {
+                base,
+                len,
+                readonly,
+            }) => {
+                assert_eq!(base, &Some(items[1]));
+                assert_eq!(*len, 11);
+                assert!(!readonly);
+            }
+            _ => bail!("Expected a memoryview"),
+        }
+        match graph.get(&items[2]) {
+            Some(DecodedData::MemoryView { base, len, .. }) => {
+                assert!(matches!(
+                    base.and_then(|base| graph.get(&base)),
+                    Some(DecodedData::String(str)) if str == "hello"
+                ));
+                assert_eq!(*len, 3);
+            }
+            _ => bail!("Expected a buffer"),
         }
 
         Ok(())
@@ -12,6 +12,104 @@ pub enum Error {
     Decode,
     #[error("Could not connect to remote process.")]
     RemoteProcessConnect(#[source] remoteprocess::Error),
+    #[error("No process with PID {0}.")]
+    ProcessNotFound(remoteprocess::Pid),
+    #[error("Not permitted to read the memory of process {0}. Try running as root, or allow ptrace by setting /proc/sys/kernel/yama/ptrace_scope to 0.")]
+    PermissionDenied(remoteprocess::Pid),
     #[error("Requested size too big")]
     SizeError,
+    #[error("Symbol {0} not found in the libraries loaded by the target.")]
+    SymbolNotFound(String),
+}
+
+/// A field-less discriminant of [`Error`], for categorizing failures without
+/// matching on the `Display` output.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    SegmentationFault,
+    NullPointer,
+    Decode,
+    RemoteProcessConnect,
+    ProcessNotFound,
+    PermissionDenied,
+    SizeError,
+    SymbolNotFound,
+}
+
+impl Error {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Error::SegmentationFault(_) => ErrorKind::SegmentationFault,
+            Error::NullPointer => ErrorKind::NullPointer,
+            Error::Decode => ErrorKind::Decode,
+            Error::RemoteProcessConnect(_) => ErrorKind::RemoteProcessConnect,
+            Error::ProcessNotFound(_) => ErrorKind::ProcessNotFound,
+            Error::PermissionDenied(_) => ErrorKind::PermissionDenied,
+            Error::SizeError => ErrorKind::SizeError,
+            Error::SymbolNotFound(_) => ErrorKind::SymbolNotFound,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::MemoryError;
+    use std::error::Error as _;
+
+    #[test]
+    fn kind() {
+        let cases = [
+            (
+                Error::SegmentationFault(MemoryError::InvalidSize("0".into()).into()),
+                ErrorKind::SegmentationFault,
+            ),
+            (Error::NullPointer, ErrorKind::NullPointer),
+            (Error::Decode, ErrorKind::Decode),
+            (
+                Error::RemoteProcessConnect(remoteprocess::Error::Other("gone".into())),
+                ErrorKind::RemoteProcessConnect,
+            ),
+            (Error::ProcessNotFound(1), ErrorKind::ProcessNotFound),
+            (Error::PermissionDenied(1), ErrorKind::PermissionDenied),
+            (Error::SizeError, ErrorKind::SizeError),
+            (
+                Error::SymbolNotFound("PyInt_Type".into()),
+                ErrorKind::SymbolNotFound,
+            ),
+        ];
+        for (error, kind) in cases {
+            assert_eq!(error.kind(), kind);
+        }
+    }
+
+    #[test]
+    fn source_chaining() {
+        let error = Error::SegmentationFault(
+            MemoryError::Unmapped {
+                address: 0x1000,
+                size: 8,
+            }
+            .into(),
+        );
+        let source = error.source().expect("segmentation faults carry a source");
+        assert_eq!(source.to_string(), "Address range 0x1000+8 is not mapped");
+
+        let error = Error::RemoteProcessConnect(remoteprocess::Error::Other("gone".into()));
+        assert_eq!(error.source().map(ToString::to_string), Some("gone".into()));
+
+        assert!(Error::NullPointer.source().is_none());
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn connect_missing_process() {
+        // Beyond the largest `pid_max` Linux allows.
+        let pid = i32::MAX;
+        assert!(matches!(
+            crate::connect(pid),
+            Err(Error::ProcessNotFound(missing)) if missing == pid
+        ));
+    }
 }
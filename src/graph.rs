@@ -0,0 +1,164 @@
+//! A walked object graph with query helpers.
+
+use std::collections::HashMap;
+
+use crate::interpreter::{Interpreter, Pointer, Type};
+use crate::memory::Memory;
+use crate::profile;
+use crate::repr;
+use crate::validate::{self, Inconsistency};
+use crate::walker::{self, DataPointer, DecodedData};
+
+/// The nodes of a walk, keyed by address, together with the address the walk
+/// started at.
+#[derive(Debug)]
+pub struct Graph {
+    root: DataPointer,
+    nodes: HashMap<DataPointer, DecodedData>,
+}
+
+impl Graph {
+    pub fn new(root: DataPointer, nodes: HashMap<DataPointer, DecodedData>) -> Self {
+        Self { root, nodes }
+    }
+
+    /// Walk the object at `pointer`. See [`walker::walk`].
+    pub fn walk<I, M>(mem: &M, pointer: Pointer) -> Self
+    where
+        I: Interpreter,
+        M: Memory + ?Sized,
+    {
+        Self::new(
+            DataPointer::from(pointer),
+            walker::walk::<I, M>(mem, pointer),
+        )
+    }
+
+    pub fn root_pointer(&self) -> DataPointer {
+        self.root
+    }
+
+    /// The node the walk started at. This is `None` only if the root itself
+    /// could not be read.
+    pub fn root(&self) -> Option<&DecodedData> {
+        self.nodes.get(&self.root)
+    }
+
+    pub fn get(&self, pointer: DataPointer) -> Option<&DecodedData> {
+        self.nodes.get(&pointer)
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (DataPointer, &DecodedData)> {
+        self.nodes.iter().map(|(&pointer, data)| (pointer, data))
+    }
+
+    /// The nodes of kind `kind`. See [`DecodedData::kind`].
+    pub fn iter_type(&self, kind: Type) -> impl Iterator<Item = (DataPointer, &DecodedData)> {
+        self.iter()
+            .filter(move |(_, data)| data.kind() == Some(kind))
+    }
+
+    /// The contents of all `str` and `unicode` nodes.
+    pub fn strings(&self) -> impl Iterator<Item = &str> {
+        self.nodes.values().filter_map(|data| match data {
+            DecodedData::String(string) => Some(string.as_str()),
+            _ => None,
+        })
+    }
+
+    /// See [`profile::summarize`].
+    pub fn summarize(&self) -> HashMap<String, usize> {
+        profile::summarize(&self.nodes)
+    }
+
+    /// Render the root like Python's `repr()`. See [`repr::to_repr`].
+    pub fn to_repr(&self, max_depth: usize, max_len: usize) -> String {
+        repr::to_repr(&self.nodes, self.root, max_depth, max_len)
+    }
+
+    /// See [`validate::validate`].
+    pub fn validate(&self) -> Vec<Inconsistency> {
+        validate::validate(&self.nodes)
+    }
+
+    pub fn nodes(&self) -> &HashMap<DataPointer, DecodedData> {
+        &self.nodes
+    }
+
+    pub fn into_nodes(self) -> HashMap<DataPointer, DecodedData> {
+        self.nodes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cpython27::Cpython2_7;
+    use crate::error::Error;
+    use crate::testing::spawn_python27;
+
+    #[test]
+    fn queries() {
+        let mut nodes = HashMap::new();
+        nodes.insert(
+            DataPointer(0x100),
+            DecodedData::List(vec![
+                DataPointer(0x200),
+                DataPointer(0x300),
+                DataPointer(0x400),
+            ]),
+        );
+        nodes.insert(DataPointer(0x200), DecodedData::String("a".to_owned()));
+        nodes.insert(DataPointer(0x300), DecodedData::String("b".to_owned()));
+        nodes.insert(DataPointer(0x400), DecodedData::Error(Error::Decode));
+        let graph = Graph::new(DataPointer(0x100), nodes);
+
+        assert_eq!(graph.len(), 4);
+        assert!(!graph.is_empty());
+        assert!(matches!(graph.root(), Some(DecodedData::List(items)) if items.len() == 3));
+        assert!(matches!(graph.get(DataPointer(0x200)), Some(DecodedData::String(a)) if a == "a"));
+        assert!(graph.get(DataPointer(0x500)).is_none());
+
+        let mut strings: Vec<&str> = graph.strings().collect();
+        strings.sort_unstable();
+        assert_eq!(strings, ["a", "b"]);
+
+        let mut pointers: Vec<DataPointer> = graph
+            .iter_type(Type::String)
+            .map(|(pointer, _)| pointer)
+            .collect();
+        pointers.sort_unstable_by_key(|pointer| pointer.0);
+        assert_eq!(pointers, [DataPointer(0x200), DataPointer(0x300)]);
+        assert_eq!(graph.iter_type(Type::List).count(), 1);
+        assert_eq!(graph.iter_type(Type::Dict).count(), 0);
+
+        assert_eq!(graph.summarize()["str"], 2);
+        assert!(graph.validate().is_empty());
+    }
+
+    #[test]
+    fn walk() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27("entry = {'key': [1.5, u'value']}")?;
+        let mem = crate::connect(child.pid)?;
+
+        let graph = Graph::walk::<Cpython2_7, _>(&mem, Pointer::new(child.pointer));
+        assert_eq!(graph.root_pointer(), DataPointer(child.pointer));
+        assert!(
+            matches!(graph.root(), Some(DecodedData::Dict { entries, .. }) if entries.len() == 1)
+        );
+        let mut strings: Vec<&str> = graph.strings().collect();
+        strings.sort_unstable();
+        assert_eq!(strings, ["key", "value"]);
+        assert_eq!(graph.iter_type(Type::Float).count(), 1);
+
+        Ok(())
+    }
+}
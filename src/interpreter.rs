@@ -6,6 +6,7 @@ use crate::memory::Memory;
 
 pub const PY_SIZE_T: usize = std::mem::size_of::<usize>();
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Type {
     Type,
     Object,
@@ -21,29 +22,75 @@ pub enum Type {
     Bool,
     Int,
     Float,
+    Property,
+    Exception,
+    MemoryView,
+    Traceback,
+    Set,
+    ClassMethod,
+    StaticMethod,
+    Mmap,
+    WrapperDescriptor,
+    SlotWrapper,
+    Code,
+    #[cfg(feature = "datetime")]
+    DateTime,
+    Enumerate,
+    Reversed,
+    Slice,
+    DefaultDict,
+    Long,
+    Deque,
+    Capsule,
+    Array,
 }
 
 /// Implementors of this trait collect together specific CPython object
 /// implementations. This allows mixing and matching of implementations. Usually
 /// this trait will be implemented by a marker type.
+///
+/// All object types are `Clone`, so they can be stashed by custom traversals.
+/// Whether they are also `Copy` is up to the implementation.
 pub trait Interpreter: Copy + Clone + std::fmt::Debug {
-    type TypedObject: TypedObject<Self>;
-    type TypeObject: TypeObject<Self> + TryDeref;
-    type Object: Object<Self> + TryDeref;
-    type VarObject: VarObject<Self> + TryDeref;
-    type ClassObject: ClassObject<Self> + TryDeref;
-    type InstanceObject: InstanceObject<Self> + TryDeref;
-    type NoneObject: NoneObject<Self> + TryDeref;
-    type BytesObject: BytesObject<Self>;
-    type StringObject: StringObject<Self> + TryDeref;
-    type UnicodeObject: UnicodeObject<Self> + TryDeref;
-    type TupleObject: TupleObject<Self> + TryDeref;
-    type ListObject: ListObject<Self> + TryDeref;
-    type DictEntry: DictEntry<Self>;
-    type DictObject: DictObject<Self> + TryDeref;
-    type BoolObject: BoolObject<Self> + TryDeref;
-    type IntObject: IntObject<Self> + TryDeref;
-    type FloatObject: FloatObject<Self> + TryDeref;
+    /// The number of bytes of a type's `tp_name` that are read. Longer names
+    /// are truncated; see `TypeObject::name_truncated`.
+    const MAX_TYPE_NAME_LENGTH: usize = 1000;
+
+    type TypedObject: TypedObject<Self> + Clone;
+    type TypeObject: TypeObject<Self> + TryDeref + Clone;
+    type Object: Object<Self> + TryDeref + Clone;
+    type VarObject: VarObject<Self> + TryDeref + Clone;
+    type ClassObject: ClassObject<Self> + TryDeref + Clone;
+    type InstanceObject: InstanceObject<Self> + TryDeref + Clone;
+    type NoneObject: NoneObject<Self> + TryDeref + Clone;
+    type BytesObject: BytesObject<Self> + TryDeref + Clone;
+    type StringObject: StringObject<Self> + TryDeref + Clone;
+    type UnicodeObject: UnicodeObject<Self> + TryDeref + Clone;
+    type TupleObject: TupleObject<Self> + TryDeref + Clone;
+    type ListObject: ListObject<Self> + TryDeref + Clone;
+    type DictEntry: DictEntry<Self> + Clone;
+    type DictObject: DictObject<Self> + TryDeref + Clone;
+    type BoolObject: BoolObject<Self> + TryDeref + Clone;
+    type IntObject: IntObject<Self> + TryDeref + Clone;
+    type FloatObject: FloatObject<Self> + TryDeref + Clone;
+    type PropertyObject: PropertyObject<Self> + TryDeref + Clone;
+    type ExceptionObject: ExceptionObject<Self> + TryDeref + Clone;
+    type MemoryViewObject: MemoryViewObject<Self> + TryDeref + Clone;
+    type TracebackObject: TracebackObject<Self> + TryDeref + Clone;
+    type SetObject: SetObject<Self> + TryDeref + Clone;
+    type MethodWrapperObject: MethodWrapperObject<Self> + TryDeref + Clone;
+    type MmapObject: MmapObject<Self> + TryDeref + Clone;
+    type WrapperDescriptorObject: WrapperDescriptorObject<Self> + TryDeref + Clone;
+    type SlotWrapperObject: SlotWrapperObject<Self> + TryDeref + Clone;
+    type CodeObject: CodeObject<Self> + TryDeref + Clone;
+    type DateTimeObject: DateTimeObject<Self> + Clone;
+    type IndexedIteratorObject: IndexedIteratorObject<Self> + TryDeref + Clone;
+    type SliceObject: SliceObject<Self> + TryDeref + Clone;
+    type DefaultDictObject: DefaultDictObject<Self> + TryDeref + Clone;
+    type LongObject: LongObject<Self> + TryDeref + Clone;
+    type DequeObject: DequeObject<Self> + TryDeref + Clone;
+    type CapsuleObject: CapsuleObject<Self> + TryDeref + Clone;
+    type ArrayObject: ArrayObject<Self> + TryDeref + Clone;
 }
 
 #[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
@@ -76,11 +123,15 @@ impl Pointer {
         Self { address }
     }
 
-    pub fn get_usize(&self, mem: &impl Memory) -> Result<usize> {
+    pub fn get_usize(&self, mem: &(impl Memory + ?Sized)) -> Result<usize> {
         mem.get_usize(self.address)
     }
 
-    pub fn deref_c_str(&self, mem: &impl Memory, max_length: Option<usize>) -> Result<String> {
+    pub fn deref_c_str(
+        &self,
+        mem: &(impl Memory + ?Sized),
+        max_length: Option<usize>,
+    ) -> Result<String> {
         mem.get_c_str(self.address_checked()?, max_length)
     }
 
@@ -88,11 +139,23 @@ impl Pointer {
         self.address
     }
 
+    /// The pointer `offset` bytes further on, or `None` if that overflows,
+    /// where `+` would wrap around to a small address.
+    pub fn checked_add(&self, offset: usize) -> Option<Self> {
+        self.address.checked_add(offset).map(Self::new)
+    }
+
+    /// The pointer `offset` bytes further on or, if `offset` is negative,
+    /// back, or `None` if that overflows or underflows.
+    pub fn checked_add_signed(&self, offset: isize) -> Option<Self> {
+        self.address.checked_add_signed(offset).map(Self::new)
+    }
+
     pub fn null(&self) -> bool {
         self.address == 0
     }
 
-    pub fn try_deref_me<O: TryDeref>(&self, mem: &impl Memory) -> Result<O> {
+    pub fn try_deref_me<O: TryDeref>(&self, mem: &(impl Memory + ?Sized)) -> Result<O> {
         if self.null() {
             return Err(Error::NullPointer);
         }
@@ -109,13 +172,14 @@ impl Pointer {
 }
 
 impl TryDeref for Pointer {
-    fn try_deref(mem: &impl Memory, pointer: Self) -> Result<Self> {
+    fn try_deref(mem: &(impl Memory + ?Sized), pointer: Self) -> Result<Self> {
         Ok(Self {
             address: pointer.get_usize(mem)?,
         })
     }
 }
 
+#[allow(clippy::wrong_self_convention)]
 pub trait TypedObject<I: Interpreter> {
     fn object_type(&self) -> Type;
     fn as_type(self) -> Option<I::TypeObject>;
@@ -132,28 +196,89 @@ pub trait TypedObject<I: Interpreter> {
     fn as_bool(self) -> Option<I::BoolObject>;
     fn as_int(self) -> Option<I::IntObject>;
     fn as_float(self) -> Option<I::FloatObject>;
+    fn as_property(self) -> Option<I::PropertyObject>;
+    fn as_exception(self) -> Option<I::ExceptionObject>;
+    fn as_memory_view(self) -> Option<I::MemoryViewObject>;
+    fn as_traceback(self) -> Option<I::TracebackObject>;
+    fn as_set(self) -> Option<I::SetObject>;
+    fn as_class_method(self) -> Option<I::MethodWrapperObject>;
+    fn as_static_method(self) -> Option<I::MethodWrapperObject>;
+    fn as_mmap(self) -> Option<I::MmapObject>;
+    fn as_wrapper_descriptor(self) -> Option<I::WrapperDescriptorObject>;
+    fn as_slot_wrapper(self) -> Option<I::SlotWrapperObject>;
+    fn as_code(self) -> Option<I::CodeObject>;
+    #[cfg(feature = "datetime")]
+    fn as_date_time(self) -> Option<I::DateTimeObject>;
+    fn as_enumerate(self) -> Option<I::IndexedIteratorObject>;
+    fn as_reversed(self) -> Option<I::IndexedIteratorObject>;
+    fn as_slice(self) -> Option<I::SliceObject>;
+    fn as_default_dict(self) -> Option<I::DefaultDictObject>;
+    fn as_long(self) -> Option<I::LongObject>;
+    fn as_deque(self) -> Option<I::DequeObject>;
+    fn as_capsule(self) -> Option<I::CapsuleObject>;
+    fn as_array(self) -> Option<I::ArrayObject>;
 }
 
 pub trait TryDeref: Sized {
-    fn try_deref(mem: &impl Memory, pointer: Pointer) -> Result<Self>;
+    fn try_deref(mem: &(impl Memory + ?Sized), pointer: Pointer) -> Result<Self>;
 }
 
 pub trait TypeObject<I: Interpreter> {
     fn to_var_object(&self) -> I::VarObject;
     fn name(&self) -> &str;
+    /// Whether `tp_name` is longer than `Interpreter::MAX_TYPE_NAME_LENGTH`,
+    /// so that `name` holds only its start.
+    fn name_truncated(&self) -> bool;
     fn tp_basicsize(&self) -> isize;
     fn tp_itemsize(&self) -> isize;
     fn tp_dictoffset(&self) -> isize;
-    fn downcast(&self, mem: &impl Memory, object: I::Object) -> Result<I::TypedObject>;
+    /// The offset of the head of an instance's weakref list, or 0 if
+    /// instances cannot be weakly referenced.
+    fn tp_weaklistoffset(&self) -> isize;
+    /// The method resolution order: a tuple of type objects, starting with
+    /// this type. This is NULL for types that have not been readied.
+    fn tp_mro(&self, mem: &(impl Memory + ?Sized)) -> Result<Option<I::TupleObject>>;
+    /// The single base type this type was derived from, which is `None` only
+    /// for `object`. Unlike `tp_mro`, this is set for static types before
+    /// they are readied.
+    fn tp_base(&self, mem: &(impl Memory + ?Sized)) -> Result<Option<I::TypeObject>>;
+    /// The type's attribute dict, holding its methods and class variables.
+    /// This is NULL for static types that have not been readied.
+    fn tp_dict(&self, mem: &(impl Memory + ?Sized)) -> Result<Option<I::DictObject>>;
+    /// For subclasses of builtins with a decodable value (e.g. `class
+    /// Tagged(int)`), the builtin's type. Exact builtins report their own type.
+    fn builtin_base(&self) -> Option<Type>;
+    /// Whether the type was created at runtime, e.g. by a `class` statement,
+    /// rather than defined statically in C.
+    fn is_heap_type(&self) -> bool;
+    /// The type's `tp_as_buffer` slot, which is NULL unless its instances
+    /// export a buffer.
+    fn tp_as_buffer(&self) -> Pointer;
+    /// The address and length in bytes of the buffer that `object`, an
+    /// instance of this type, exports. `None` if the type exports no buffer,
+    /// or if where an instance keeps its buffer is not known for the type:
+    /// the buffer protocol itself cannot be called in a remote process.
+    fn exported_buffer(
+        &self,
+        mem: &(impl Memory + ?Sized),
+        object: &I::Object,
+    ) -> Result<Option<(Pointer, usize)>>;
+    fn downcast(&self, mem: &(impl Memory + ?Sized), object: I::Object) -> Result<I::TypedObject>;
 }
 
 pub trait Object<I: Interpreter<Object = Self>> {
     fn me(&self) -> Pointer;
-    fn ob_type(&self, mem: &impl Memory) -> Result<I::TypeObject>;
+    fn ob_refcnt(&self) -> isize;
+    fn ob_type(&self, mem: &(impl Memory + ?Sized)) -> Result<I::TypeObject>;
     fn ob_type_pointer(&self) -> Pointer;
-    fn attributes(&self, mem: &impl Memory) -> Result<Option<I::DictObject>>;
+    fn attributes(&self, mem: &(impl Memory + ?Sized)) -> Result<Option<I::DictObject>>;
+    /// The `weakref`s and proxies referring to this object, in the order of
+    /// its weakref list.
+    fn weak_references(&self, mem: &(impl Memory + ?Sized)) -> Result<Vec<I::Object>>
+    where
+        Self: Sized;
 
-    fn downcast(self, mem: &impl Memory) -> Result<I::TypedObject>
+    fn downcast(self, mem: &(impl Memory + ?Sized)) -> Result<I::TypedObject>
     where
         Self: Sized,
     {
@@ -164,19 +289,20 @@ pub trait Object<I: Interpreter<Object = Self>> {
 pub trait VarObject<I: Interpreter<VarObject = Self>> {
     fn to_object(&self) -> I::Object;
     fn ob_size(&self) -> isize;
-    fn attributes(&self, mem: &impl Memory) -> Result<Option<I::DictObject>>;
+    fn attributes(&self, mem: &(impl Memory + ?Sized)) -> Result<Option<I::DictObject>>;
 }
 
 pub trait ClassObject<I: Interpreter> {
     fn to_object(&self) -> I::Object;
     fn name(&self) -> &str;
-    fn bases(&self, mem: &impl Memory) -> Result<Option<I::ClassObject>>;
+    /// The direct base classes, in declaration order.
+    fn bases(&self, mem: &(impl Memory + ?Sized)) -> Result<Vec<I::ClassObject>>;
 }
 
 pub trait InstanceObject<I: Interpreter> {
     fn to_object(&self) -> I::Object;
-    fn class(&self, mem: &impl Memory) -> Result<I::ClassObject>;
-    fn attributes(&self, mem: &impl Memory) -> Result<I::DictObject>;
+    fn class(&self, mem: &(impl Memory + ?Sized)) -> Result<I::ClassObject>;
+    fn attributes(&self, mem: &(impl Memory + ?Sized)) -> Result<I::DictObject>;
 }
 
 pub trait NoneObject<I: Interpreter> {
@@ -185,47 +311,107 @@ pub trait NoneObject<I: Interpreter> {
 
 pub trait BytesObject<I: Interpreter> {
     fn to_var_object(&self) -> I::VarObject;
-    fn read(&self, mem: &impl Memory) -> Result<Vec<u8>>;
+    fn read(&self, mem: &(impl Memory + ?Sized)) -> Result<Vec<u8>>;
+
+    /// The first `max_len` bytes, and the full length in bytes. The default
+    /// reads all bytes.
+    fn read_prefix(
+        &self,
+        mem: &(impl Memory + ?Sized),
+        max_len: usize,
+    ) -> Result<(Vec<u8>, usize)> {
+        let mut bytes = self.read(mem)?;
+        let len = bytes.len();
+        bytes.truncate(max_len);
+        Ok((bytes, len))
+    }
 }
 
 pub trait StringObject<I: Interpreter> {
     fn to_var_object(&self) -> I::VarObject;
-    fn read_bytes(&self, mem: &impl Memory) -> Result<Vec<u8>>;
+    fn read_bytes(&self, mem: &(impl Memory + ?Sized)) -> Result<Vec<u8>>;
 
-    fn read(&self, mem: &impl Memory) -> Result<String> {
+    fn read(&self, mem: &(impl Memory + ?Sized)) -> Result<String> {
         Ok(String::from_utf8_lossy(&self.read_bytes(mem)?).to_string())
     }
+
+    /// The first `max_len` bytes, and the full length in bytes. The default
+    /// reads all bytes.
+    fn read_bytes_prefix(
+        &self,
+        mem: &(impl Memory + ?Sized),
+        max_len: usize,
+    ) -> Result<(Vec<u8>, usize)> {
+        let mut bytes = self.read_bytes(mem)?;
+        let len = bytes.len();
+        bytes.truncate(max_len);
+        Ok((bytes, len))
+    }
+
+    /// Like `read`, but of the first `max_len` bytes only. Also returns the
+    /// full length in bytes.
+    fn read_prefix(&self, mem: &(impl Memory + ?Sized), max_len: usize) -> Result<(String, usize)> {
+        let (bytes, len) = self.read_bytes_prefix(mem, max_len)?;
+        Ok((String::from_utf8_lossy(&bytes).to_string(), len))
+    }
 }
 
 pub trait UnicodeObject<I: Interpreter> {
     fn to_object(&self) -> I::Object;
-    fn read_bytes(&self, mem: &impl Memory) -> Result<Vec<u8>>;
-    fn read(&self, mem: &impl Memory) -> Result<String>;
+    fn read_bytes(&self, mem: &(impl Memory + ?Sized)) -> Result<Vec<u8>>;
+    fn read(&self, mem: &(impl Memory + ?Sized)) -> Result<String>;
+    /// Like `read`, but of the first `max_len` code units only. Also returns
+    /// the full length in code units.
+    fn read_prefix(&self, mem: &(impl Memory + ?Sized), max_len: usize) -> Result<(String, usize)>;
 }
 
-pub struct TupleItems<'a, I, M> {
+pub struct TupleItems<'a, I, M: ?Sized> {
     mem: &'a M,
     offset: Pointer,
     end_pointer: Pointer,
+    error: Option<Error>,
     _interp: PhantomData<I>,
 }
 
-impl<'a, I, M> TupleItems<'a, I, M> {
+impl<'a, I, M: ?Sized> TupleItems<'a, I, M> {
+    /// The `length` items of the array at `offset`. An array reaching past
+    /// the end of the address space yields [`Error::Decode`] instead.
     pub fn new(mem: &'a M, offset: Pointer, length: usize) -> Self {
+        let end_pointer = length
+            .checked_mul(std::mem::size_of::<usize>())
+            .and_then(|size| offset.checked_add(size));
+        match end_pointer {
+            Some(end_pointer) => Self {
+                mem,
+                offset,
+                end_pointer,
+                error: None,
+                _interp: PhantomData,
+            },
+            None => Self::error(mem, Error::Decode),
+        }
+    }
+
+    /// Items that cannot be read, e.g. because the length is corrupt. Yields
+    /// `error` once.
+    pub fn error(mem: &'a M, error: Error) -> Self {
         Self {
             mem,
-            offset,
-            end_pointer: offset + length * std::mem::size_of::<usize>(),
+            offset: Pointer::new(0),
+            end_pointer: Pointer::new(0),
+            error: Some(error),
             _interp: PhantomData,
         }
     }
 }
 
-impl<'a, I: Interpreter, M: Memory> Iterator for TupleItems<'a, I, M> {
+impl<'a, I: Interpreter, M: Memory + ?Sized> Iterator for TupleItems<'a, I, M> {
     type Item = Result<I::Object>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.offset.address < self.end_pointer.address {
+        if let Some(error) = self.error.take() {
+            Some(Err(error))
+        } else if self.offset.address < self.end_pointer.address {
             let object = self
                 .offset
                 .try_deref_me(self.mem)
@@ -240,32 +426,67 @@ impl<'a, I: Interpreter, M: Memory> Iterator for TupleItems<'a, I, M> {
 
 pub trait TupleObject<I: Interpreter> {
     fn to_var_object(&self) -> I::VarObject;
-    fn items<'a, M: Memory>(&self, mem: &'a M) -> TupleItems<'a, I, M>;
+    /// The number of items, from `ob_size`, without reading them. A negative
+    /// `ob_size` gives 0.
+    fn len(&self) -> usize;
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    fn items<'a, M: Memory + ?Sized>(&self, mem: &'a M) -> TupleItems<'a, I, M>;
+    /// Whether the header disagrees with the layout of `type_object`, the
+    /// tuple's type: the items follow a `tp_basicsize`-byte header in
+    /// `tp_itemsize`-byte slots, and all `ob_size` of them must fit in the
+    /// address space. Only a corrupt header or type does.
+    fn inconsistent_with_type(&self, type_object: &I::TypeObject) -> bool;
 }
 
-pub struct ListItems<'a, I, M> {
+pub struct ListItems<'a, I, M: ?Sized> {
     mem: &'a M,
     offset: Pointer,
     end_pointer: Pointer,
+    error: Option<Error>,
     _interp: PhantomData<I>,
 }
 
-impl<'a, I, M> ListItems<'a, I, M> {
+impl<'a, I, M: ?Sized> ListItems<'a, I, M> {
+    /// The `length` items of the array at `offset`. An array reaching past
+    /// the end of the address space yields [`Error::Decode`] instead.
     pub fn new(mem: &'a M, offset: Pointer, length: usize) -> Self {
+        let end_pointer = length
+            .checked_mul(std::mem::size_of::<usize>())
+            .and_then(|size| offset.checked_add(size));
+        match end_pointer {
+            Some(end_pointer) => Self {
+                mem,
+                offset,
+                end_pointer,
+                error: None,
+                _interp: PhantomData,
+            },
+            None => Self::error(mem, Error::Decode),
+        }
+    }
+
+    /// Items that cannot be read, e.g. because the length is corrupt. Yields
+    /// `error` once.
+    pub fn error(mem: &'a M, error: Error) -> Self {
         Self {
             mem,
-            offset,
-            end_pointer: offset + length * std::mem::size_of::<usize>(),
+            offset: Pointer::new(0),
+            end_pointer: Pointer::new(0),
+            error: Some(error),
             _interp: PhantomData,
         }
     }
 }
 
-impl<'a, I: Interpreter, M: Memory> Iterator for ListItems<'a, I, M> {
+impl<'a, I: Interpreter, M: Memory + ?Sized> Iterator for ListItems<'a, I, M> {
     type Item = Result<I::Object>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.offset.address < self.end_pointer.address {
+        if let Some(error) = self.error.take() {
+            Some(Err(error))
+        } else if self.offset.address < self.end_pointer.address {
             let object = self
                 .offset
                 .try_deref_me(self.mem)
@@ -280,7 +501,21 @@ impl<'a, I: Interpreter, M: Memory> Iterator for ListItems<'a, I, M> {
 
 pub trait ListObject<I: Interpreter> {
     fn to_var_object(&self) -> I::VarObject;
-    fn items<'a, M: Memory>(&self, mem: &'a M) -> ListItems<'a, I, M>;
+    /// The number of items, from `ob_size`, without reading them. A negative
+    /// `ob_size` gives 0. This may exceed the number of `items` if the list
+    /// `exceeds_allocated`.
+    fn len(&self) -> usize;
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// The items, of which there are at most `allocated` if `ob_size` exceeds
+    /// it (see `exceeds_allocated`).
+    fn items<'a, M: Memory + ?Sized>(&self, mem: &'a M) -> ListItems<'a, I, M>;
+    /// The capacity of the item array.
+    fn allocated(&self) -> isize;
+    /// Whether `ob_size` exceeds a non-negative `allocated`, which only a
+    /// corrupt header has.
+    fn exceeds_allocated(&self) -> bool;
 }
 
 pub trait DictEntry<I: Interpreter> {
@@ -290,9 +525,227 @@ pub trait DictEntry<I: Interpreter> {
     fn take(self) -> (usize, I::Object, I::Object);
 }
 
+/// A `classmethod` or `staticmethod` wrapper.
+pub trait MethodWrapperObject<I: Interpreter> {
+    fn to_object(&self) -> I::Object;
+    fn callable(&self, mem: &(impl Memory + ?Sized)) -> Result<I::Object>;
+    /// The name of the wrapped callable, if it is a Python function.
+    fn callable_name(&self, mem: &(impl Memory + ?Sized)) -> Result<Option<String>>;
+}
+
+/// An `mmap.mmap`. Only the metadata is exposed, as the mapping may be huge.
+pub trait MmapObject<I: Interpreter> {
+    fn to_object(&self) -> I::Object;
+    /// The length of the mapping in bytes.
+    fn size(&self) -> usize;
+    /// The current file position, relative to the start of the mapping.
+    fn pos(&self) -> usize;
+    /// The mapped file's descriptor, or -1 for anonymous mappings.
+    fn fd(&self) -> i32;
+}
+
+/// A `wrapper_descriptor`: a type's C slot (e.g. `__str__`) exposed in its
+/// dict.
+pub trait WrapperDescriptorObject<I: Interpreter> {
+    fn to_object(&self) -> I::Object;
+    /// The type that owns the slot.
+    fn owner(&self, mem: &(impl Memory + ?Sized)) -> Result<I::Object>;
+    fn name(&self, mem: &(impl Memory + ?Sized)) -> Result<String>;
+}
+
+/// A `method-wrapper`: a `wrapper_descriptor` bound to an object.
+pub trait SlotWrapperObject<I: Interpreter> {
+    fn to_object(&self) -> I::Object;
+    fn descriptor(&self, mem: &(impl Memory + ?Sized)) -> Result<I::Object>;
+    fn bound_self(&self, mem: &(impl Memory + ?Sized)) -> Result<I::Object>;
+}
+
+pub trait CodeObject<I: Interpreter> {
+    fn to_object(&self) -> I::Object;
+    fn name(&self, mem: &(impl Memory + ?Sized)) -> Result<String>;
+    fn filename(&self, mem: &(impl Memory + ?Sized)) -> Result<String>;
+    fn firstlineno(&self) -> i32;
+    /// The constant pool: a tuple that includes the code objects of nested
+    /// functions.
+    fn consts(&self, mem: &(impl Memory + ?Sized)) -> Result<I::Object>;
+    /// The names of globals and attributes used, as a tuple of strings.
+    fn names(&self, mem: &(impl Memory + ?Sized)) -> Result<I::Object>;
+    /// The names of arguments and locals, as a tuple of strings.
+    fn varnames(&self, mem: &(impl Memory + ?Sized)) -> Result<I::Object>;
+}
+
+/// An `enumerate` or `reversed` iterator (or `listreverseiterator`, which
+/// `reversed` returns for lists): a position in a source it holds a reference
+/// to.
+pub trait IndexedIteratorObject<I: Interpreter> {
+    fn to_object(&self) -> I::Object;
+    /// For `enumerate`, the count of the next item. For `reversed`, the index
+    /// of the next item in the source, counting down.
+    fn index(&self) -> isize;
+    /// For `enumerate`, the iterator over the source (e.g. a `listiterator`).
+    /// For `reversed`, the source sequence itself, or `None` once exhausted.
+    fn source(&self, mem: &(impl Memory + ?Sized)) -> Result<Option<I::Object>>;
+}
+
+/// A `collections.defaultdict`, which is a dict with a factory for missing
+/// keys.
+pub trait DefaultDictObject<I: Interpreter> {
+    fn to_object(&self) -> I::Object;
+    /// The dict holding the entries.
+    fn dict(&self) -> &I::DictObject;
+    /// The factory called for missing keys, or `None` if it is unset.
+    fn default_factory(&self, mem: &(impl Memory + ?Sized)) -> Result<Option<I::Object>>;
+}
+
+pub trait SliceObject<I: Interpreter> {
+    fn to_object(&self) -> I::Object;
+    /// The bounds of the slice. Omitted bounds are `None` in Python, and are
+    /// returned as `None` here.
+    fn start(&self, mem: &(impl Memory + ?Sized)) -> Result<Option<I::Object>>;
+    fn stop(&self, mem: &(impl Memory + ?Sized)) -> Result<Option<I::Object>>;
+    fn step(&self, mem: &(impl Memory + ?Sized)) -> Result<Option<I::Object>>;
+}
+
+/// The value of a `datetime` module object.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DateTimeValue {
+    Date {
+        year: u16,
+        month: u8,
+        day: u8,
+    },
+    DateTime {
+        year: u16,
+        month: u8,
+        day: u8,
+        hour: u8,
+        minute: u8,
+        second: u8,
+        microsecond: u32,
+    },
+    TimeDelta {
+        days: i32,
+        seconds: i32,
+        microseconds: i32,
+    },
+}
+
+/// A `datetime.date`, `datetime.datetime` or `datetime.timedelta` object.
+pub trait DateTimeObject<I: Interpreter> {
+    fn to_object(&self) -> I::Object;
+    fn value(&self) -> DateTimeValue;
+    /// The `tzinfo` of an aware `datetime`, or `None` for naive datetimes and
+    /// the other kinds.
+    fn tzinfo(&self, mem: &(impl Memory + ?Sized)) -> Result<Option<I::Object>>;
+}
+
+pub trait SetObject<I: Interpreter> {
+    fn to_object(&self) -> I::Object;
+    /// The members in the first `max_slots` slots of the hash table, in table
+    /// order, and whether the table has more slots than that, in which case
+    /// the members are incomplete.
+    fn members(
+        &self,
+        mem: &(impl Memory + ?Sized),
+        max_slots: usize,
+    ) -> Result<(Vec<I::Object>, bool)>;
+}
+
+pub trait CapsuleObject<I: Interpreter> {
+    fn to_object(&self) -> I::Object;
+    /// The capsule's name, or `None` for unnamed capsules.
+    fn name(&self, mem: &(impl Memory + ?Sized)) -> Result<Option<String>>;
+    /// The wrapped native pointer.
+    fn pointer(&self) -> usize;
+    /// The context pointer set with `PyCapsule_SetContext`, or 0.
+    fn context(&self) -> usize;
+}
+
+pub trait DequeObject<I: Interpreter> {
+    fn to_object(&self) -> I::Object;
+    /// The elements of the deque, from left to right.
+    fn items(&self, mem: &(impl Memory + ?Sized)) -> Result<Vec<I::Object>>;
+}
+
+/// The keys or the values of a dict's entries, each read only as it is
+/// yielded.
+pub struct DictItems<'a, I, M: ?Sized> {
+    mem: &'a M,
+    pointers: std::vec::IntoIter<Pointer>,
+    capped: bool,
+    error: Option<Error>,
+    _interp: PhantomData<I>,
+}
+
+impl<'a, I, M: ?Sized> DictItems<'a, I, M> {
+    pub fn new(mem: &'a M, pointers: Vec<Pointer>, capped: bool) -> Self {
+        Self {
+            mem,
+            pointers: pointers.into_iter(),
+            capped,
+            error: None,
+            _interp: PhantomData,
+        }
+    }
+
+    /// Items of a dict whose table cannot be read. Yields `error` once.
+    pub fn error(mem: &'a M, error: Error) -> Self {
+        Self {
+            mem,
+            pointers: Vec::new().into_iter(),
+            capped: false,
+            error: Some(error),
+            _interp: PhantomData,
+        }
+    }
+
+    /// Whether the table had more slots than were read; see
+    /// `DictObject::entries`.
+    pub fn capped(&self) -> bool {
+        self.capped
+    }
+}
+
+impl<'a, I: Interpreter, M: Memory + ?Sized> Iterator for DictItems<'a, I, M> {
+    type Item = Result<I::Object>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(error) = self.error.take() {
+            Some(Err(error))
+        } else {
+            self.pointers
+                .next()
+                .map(|pointer| pointer.try_deref_me(self.mem))
+        }
+    }
+}
+
+/// The default number of hash table slots read from a dict by walks. Tables
+/// are sized to powers of two, so this admits dicts of up to ~700,000 entries.
+pub const DEFAULT_MAX_DICT_SLOTS: usize = 1 << 20;
+
 pub trait DictObject<I: Interpreter> {
     fn to_object(&self) -> I::Object;
-    fn entries(&self, mem: &impl Memory) -> Result<Vec<I::DictEntry>>;
+    /// The entries in the first `max_slots` slots of the hash table, and
+    /// whether the table has more slots than that, in which case the entries
+    /// are incomplete.
+    fn entries(
+        &self,
+        mem: &(impl Memory + ?Sized),
+        max_slots: usize,
+    ) -> Result<(Vec<I::DictEntry>, bool)>;
+    /// The keys of `entries`, without reading the values.
+    fn keys_iter<'a, M: Memory + ?Sized>(
+        &self,
+        mem: &'a M,
+        max_slots: usize,
+    ) -> DictItems<'a, I, M>;
+    /// The values of `entries`, without reading the keys.
+    fn values_iter<'a, M: Memory + ?Sized>(
+        &self,
+        mem: &'a M,
+        max_slots: usize,
+    ) -> DictItems<'a, I, M>;
 }
 
 pub trait BoolObject<I: Interpreter> {
@@ -302,10 +755,182 @@ pub trait BoolObject<I: Interpreter> {
 
 pub trait IntObject<I: Interpreter> {
     fn to_object(&self) -> I::Object;
-    fn read(&self, mem: &impl Memory) -> Result<BigInt>;
+    fn read(&self, mem: &(impl Memory + ?Sized)) -> Result<BigInt>;
+}
+
+pub trait LongObject<I: Interpreter> {
+    fn to_var_object(&self) -> I::VarObject;
+    /// The value, with digits as wide as those of `type_object`, the long's
+    /// type. Its `tp_itemsize` is the size of a digit, which depends on how
+    /// the target was built.
+    fn read(&self, mem: &(impl Memory + ?Sized), type_object: &I::TypeObject) -> Result<BigInt>;
+}
+
+/// The elements of an `array.array`. Integer typecodes, including the
+/// character typecodes `c` and `u`, decode to `Int`; `f` and `d` decode to
+/// `Float`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ArrayValues {
+    Int(Vec<BigInt>),
+    Float(Vec<f64>),
+}
+
+impl ArrayValues {
+    pub fn len(&self) -> usize {
+        match self {
+            ArrayValues::Int(values) => values.len(),
+            ArrayValues::Float(values) => values.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+pub trait ArrayObject<I: Interpreter> {
+    fn to_var_object(&self) -> I::VarObject;
+    fn typecode(&self, mem: &(impl Memory + ?Sized)) -> Result<char>;
+    fn read(&self, mem: &(impl Memory + ?Sized)) -> Result<ArrayValues>;
+
+    /// The first `max_len` elements, and the full element count.
+    fn read_prefix(
+        &self,
+        mem: &(impl Memory + ?Sized),
+        max_len: usize,
+    ) -> Result<(ArrayValues, usize)>;
+}
+
+/// Reconstruct an arbitrary-precision integer from the digit buffer of a
+/// `long`. `digits` are stored least significant first, each holding
+/// `digit_bits` bits of the magnitude (15 or 30 in CPython builds); the sign
+/// is carried separately, as it is in `ob_size`.
+pub fn bigint_from_digits(digits: &[u32], digit_bits: u32, negative: bool) -> BigInt {
+    let magnitude = digits.iter().rev().fold(BigInt::from(0), |acc, &digit| {
+        (acc << digit_bits) | BigInt::from(digit)
+    });
+    if negative {
+        -magnitude
+    } else {
+        magnitude
+    }
 }
 
 pub trait FloatObject<I: Interpreter> {
     fn to_object(&self) -> I::Object;
     fn value(&self) -> f64;
 }
+
+pub trait PropertyObject<I: Interpreter> {
+    fn to_object(&self) -> I::Object;
+    fn fget(&self, mem: &(impl Memory + ?Sized)) -> Result<Option<I::Object>>;
+    fn fset(&self, mem: &(impl Memory + ?Sized)) -> Result<Option<I::Object>>;
+    fn fdel(&self, mem: &(impl Memory + ?Sized)) -> Result<Option<I::Object>>;
+    fn doc(&self, mem: &(impl Memory + ?Sized)) -> Result<Option<I::Object>>;
+}
+
+pub trait TracebackObject<I: Interpreter> {
+    fn to_object(&self) -> I::Object;
+    /// The traceback entry of the next (inner) frame, if any.
+    fn next(&self, mem: &(impl Memory + ?Sized)) -> Result<Option<I::TracebackObject>>;
+    fn frame(&self, mem: &(impl Memory + ?Sized)) -> Result<I::Object>;
+    fn lineno(&self) -> i32;
+    fn lasti(&self) -> i32;
+}
+
+pub trait ExceptionObject<I: Interpreter> {
+    fn to_object(&self) -> I::Object;
+    fn args(&self, mem: &(impl Memory + ?Sized)) -> Result<I::TupleObject>;
+    fn message(&self, mem: &(impl Memory + ?Sized)) -> Result<Option<I::Object>>;
+    fn attributes(&self, mem: &(impl Memory + ?Sized)) -> Result<Option<I::DictObject>>;
+}
+
+pub trait MemoryViewObject<I: Interpreter> {
+    fn to_object(&self) -> I::Object;
+    /// The object exporting the buffer, if any.
+    fn base(&self, mem: &(impl Memory + ?Sized)) -> Result<Option<I::Object>>;
+    /// The start of the exposed buffer. This may be NULL if the buffer is
+    /// resolved through the base object on access.
+    fn buf(&self) -> Pointer;
+    fn len(&self) -> isize;
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    fn readonly(&self) -> bool;
+    /// The struct-module style format of the buffer items, if known.
+    fn format(&self, mem: &(impl Memory + ?Sized)) -> Result<Option<String>>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cpython27::Cpython2_7;
+    use crate::memory::SliceMemory;
+
+    #[test]
+    fn items_past_address_space() {
+        let mem = SliceMemory::new(0x1000, &[]);
+        let offset = Pointer::new(usize::MAX - 0xf);
+
+        let items: Vec<_> = TupleItems::<Cpython2_7, _>::new(&mem, offset, 4).collect();
+        assert!(matches!(items[..], [Err(Error::Decode)]));
+        let items: Vec<_> = ListItems::<Cpython2_7, _>::new(&mem, offset, usize::MAX).collect();
+        assert!(matches!(items[..], [Err(Error::Decode)]));
+    }
+
+    #[test]
+    fn bigint_from_digits_widths() {
+        for digit_bits in [15, 30] {
+            let base = BigInt::from(1u64 << digit_bits);
+
+            assert_eq!(bigint_from_digits(&[], digit_bits, false), BigInt::from(0));
+            assert_eq!(bigint_from_digits(&[], digit_bits, true), BigInt::from(0));
+            assert_eq!(
+                bigint_from_digits(&[42], digit_bits, false),
+                BigInt::from(42)
+            );
+            assert_eq!(
+                bigint_from_digits(&[42], digit_bits, true),
+                BigInt::from(-42)
+            );
+
+            let digits = [1, 2, 3];
+            let expected =
+                BigInt::from(1) + BigInt::from(2) * &base + BigInt::from(3) * &base * &base;
+            assert_eq!(bigint_from_digits(&digits, digit_bits, false), expected);
+            assert_eq!(bigint_from_digits(&digits, digit_bits, true), -expected);
+        }
+
+        // 2**100 with 30-bit digits: 100 = 3 * 30 + 10.
+        assert_eq!(
+            bigint_from_digits(&[0, 0, 0, 1 << 10], 30, false),
+            BigInt::from(1) << 100usize
+        );
+        // 2**100 with 15-bit digits: 100 = 6 * 15 + 10.
+        assert_eq!(
+            bigint_from_digits(&[0, 0, 0, 0, 0, 0, 1 << 10], 15, true),
+            -(BigInt::from(1) << 100usize)
+        );
+    }
+
+    #[test]
+    fn pointer_checked_add() {
+        let near_max = Pointer::new(usize::MAX - 8);
+        assert_eq!(near_max.checked_add(8), Some(Pointer::new(usize::MAX)));
+        assert_eq!(near_max.checked_add(9), None);
+        assert_eq!(
+            near_max.checked_add_signed(8),
+            Some(Pointer::new(usize::MAX))
+        );
+        assert_eq!(near_max.checked_add_signed(9), None);
+        assert_eq!(
+            near_max.checked_add_signed(-8),
+            Some(Pointer::new(usize::MAX - 16))
+        );
+
+        let low = Pointer::new(0x10);
+        assert_eq!(low.checked_add_signed(-0x10), Some(Pointer::new(0)));
+        assert_eq!(low.checked_add_signed(-0x11), None);
+        assert_eq!(low.checked_add_signed(isize::MIN), None);
+    }
+}
@@ -50,14 +50,45 @@ pub use remoteprocess::Pid;
 
 pub mod cpython27;
 pub mod error;
+pub mod graph;
 pub mod interpreter;
 pub mod memory;
+pub mod profile;
+pub mod repr;
+pub mod scan;
+#[cfg(target_os = "linux")]
+pub mod symbols;
+pub mod validate;
 pub mod walker;
 
+#[cfg(test)]
+mod testing;
+
 use error::{Error, Result};
 
+/// Connect to the process `pid`.
+///
+/// Failures distinguish a missing process ([`Error::ProcessNotFound`]) and
+/// insufficient privileges to read its memory ([`Error::PermissionDenied`])
+/// from other errors.
 pub fn connect(pid: Pid) -> Result<memory::Process> {
-    Ok(memory::Process::new(
-        remoteprocess::Process::new(pid).map_err(Error::RemoteProcessConnect)?,
-    ))
+    let process = remoteprocess::Process::new(pid).map_err(|error| connect_error(pid, error))?;
+
+    // Opening the handle does not touch the process on Linux. Resolving its
+    // executable does, and requires the same ptrace access as reading memory.
+    #[cfg(target_os = "linux")]
+    process.exe().map_err(|error| connect_error(pid, error))?;
+
+    Ok(memory::Process::new(process))
+}
+
+fn connect_error(pid: Pid, error: remoteprocess::Error) -> Error {
+    match &error {
+        remoteprocess::Error::IOError(io) => match io.kind() {
+            std::io::ErrorKind::NotFound => Error::ProcessNotFound(pid),
+            std::io::ErrorKind::PermissionDenied => Error::PermissionDenied(pid),
+            _ => Error::RemoteProcessConnect(error),
+        },
+        _ => Error::RemoteProcessConnect(error),
+    }
 }
@@ -1,32 +1,88 @@
 use remoteprocess::ProcessMemory;
+use std::collections::HashMap;
 use std::convert::TryInto;
+use std::sync::{mpsc, Arc, Mutex, PoisonError};
 use thiserror::Error;
 
 use crate::error::{Error, Result};
+use crate::interpreter::Pointer;
 
 #[derive(Error, Debug)]
 pub enum MemoryError {
     #[error("Invalid size: {0}")]
     InvalidSize(String),
+    #[error("Address range {address:#x}+{size} is not mapped")]
+    Unmapped { address: usize, size: usize },
+    #[error("Address range {address:#x}+{size} was not recorded")]
+    NotRecorded { address: usize, size: usize },
+    #[error("Only {read} bytes of address range {address:#x}+{size} could be read")]
+    PartialRead {
+        address: usize,
+        size: usize,
+        read: usize,
+    },
+    #[error("Reading address range {address:#x}+{size} would exceed the budget of {budget} bytes")]
+    BudgetExceeded {
+        address: usize,
+        size: usize,
+        budget: usize,
+    },
 }
 
+/// A source of target-process memory.
+///
+/// The trait is object safe, so a backend can be chosen at runtime and passed
+/// around as a `&dyn Memory` or `Box<dyn Memory>`.
+///
+/// The provided methods reading fixed-size values fail with [`Error::Decode`]
+/// if `get_vec` returns fewer bytes than they asked for.
 pub trait Memory {
     /// `address` and `size` are in bytes.
+    ///
+    /// Implementations should return exactly `size` bytes or an error. A
+    /// backend that can return fewer bytes can be wrapped in a
+    /// [`StrictMemory`] to enforce this.
     fn get_vec(&self, address: usize, size: usize) -> Result<Vec<u8>>;
 
+    /// Whether `size` bytes at `address` can likely be read, checked more
+    /// cheaply than reading them. The default reads the first and last byte.
+    fn is_readable(&self, address: usize, size: usize) -> bool {
+        probe_readable(self, address, size)
+    }
+
+    /// Pause the target until the returned guard is dropped, so that reads
+    /// see a consistent state. Returns `None` if the target cannot be paused,
+    /// as is the case for memory that is not a live process.
+    fn suspend(&self) -> Option<Result<Suspension>> {
+        None
+    }
+
+    /// Whether the target is currently paused by a [`Suspension`], or `None`
+    /// if it cannot be paused.
+    fn is_suspended(&self) -> Option<bool> {
+        None
+    }
+
     /// Note: the length is the size in 2-bytes.
     fn get_u16_vec(&self, address: usize, length: usize) -> Result<Vec<u16>> {
-        let vec = self.get_vec(address, length * 2)?;
-        let mut result = Vec::with_capacity(length);
-        for idx in 0..length {
-            result.push(u16::from_le_bytes([vec[idx * 2], vec[idx * 2 + 1]]))
+        let size = length.checked_mul(2).ok_or(Error::Decode)?;
+        let vec = self.get_vec(address, size)?;
+        if vec.len() < size {
+            return Err(Error::Decode);
         }
-        Ok(result)
+        Ok(vec
+            .chunks_exact(2)
+            .take(length)
+            .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+            .collect())
     }
 
     /// Address is in bytes.
     fn get_u8(&self, address: usize) -> Result<u8> {
-        Ok(self.get_vec(address, 1)?[0])
+        self.get_vec(address, 1)?
+            .first()
+            .copied()
+            .ok_or(Error::Decode)
     }
 
     /// Address is in bytes.
@@ -51,7 +107,10 @@ pub trait Memory {
 
     // Address is in bytes.
     fn get_u64_array(&self, address: usize) -> Result<[u8; 8]> {
-        Ok(self.get_vec(address, 8)?.try_into().unwrap())
+        self.get_vec(address, 8)?
+            .get(..8)
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or(Error::Decode)
     }
 
     // Address is in bytes.
@@ -68,22 +127,1249 @@ pub trait Memory {
     fn get_isize(&self, address: usize) -> Result<isize> {
         Ok(self.get_u64(address)? as isize)
     }
+
+    /// Address is in bytes.
+    /// Reads pointer-sized words up to (not including) the first NULL word,
+    /// reading at most `max` words to guard against runaway reads. Words are
+    /// read in batches; a batch that cannot be read whole (e.g. because it
+    /// runs into unmapped memory) is read word by word.
+    fn get_pointer_list_until_null(&self, address: usize, max: usize) -> Result<Vec<Pointer>> {
+        const BATCH: usize = 64;
+
+        let mut pointers = Vec::new();
+        let mut idx = 0;
+        while idx < max {
+            let batch_address = address + idx * Pointer::SIZE;
+            let count = BATCH.min(max - idx);
+            let words: Vec<usize> = match self.get_vec(batch_address, count * Pointer::SIZE) {
+                Ok(bytes) if bytes.len() == count * Pointer::SIZE => bytes
+                    .chunks_exact(Pointer::SIZE)
+                    .map(|word| usize::from_le_bytes(word.try_into().unwrap()))
+                    .collect(),
+                _ => {
+                    let mut words = Vec::with_capacity(count);
+                    for word_idx in 0..count {
+                        let word = self.get_usize(batch_address + word_idx * Pointer::SIZE)?;
+                        words.push(word);
+                        if word == 0 {
+                            break;
+                        }
+                    }
+                    words
+                }
+            };
+
+            for word in &words {
+                let pointer = Pointer::new(*word);
+                if pointer.null() {
+                    return Ok(pointers);
+                }
+                pointers.push(pointer);
+            }
+            idx += words.len();
+        }
+
+        Ok(pointers)
+    }
+}
+
+/// Read the first and last byte of `size` bytes at `address`.
+fn probe_readable<M: Memory + ?Sized>(mem: &M, address: usize, size: usize) -> bool {
+    match size
+        .checked_sub(1)
+        .and_then(|last| address.checked_add(last))
+    {
+        Some(last) => mem.get_vec(address, 1).is_ok() && mem.get_vec(last, 1).is_ok(),
+        None => size == 0,
+    }
+}
+
+/// Keeps the target paused while alive, and resumes it when dropped, including
+/// when unwinding from a panic. See [`Process::suspend`].
+pub struct Suspension {
+    suspensions: Arc<Mutex<Suspensions>>,
 }
 
+impl Drop for Suspension {
+    fn drop(&mut self) {
+        let mut suspensions = self
+            .suspensions
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        suspensions.count -= 1;
+        if suspensions.count == 0 {
+            // Resume before allowing the next `suspend` to pause again.
+            suspensions.pause = None;
+        }
+    }
+}
+
+/// The pause shared by the `Suspension`s of a `Process`.
+#[derive(Default)]
+struct Suspensions {
+    /// The number of live `Suspension`s.
+    count: usize,
+    /// Holds the target paused while `count` is non-zero.
+    pause: Option<Pause>,
+}
+
+/// A thread holding the target paused until dropped.
+///
+/// On Linux the target is paused by attaching to it with ptrace, and only the
+/// attaching thread can detach again. Pausing on a thread of its own lets
+/// whichever thread drops the last `Suspension` resume the target.
+struct Pause {
+    release: mpsc::Sender<()>,
+    thread: Option<std::thread::JoinHandle<()>>,
+}
+
+impl Pause {
+    fn new(pid: remoteprocess::Pid) -> Result<Self> {
+        let (locked_sender, locked) = mpsc::channel();
+        let (release, released) = mpsc::channel::<()>();
+        let thread = std::thread::spawn(move || {
+            match remoteprocess::Process::new(pid).and_then(|process| process.lock()) {
+                Ok(_lock) => {
+                    let _ = locked_sender.send(Ok(()));
+                    // Also returns if the `Pause` is dropped without sending.
+                    let _ = released.recv();
+                }
+                Err(error) => {
+                    let _ = locked_sender.send(Err(error));
+                }
+            }
+        });
+
+        let result = locked.recv().unwrap_or_else(|_| {
+            Err(remoteprocess::Error::Other(
+                "pausing thread exited".to_owned(),
+            ))
+        });
+        match result {
+            Ok(()) => Ok(Self {
+                release,
+                thread: Some(thread),
+            }),
+            Err(error) => {
+                let _ = thread.join();
+                Err(Error::RemoteProcessConnect(error))
+            }
+        }
+    }
+}
+
+impl Drop for Pause {
+    fn drop(&mut self) {
+        let _ = self.release.send(());
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// The memory of a live process.
+///
+/// `Process` is `Send` and `Sync`, so one connection can be shared by threads
+/// walking in parallel: reads copy out of the target without mutating the
+/// handle, and the lazily read memory maps are behind a `OnceLock`.
 pub struct Process {
     process: remoteprocess::Process,
+    /// The pause shared by the live `Suspension`s of the target.
+    suspensions: Arc<Mutex<Suspensions>>,
+    /// The readable `(start, end)` ranges of the target, read on first use.
+    #[cfg(target_os = "linux")]
+    maps: std::sync::OnceLock<Vec<(usize, usize)>>,
 }
 
 impl Process {
     pub fn new(process: remoteprocess::Process) -> Self {
-        Self { process }
+        Self {
+            process,
+            suspensions: Arc::new(Mutex::new(Suspensions::default())),
+            #[cfg(target_os = "linux")]
+            maps: std::sync::OnceLock::new(),
+        }
+    }
+
+    /// The process ID of the target.
+    pub fn pid(&self) -> remoteprocess::Pid {
+        self.process.pid
+    }
+
+    /// Pause the target until the returned guard is dropped. Holding the
+    /// guard across several walks lets them see the same state; dropping it
+    /// as soon as the walks are done keeps the pause short.
+    ///
+    /// The target may be suspended again while paused, e.g. by a walk with
+    /// `WalkOptions::retry_suspended`, including from other threads; it
+    /// resumes once every guard is dropped, whichever thread drops the last.
+    pub fn suspend(&self) -> Result<Suspension> {
+        let mut suspensions = self
+            .suspensions
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if suspensions.count == 0 {
+            // Other threads suspending meanwhile wait until the target is
+            // paused.
+            suspensions.pause = Some(Pause::new(self.process.pid)?);
+        }
+        suspensions.count += 1;
+
+        Ok(Suspension {
+            suspensions: Arc::clone(&self.suspensions),
+        })
     }
 }
 
 impl Memory for Process {
+    /// Returns exactly `size` bytes or an error. On Linux, a read that stops
+    /// short, e.g. at a page boundary followed by an unmapped page, is retried
+    /// once from where it stopped, and fails with `MemoryError::PartialRead`
+    /// if it stops short again.
     fn get_vec(&self, address: usize, size: usize) -> Result<Vec<u8>> {
+        #[cfg(target_os = "linux")]
+        {
+            let mut bytes = vec![0; size];
+            let read = match read_process_vm(self.process.pid, address, &mut bytes) {
+                Ok(read) => read,
+                // `remoteprocess` falls back to `/proc/<pid>/mem` on kernels
+                // without `process_vm_readv`.
+                Err(error) if error.raw_os_error() == Some(libc::ENOSYS) => {
+                    return self
+                        .process
+                        .copy(address, size)
+                        .map_err(|e| Error::SegmentationFault(e.into()));
+                }
+                Err(error) => return Err(Error::SegmentationFault(error.into())),
+            };
+            if read < size {
+                let retried = read_process_vm(self.process.pid, address + read, &mut bytes[read..])
+                    .unwrap_or(0);
+                if read + retried < size {
+                    return Err(Error::SegmentationFault(
+                        MemoryError::PartialRead {
+                            address,
+                            size,
+                            read: read + retried,
+                        }
+                        .into(),
+                    ));
+                }
+            }
+            Ok(bytes)
+        }
+
+        #[cfg(not(target_os = "linux"))]
         self.process
             .copy(address, size)
             .map_err(|e| Error::SegmentationFault(e.into()))
     }
+
+    fn suspend(&self) -> Option<Result<Suspension>> {
+        Some(Process::suspend(self))
+    }
+
+    fn is_suspended(&self) -> Option<bool> {
+        let suspensions = self
+            .suspensions
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        Some(suspensions.count > 0)
+    }
+
+    /// Consults a snapshot of the target's memory maps. As the target may map
+    /// memory after the snapshot is taken, ranges outside of it fall back to
+    /// reading.
+    #[cfg(target_os = "linux")]
+    fn is_readable(&self, address: usize, size: usize) -> bool {
+        let maps = self.maps.get_or_init(|| {
+            proc_maps::get_process_maps(self.process.pid)
+                .map(|maps| {
+                    maps.iter()
+                        .filter(|map| map.is_read())
+                        .map(|map| (map.start(), map.start() + map.size()))
+                        .collect()
+                })
+                .unwrap_or_default()
+        });
+        let end = match address.checked_add(size) {
+            Some(end) => end,
+            None => return false,
+        };
+        if maps
+            .iter()
+            .any(|&(start, map_end)| start <= address && end <= map_end)
+        {
+            return true;
+        }
+
+        probe_readable(self, address, size)
+    }
+}
+
+/// Read `buf.len()` bytes at `address` of process `pid`, returning how many
+/// were read. Unlike `remoteprocess`'s `copy`, which ignores the count, this
+/// lets short reads be told apart from complete ones.
+#[cfg(target_os = "linux")]
+fn read_process_vm(
+    pid: remoteprocess::Pid,
+    address: usize,
+    buf: &mut [u8],
+) -> std::io::Result<usize> {
+    if buf.is_empty() {
+        return Ok(0);
+    }
+    let local = libc::iovec {
+        iov_base: buf.as_mut_ptr() as *mut libc::c_void,
+        iov_len: buf.len(),
+    };
+    let remote = libc::iovec {
+        iov_base: address as *mut libc::c_void,
+        iov_len: buf.len(),
+    };
+    let read = unsafe { libc::process_vm_readv(pid, &local, 1, &remote, 1, 0) };
+    if read < 0 {
+        Err(std::io::Error::last_os_error())
+    } else {
+        Ok(read as usize)
+    }
+}
+
+/// Memory backed by a byte buffer that is mapped at `base`. Reads outside of
+/// the buffer fail.
+pub struct SliceMemory<'a> {
+    base: usize,
+    data: &'a [u8],
+}
+
+impl<'a> SliceMemory<'a> {
+    pub fn new(base: usize, data: &'a [u8]) -> Self {
+        Self { base, data }
+    }
+}
+
+impl<'a> Memory for SliceMemory<'a> {
+    fn get_vec(&self, address: usize, size: usize) -> Result<Vec<u8>> {
+        address
+            .checked_sub(self.base)
+            .and_then(|start| Some(start..start.checked_add(size)?))
+            .and_then(|range| self.data.get(range))
+            .map(<[u8]>::to_vec)
+            .ok_or_else(|| Error::SegmentationFault(MemoryError::Unmapped { address, size }.into()))
+    }
+
+    fn is_readable(&self, address: usize, size: usize) -> bool {
+        address
+            .checked_sub(self.base)
+            .and_then(|start| start.checked_add(size))
+            .is_some_and(|end| end <= self.data.len())
+    }
+}
+
+/// Memory backed by a flat snapshot file of captured regions. Each region maps
+/// the addresses `start..end` to the file contents at `file_offset`. Regions
+/// must not overlap. Reads touching an address outside of all regions fail.
+#[cfg(unix)]
+pub struct SnapshotMemory {
+    file: std::fs::File,
+    /// `(start, end, file_offset)`, sorted by `start`.
+    regions: Vec<(usize, usize, u64)>,
+}
+
+#[cfg(unix)]
+impl SnapshotMemory {
+    pub fn new(file: std::fs::File, mut regions: Vec<(usize, usize, u64)>) -> Self {
+        regions.sort_unstable_by_key(|&(start, _, _)| start);
+        Self { file, regions }
+    }
+
+    /// The region containing `address`: the last one starting at or before
+    /// it, if it has not ended yet.
+    fn region_at(&self, address: usize) -> Option<(usize, usize, u64)> {
+        let idx = self
+            .regions
+            .partition_point(|&(start, _, _)| start <= address);
+        let region = *self.regions.get(idx.checked_sub(1)?)?;
+        Some(region).filter(|&(_, end, _)| address < end)
+    }
+}
+
+#[cfg(unix)]
+impl Memory for SnapshotMemory {
+    fn get_vec(&self, address: usize, size: usize) -> Result<Vec<u8>> {
+        use std::os::unix::fs::FileExt;
+
+        let unmapped = || Error::SegmentationFault(MemoryError::Unmapped { address, size }.into());
+        let end = address.checked_add(size).ok_or_else(unmapped)?;
+
+        // A read may span adjacent regions, as long as there is no gap.
+        let mut vec = vec![0; size];
+        let mut cursor = address;
+        while cursor < end {
+            let (start, region_end, file_offset) = self.region_at(cursor).ok_or_else(unmapped)?;
+            let chunk_end = region_end.min(end);
+            self.file
+                .read_exact_at(
+                    &mut vec[cursor - address..chunk_end - address],
+                    file_offset + (cursor - start) as u64,
+                )
+                .map_err(|e| Error::SegmentationFault(e.into()))?;
+            cursor = chunk_end;
+        }
+
+        Ok(vec)
+    }
+
+    fn is_readable(&self, address: usize, size: usize) -> bool {
+        let end = match address.checked_add(size) {
+            Some(end) => end,
+            None => return false,
+        };
+        let mut cursor = address;
+        while cursor < end {
+            match self.region_at(cursor) {
+                Some((_, region_end, _)) => cursor = region_end,
+                None => return false,
+            }
+        }
+        true
+    }
+}
+
+/// A wrapper that counts the reads made through it, for benchmarking and for
+/// asserting read behavior in tests.
+pub struct CountingMemory<M> {
+    inner: M,
+    reads: std::cell::Cell<usize>,
+    bytes_read: std::cell::Cell<usize>,
+    budget: Option<usize>,
+    budget_exceeded: std::cell::Cell<bool>,
+}
+
+impl<M: Memory> CountingMemory<M> {
+    pub fn new(inner: M) -> Self {
+        Self {
+            inner,
+            reads: std::cell::Cell::new(0),
+            bytes_read: std::cell::Cell::new(0),
+            budget: None,
+            budget_exceeded: std::cell::Cell::new(false),
+        }
+    }
+
+    /// Count reads as [`CountingMemory::new`] does, but fail any read that
+    /// would take `bytes_read` past `budget` with
+    /// [`MemoryError::BudgetExceeded`], without passing it on to `inner`.
+    pub fn with_budget(inner: M, budget: usize) -> Self {
+        Self {
+            budget: Some(budget),
+            ..Self::new(inner)
+        }
+    }
+
+    /// Whether a read has failed for exceeding the budget.
+    pub fn budget_exceeded(&self) -> bool {
+        self.budget_exceeded.get()
+    }
+
+    /// The number of `get_vec` calls so far, including failed ones.
+    pub fn reads(&self) -> usize {
+        self.reads.get()
+    }
+
+    /// The number of bytes requested so far, including by failed reads but
+    /// not by reads failed for exceeding the budget.
+    pub fn bytes_read(&self) -> usize {
+        self.bytes_read.get()
+    }
+}
+
+impl<M: Memory> Memory for CountingMemory<M> {
+    fn get_vec(&self, address: usize, size: usize) -> Result<Vec<u8>> {
+        self.reads.set(self.reads.get() + 1);
+        if let Some(budget) = self.budget {
+            if self.bytes_read.get().saturating_add(size) > budget {
+                self.budget_exceeded.set(true);
+                return Err(Error::SegmentationFault(
+                    MemoryError::BudgetExceeded {
+                        address,
+                        size,
+                        budget,
+                    }
+                    .into(),
+                ));
+            }
+        }
+        self.bytes_read.set(self.bytes_read.get() + size);
+        self.inner.get_vec(address, size)
+    }
+
+    fn suspend(&self) -> Option<Result<Suspension>> {
+        self.inner.suspend()
+    }
+
+    fn is_suspended(&self) -> Option<bool> {
+        self.inner.is_suspended()
+    }
+}
+
+/// A wrapper that enforces the [`Memory::get_vec`] contract on a backend that
+/// may read short, failing with [`MemoryError::PartialRead`] instead of
+/// returning fewer bytes than were asked for.
+pub struct StrictMemory<M> {
+    inner: M,
+}
+
+impl<M: Memory> StrictMemory<M> {
+    pub fn new(inner: M) -> Self {
+        Self { inner }
+    }
+
+    pub fn inner(&self) -> &M {
+        &self.inner
+    }
+}
+
+impl<M: Memory> Memory for StrictMemory<M> {
+    fn get_vec(&self, address: usize, size: usize) -> Result<Vec<u8>> {
+        let vec = self.inner.get_vec(address, size)?;
+        if vec.len() != size {
+            return Err(Error::SegmentationFault(
+                MemoryError::PartialRead {
+                    address,
+                    size,
+                    read: vec.len(),
+                }
+                .into(),
+            ));
+        }
+        Ok(vec)
+    }
+
+    fn is_readable(&self, address: usize, size: usize) -> bool {
+        self.inner.is_readable(address, size)
+    }
+
+    fn suspend(&self) -> Option<Result<Suspension>> {
+        self.inner.suspend()
+    }
+
+    fn is_suspended(&self) -> Option<bool> {
+        self.inner.is_suspended()
+    }
+}
+
+/// Memory that serves overridden byte ranges on top of an inner memory, for
+/// non-destructive what-if experiments. Bytes outside of the overrides are read
+/// from the inner memory.
+pub struct OverlayMemory<M> {
+    inner: M,
+    overrides: HashMap<usize, Vec<u8>>,
+}
+
+impl<M: Memory> OverlayMemory<M> {
+    pub fn new(inner: M) -> Self {
+        Self {
+            inner,
+            overrides: HashMap::new(),
+        }
+    }
+
+    /// Serve `bytes` at `address` instead of the inner memory's contents.
+    /// Where overrides overlap, the one starting at the higher address wins.
+    pub fn set_override(&mut self, address: usize, bytes: Vec<u8>) {
+        self.overrides.insert(address, bytes);
+    }
+}
+
+impl<M: Memory> Memory for OverlayMemory<M> {
+    fn get_vec(&self, address: usize, size: usize) -> Result<Vec<u8>> {
+        let mut vec = self.inner.get_vec(address, size)?;
+        let end = address.saturating_add(size);
+
+        let mut starts: Vec<usize> = self.overrides.keys().copied().collect();
+        starts.sort_unstable();
+        for start in starts {
+            let bytes = &self.overrides[&start];
+            let from = start.max(address);
+            let to = start.saturating_add(bytes.len()).min(end);
+            if from < to {
+                vec[from - address..to - address].copy_from_slice(&bytes[from - start..to - start]);
+            }
+        }
+
+        Ok(vec)
+    }
+
+    fn suspend(&self) -> Option<Result<Suspension>> {
+        self.inner.suspend()
+    }
+
+    fn is_suspended(&self) -> Option<bool> {
+        self.inner.is_suspended()
+    }
+}
+
+/// A single read made through a [`RecordingMemory`]: the requested address
+/// and size, and the bytes returned, or `None` if the read failed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecordedRead {
+    pub address: usize,
+    pub size: usize,
+    pub bytes: Option<Vec<u8>>,
+}
+
+/// A wrapper that logs every read made through it, so that a walk against a
+/// live process can later be replayed with [`ReplayMemory`].
+///
+/// Readability checks are answered by probing through `get_vec`, rather than
+/// by the inner memory, so that they are recorded too.
+pub struct RecordingMemory<M> {
+    inner: M,
+    log: std::cell::RefCell<Vec<RecordedRead>>,
+}
+
+impl<M: Memory> RecordingMemory<M> {
+    pub fn new(inner: M) -> Self {
+        Self {
+            inner,
+            log: std::cell::RefCell::new(Vec::new()),
+        }
+    }
+
+    /// The reads made so far, in order.
+    pub fn log(&self) -> std::cell::Ref<'_, [RecordedRead]> {
+        std::cell::Ref::map(self.log.borrow(), Vec::as_slice)
+    }
+
+    pub fn into_log(self) -> Vec<RecordedRead> {
+        self.log.into_inner()
+    }
+
+    /// Write the reads made so far to `writer`, in the format read by
+    /// [`ReplayMemory::read_log`].
+    pub fn write_log<W: std::io::Write>(&self, mut writer: W) -> std::io::Result<()> {
+        for read in self.log.borrow().iter() {
+            writer.write_all(&(read.address as u64).to_le_bytes())?;
+            writer.write_all(&(read.size as u64).to_le_bytes())?;
+            match &read.bytes {
+                Some(bytes) => {
+                    writer.write_all(&[1])?;
+                    writer.write_all(&(bytes.len() as u64).to_le_bytes())?;
+                    writer.write_all(bytes)?;
+                }
+                None => writer.write_all(&[0])?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<M: Memory> Memory for RecordingMemory<M> {
+    fn get_vec(&self, address: usize, size: usize) -> Result<Vec<u8>> {
+        let result = self.inner.get_vec(address, size);
+        self.log.borrow_mut().push(RecordedRead {
+            address,
+            size,
+            bytes: result.as_ref().ok().cloned(),
+        });
+        result
+    }
+
+    fn suspend(&self) -> Option<Result<Suspension>> {
+        self.inner.suspend()
+    }
+
+    fn is_suspended(&self) -> Option<bool> {
+        self.inner.is_suspended()
+    }
+}
+
+/// Memory that serves reads from a log recorded by [`RecordingMemory`]. A read
+/// returns what the same read returned when recorded; reads that were not
+/// recorded fail.
+pub struct ReplayMemory {
+    reads: HashMap<(usize, usize), Option<Vec<u8>>>,
+}
+
+impl ReplayMemory {
+    pub fn new(log: impl IntoIterator<Item = RecordedRead>) -> Self {
+        Self {
+            reads: log
+                .into_iter()
+                .map(|read| ((read.address, read.size), read.bytes))
+                .collect(),
+        }
+    }
+
+    /// Read a log written by [`RecordingMemory::write_log`].
+    pub fn read_log<R: std::io::Read>(mut reader: R) -> std::io::Result<Self> {
+        fn read_u64<R: std::io::Read>(reader: &mut R) -> std::io::Result<u64> {
+            let mut word = [0; 8];
+            reader.read_exact(&mut word)?;
+            Ok(u64::from_le_bytes(word))
+        }
+
+        let mut log = Vec::new();
+        loop {
+            let mut word = [0; 8];
+            match reader.read_exact(&mut word) {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+                Err(e) => return Err(e),
+            }
+            let address = u64::from_le_bytes(word) as usize;
+            let size = read_u64(&mut reader)? as usize;
+            let mut status = [0];
+            reader.read_exact(&mut status)?;
+            let bytes = match status[0] {
+                0 => None,
+                _ => {
+                    let mut bytes = vec![0; read_u64(&mut reader)? as usize];
+                    reader.read_exact(&mut bytes)?;
+                    Some(bytes)
+                }
+            };
+            log.push(RecordedRead {
+                address,
+                size,
+                bytes,
+            });
+        }
+
+        Ok(Self::new(log))
+    }
+}
+
+impl Memory for ReplayMemory {
+    fn get_vec(&self, address: usize, size: usize) -> Result<Vec<u8>> {
+        match self.reads.get(&(address, size)) {
+            Some(Some(bytes)) => Ok(bytes.clone()),
+            Some(None) => Err(Error::SegmentationFault(
+                MemoryError::Unmapped { address, size }.into(),
+            )),
+            None => Err(Error::SegmentationFault(
+                MemoryError::NotRecorded { address, size }.into(),
+            )),
+        }
+    }
+}
+
+impl<M: Memory + ?Sized> Memory for &M {
+    fn get_vec(&self, address: usize, size: usize) -> Result<Vec<u8>> {
+        (**self).get_vec(address, size)
+    }
+
+    fn is_readable(&self, address: usize, size: usize) -> bool {
+        (**self).is_readable(address, size)
+    }
+
+    fn suspend(&self) -> Option<Result<Suspension>> {
+        (**self).suspend()
+    }
+
+    fn is_suspended(&self) -> Option<bool> {
+        (**self).is_suspended()
+    }
+}
+
+impl<M: Memory + ?Sized> Memory for Box<M> {
+    fn get_vec(&self, address: usize, size: usize) -> Result<Vec<u8>> {
+        (**self).get_vec(address, size)
+    }
+
+    fn is_readable(&self, address: usize, size: usize) -> bool {
+        (**self).is_readable(address, size)
+    }
+
+    fn suspend(&self) -> Option<Result<Suspension>> {
+        (**self).suspend()
+    }
+
+    fn is_suspended(&self) -> Option<bool> {
+        (**self).is_suspended()
+    }
+}
+
+/// Typed reads on top of [`Memory`].
+///
+/// This lives in a separate, blanket-implemented trait because generic methods
+/// would make `Memory` itself no longer object safe.
+pub trait MemoryExt: Memory {
+    /// Address is in bytes.
+    /// Reads `size_of::<T>()` bytes and reinterprets them as a `T`. Returns
+    /// `Error::Decode` if the backend returns fewer bytes than requested.
+    fn read_struct<T: bytemuck::Pod>(&self, address: usize) -> Result<T> {
+        let bytes = self.get_vec(address, std::mem::size_of::<T>())?;
+        bytemuck::try_pod_read_unaligned(&bytes).map_err(|_| Error::Decode)
+    }
+
+    /// Address is in bytes.
+    /// Reads `count` consecutive `N`-byte records with a single `get_vec`.
+    /// Returns `Error::Decode` if the backend returns fewer bytes than
+    /// requested.
+    fn get_records<const N: usize>(&self, address: usize, count: usize) -> Result<Vec<[u8; N]>> {
+        let size = count.checked_mul(N).ok_or(Error::Decode)?;
+        let bytes = self.get_vec(address, size)?;
+        if bytes.len() < size {
+            return Err(Error::Decode);
+        }
+
+        Ok(bytes
+            .chunks_exact(N)
+            .map(|record| record.try_into().expect("chunk size"))
+            .collect())
+    }
+}
+
+impl<M: Memory + ?Sized> MemoryExt for M {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cpython27::Cpython2_7;
+    use crate::interpreter::{Object, Pointer};
+    use crate::testing::spawn_python27;
+    use crate::walker::{walk, DataPointer, DecodedData};
+
+    #[test]
+    fn get_records() -> Result<()> {
+        let data: Vec<u8> = (0..=255).collect();
+        let mem = SliceMemory::new(0x100, &data);
+
+        let records = mem.get_records::<12>(0x104, 20)?;
+        assert_eq!(records.len(), 20);
+        for (idx, record) in records.iter().enumerate() {
+            assert_eq!(&record[..], &mem.get_vec(0x104 + idx * 12, 12)?[..]);
+        }
+
+        assert!(mem.get_records::<12>(0x104, 0)?.is_empty());
+        assert!(mem.get_records::<12>(0x1f8, 2).is_err());
+        assert!(mem.get_records::<12>(0x104, usize::MAX / 4).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn pointer_list_until_null() -> Result<()> {
+        let words: [u64; 4] = [0x1000, 0x2000, 0, 0x3000];
+        let data: Vec<u8> = words.iter().flat_map(|word| word.to_le_bytes()).collect();
+        let mem = SliceMemory::new(0x100, &data);
+
+        assert_eq!(
+            mem.get_pointer_list_until_null(0x100, 10)?,
+            vec![Pointer::new(0x1000), Pointer::new(0x2000)]
+        );
+        assert_eq!(
+            mem.get_pointer_list_until_null(0x100, 1)?,
+            vec![Pointer::new(0x1000)]
+        );
+        assert!(mem.get_pointer_list_until_null(0x118, 10).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn walk_boxed_memory() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27("entry = ['boxed', 'memory']")?;
+        let mem: Box<dyn Memory> = Box::new(crate::connect(child.pid)?);
+
+        let graph = walk::<Cpython2_7, _>(&mem, Pointer::new(child.pointer));
+        match graph.get(&DataPointer(child.pointer)) {
+            Some(DecodedData::List(items)) => assert_eq!(items.len(), 2),
+            _ => anyhow::bail!("Expected a list"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn walk_dyn_memory() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27("entry = ('dyn', 'memory', 3)")?;
+        let process = crate::connect(child.pid)?;
+        let mem: &dyn Memory = &process;
+
+        let graph = walk::<Cpython2_7, _>(mem, Pointer::new(child.pointer));
+        match graph.get(&DataPointer(child.pointer)) {
+            Some(DecodedData::Tuple(items)) => assert_eq!(items.len(), 3),
+            _ => anyhow::bail!("Expected a tuple"),
+        }
+
+        let object: crate::cpython27::PyObject<Cpython2_7> =
+            Pointer::new(child.pointer).try_deref_me(mem)?;
+        assert_eq!(object.me(), Pointer::new(child.pointer));
+
+        Ok(())
+    }
+
+    #[test]
+    fn is_readable() {
+        let data = [0u8; 0x100];
+        let mem = SliceMemory::new(0x1000, &data);
+        assert!(mem.is_readable(0x1000, 0x100));
+        assert!(mem.is_readable(0x10f0, 0x10));
+        assert!(!mem.is_readable(0x10f0, 0x11));
+        assert!(!mem.is_readable(0xff0, 0x20));
+        assert!(!mem.is_readable(usize::MAX, 2));
+
+        // The default probes by reading.
+        let counting = CountingMemory::new(&mem);
+        assert!(counting.is_readable(0x1000, 0x100));
+        assert!(!counting.is_readable(0x10f0, 0x11));
+        assert!(!counting.is_readable(0x2000, 1));
+        assert!(counting.is_readable(0x2000, 0));
+    }
+
+    #[test]
+    fn process_is_readable() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27("entry = 'mapped'")?;
+        let mem = crate::connect(child.pid)?;
+        assert!(mem.is_readable(child.pointer, 16));
+        assert!(!mem.is_readable(0x10, 16));
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn partial_read() -> std::result::Result<(), anyhow::Error> {
+        // Two pages, the second of which becomes unreadable.
+        let child = spawn_python27(
+            r#"
+            import ctypes, mmap
+            page = mmap.PAGESIZE
+            buf = mmap.mmap(-1, 2 * page)
+            buf.write('x' * page)
+            base = ctypes.addressof(ctypes.c_char.from_buffer(buf))
+            libc = ctypes.CDLL(None)
+            libc.mprotect.argtypes = [ctypes.c_void_p, ctypes.c_size_t, ctypes.c_int]
+            assert libc.mprotect(base + page, page, 0) == 0
+            entry = base + page
+            "#,
+        )?;
+        let mem = crate::connect(child.pid)?;
+        let boundary = match walk::<Cpython2_7, _>(&mem, Pointer::new(child.pointer))
+            .remove(&DataPointer(child.pointer))
+        {
+            Some(DecodedData::Int(boundary)) => boundary.to_string().parse::<usize>()?,
+            node => anyhow::bail!("Expected an int, got {:?}", node),
+        };
+
+        assert_eq!(mem.get_vec(boundary - 16, 16)?, vec![b'x'; 16]);
+        match mem.get_vec(boundary - 16, 32) {
+            Err(Error::SegmentationFault(source)) => assert!(matches!(
+                source.downcast_ref::<MemoryError>(),
+                Some(MemoryError::PartialRead { read: 16, .. })
+            )),
+            result => anyhow::bail!("Expected a partial read, got {:?}", result),
+        }
+        assert!(mem.get_vec(boundary, 8).is_err());
+
+        Ok(())
+    }
+
+    /// Whether `pid` is stopped by a tracer: the state field of
+    /// /proc/<pid>/stat is `t` while traced.
+    #[cfg(target_os = "linux")]
+    fn traced(pid: crate::Pid) -> std::io::Result<bool> {
+        let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid))?;
+        let state = stat.rsplit(')').next().unwrap_or_default().trim_start();
+        Ok(state.starts_with('t'))
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn suspension_guard() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27("entry = ['paused', 'walks']")?;
+        let mem = crate::connect(child.pid)?;
+        let stopped = || traced(child.pid);
+        assert!(!stopped()?);
+
+        {
+            let _guard = mem.suspend()?;
+            assert!(stopped()?);
+            for _ in 0..2 {
+                let graph = walk::<Cpython2_7, _>(&mem, Pointer::new(child.pointer));
+                assert!(matches!(
+                    graph.get(&DataPointer(child.pointer)),
+                    Some(DecodedData::List(items)) if items.len() == 2
+                ));
+            }
+            // Suspending again while paused does not fail, nor resume early.
+            drop(Memory::suspend(&mem).expect("a process can be suspended")?);
+            assert!(stopped()?);
+        }
+        assert!(!stopped()?);
+
+        let panicked = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let _guard = mem.suspend().unwrap();
+            panic!("while suspended");
+        }));
+        assert!(panicked.is_err());
+        assert!(!stopped()?);
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn suspension_across_threads() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27("entry = None")?;
+        let mem = crate::connect(child.pid)?;
+        let stopped = || traced(child.pid);
+
+        // Each thread finds the target paused as soon as its guard is taken,
+        // however the threads interleave.
+        let barrier = std::sync::Barrier::new(4);
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    let _guard = mem.suspend().unwrap();
+                    assert!(stopped().unwrap());
+                    barrier.wait();
+                });
+            }
+        });
+        assert!(!stopped()?);
+
+        // The target stays paused until the last guard is dropped, even if
+        // that is not the one that paused it, on another thread.
+        let first = std::thread::scope(|scope| scope.spawn(|| mem.suspend()).join().unwrap())?;
+        let second = mem.suspend()?;
+        drop(first);
+        assert!(stopped()?);
+        std::thread::scope(|scope| {
+            scope.spawn(move || drop(second));
+        });
+        assert!(!stopped()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn process_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Process>();
+    }
+
+    #[test]
+    fn read_struct() -> Result<()> {
+        #[repr(C)]
+        #[derive(Copy, Clone, Debug, PartialEq)]
+        struct Header {
+            refcnt: isize,
+            size: u32,
+            flags: u32,
+        }
+        unsafe impl bytemuck::Zeroable for Header {}
+        unsafe impl bytemuck::Pod for Header {}
+
+        let header = Header {
+            refcnt: 3,
+            size: 0x20,
+            flags: 0xf00d,
+        };
+        let mut data = vec![0xaa];
+        data.extend_from_slice(bytemuck::bytes_of(&header));
+        let mem = SliceMemory::new(0x100, &data);
+
+        // Unaligned reads are fine.
+        assert_eq!(mem.read_struct::<Header>(0x101)?, header);
+        assert!(mem.read_struct::<Header>(0x102).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn overlay_memory() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27("entry = 123456")?;
+        let mut mem = OverlayMemory::new(crate::connect(child.pid)?);
+
+        let ival = child.pointer + memoffset::offset_of!(python27_sys::PyIntObject, ob_ival);
+        mem.set_override(ival, 654321i64.to_le_bytes().to_vec());
+
+        let graph = walk::<Cpython2_7, _>(&mem, Pointer::new(child.pointer));
+        match graph.get(&DataPointer(child.pointer)) {
+            Some(DecodedData::Int(int)) => assert_eq!(int, &num_bigint::BigInt::from(654321)),
+            _ => anyhow::bail!("Expected an int"),
+        }
+
+        // Partially overlapping reads are patched too.
+        let bytes = mem.get_vec(ival - 2, 4)?;
+        assert_eq!(&bytes[2..], &654321i64.to_le_bytes()[..2]);
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn snapshot_memory() -> std::result::Result<(), anyhow::Error> {
+        use std::io::Write;
+
+        let path = std::env::temp_dir().join(format!("cpy-walker-snapshot-{}", std::process::id()));
+        let contents: Vec<u8> = (0..48).collect();
+        std::fs::File::create(&path)?.write_all(&contents)?;
+        let file = std::fs::File::open(&path)?;
+        std::fs::remove_file(&path)?;
+
+        // Two adjacent regions at 0x2000, and one separate region at 0x1000.
+        let mem = SnapshotMemory::new(
+            file,
+            vec![
+                (0x2008, 0x2010, 32),
+                (0x1000, 0x1010, 0),
+                (0x2000, 0x2008, 16),
+            ],
+        );
+
+        assert_eq!(mem.get_vec(0x1004, 4)?, [4, 5, 6, 7]);
+        assert_eq!(mem.get_vec(0x2006, 4)?, [22, 23, 32, 33]);
+        assert!(mem.get_vec(0x100e, 4).is_err());
+        assert!(mem.get_vec(0x2010, 1).is_err());
+        // Before the first region, and in the gap between regions.
+        assert!(mem.get_vec(0xfff, 1).is_err());
+        assert!(mem.get_vec(0x1800, 1).is_err());
+        assert!(mem.is_readable(0x2000, 0x10));
+        assert!(!mem.is_readable(0x1008, 0x10));
+
+        Ok(())
+    }
+
+    #[test]
+    fn record_and_replay() -> std::result::Result<(), anyhow::Error> {
+        let child =
+            spawn_python27("entry = {'recorded': [1, 2.5, u'replayed'], 'again': ('x', None)}")?;
+        let recording = RecordingMemory::new(crate::connect(child.pid)?);
+        let recorded = walk::<Cpython2_7, _>(&recording, Pointer::new(child.pointer));
+        assert!(!recording.log().is_empty());
+
+        let mut file = Vec::new();
+        recording.write_log(&mut file)?;
+        let replay = ReplayMemory::read_log(&file[..])?;
+        // The process is not needed to replay the walk.
+        let root = child.pointer;
+        drop(child);
+        let replayed = walk::<Cpython2_7, _>(&replay, Pointer::new(root));
+
+        let sorted = |graph: &HashMap<DataPointer, DecodedData>| {
+            let mut nodes: Vec<_> = graph
+                .iter()
+                .map(|(pointer, data)| match data {
+                    // Entries are compared regardless of hash map order.
+                    DecodedData::Dict { entries, .. } => {
+                        let mut entries: Vec<_> = entries.iter().collect();
+                        entries.sort_by_key(|(key, _)| key.0);
+                        (pointer.0, format!("{:?}", entries))
+                    }
+                    data => (pointer.0, format!("{:?}", data)),
+                })
+                .collect();
+            nodes.sort();
+            nodes
+        };
+        assert_eq!(sorted(&recorded), sorted(&replayed));
+
+        let in_memory = ReplayMemory::new(recording.into_log());
+        assert!(matches!(
+            in_memory.get_vec(0x10, 8),
+            Err(Error::SegmentationFault(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn counting_batched_pointer_list() -> Result<()> {
+        let words: Vec<u64> = (1..=100).chain([0; 100]).collect();
+        let data: Vec<u8> = words.iter().flat_map(|word| word.to_le_bytes()).collect();
+        let mem = CountingMemory::new(SliceMemory::new(0x100, &data));
+
+        let pointers = mem.get_pointer_list_until_null(0x100, 1000)?;
+        assert_eq!(pointers.len(), 100);
+        let batched = mem.reads();
+        assert_eq!(batched, 2);
+        assert_eq!(mem.bytes_read(), 2 * 64 * 8);
+
+        // Reading word by word, as the list reader did before batching.
+        for idx in 0..=100 {
+            mem.get_usize(0x100 + idx * 8)?;
+        }
+        let unbatched = mem.reads() - batched;
+        assert!(batched < unbatched, "{} >= {}", batched, unbatched);
+
+        Ok(())
+    }
+
+    /// Reads at most as many bytes as its limit, however many were asked for.
+    struct ShortMemory<'a>(SliceMemory<'a>, usize);
+
+    impl Memory for ShortMemory<'_> {
+        fn get_vec(&self, address: usize, size: usize) -> Result<Vec<u8>> {
+            self.0.get_vec(address, size.min(self.1))
+        }
+    }
+
+    #[test]
+    fn counting_budget() -> Result<()> {
+        let data: Vec<u8> = (0..64).collect();
+        let mem = CountingMemory::with_budget(SliceMemory::new(0x100, &data), 24);
+
+        assert_eq!(mem.get_vec(0x100, 16)?.len(), 16);
+        assert!(!mem.budget_exceeded());
+        match mem.get_vec(0x110, 16) {
+            Err(Error::SegmentationFault(source)) => assert!(matches!(
+                source.downcast_ref::<MemoryError>(),
+                Some(MemoryError::BudgetExceeded {
+                    address: 0x110,
+                    size: 16,
+                    budget: 24,
+                })
+            )),
+            result => panic!("Expected an exceeded budget, got {:?}", result),
+        }
+        assert!(mem.budget_exceeded());
+        // Reads that still fit are served, but the rejected read is not counted.
+        assert_eq!(mem.get_vec(0x110, 8)?.len(), 8);
+        assert_eq!(mem.bytes_read(), 24);
+        assert_eq!(mem.reads(), 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn strict_short_read() -> Result<()> {
+        let data: Vec<u8> = (0..16).collect();
+        let short = ShortMemory(SliceMemory::new(0x100, &data), 4);
+        assert_eq!(short.get_vec(0x100, 8)?.len(), 4);
+        // The default methods reading fixed-size values fail rather than
+        // panic on the short reads.
+        assert!(matches!(short.get_usize(0x100), Err(Error::Decode)));
+        assert!(matches!(short.get_u16_vec(0x100, 4), Err(Error::Decode)));
+        assert!(matches!(short.get_u8(0x10f), Ok(15)));
+        let empty = ShortMemory(SliceMemory::new(0x100, &data), 0);
+        assert!(matches!(empty.get_u8(0x100), Err(Error::Decode)));
+        assert!(short.get_pointer_list_until_null(0x100, 4).is_err());
+
+        let strict = StrictMemory::new(short);
+        assert_eq!(strict.get_vec(0x100, 4)?, vec![0, 1, 2, 3]);
+        match strict.get_vec(0x104, 8) {
+            Err(Error::SegmentationFault(source)) => assert!(matches!(
+                source.downcast_ref::<MemoryError>(),
+                Some(MemoryError::PartialRead {
+                    address: 0x104,
+                    size: 8,
+                    read: 4,
+                })
+            )),
+            result => panic!("Expected a partial read, got {:?}", result),
+        }
+        assert!(matches!(
+            strict.get_usize(0x100),
+            Err(Error::SegmentationFault(_))
+        ));
+        assert!(strict.get_vec(0x200, 4).is_err());
+
+        Ok(())
+    }
 }
@@ -0,0 +1,172 @@
+//! Per-type object counts, for memory profiling.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+#[cfg(feature = "datetime")]
+use crate::interpreter::DateTimeValue;
+use crate::interpreter::{Interpreter, Pointer};
+use crate::memory::Memory;
+use crate::walker::{walk, DataPointer, DecodedData};
+
+/// The name a node is counted under: the Python type name where the node
+/// records one, and the builtin type it was decoded as otherwise.
+pub(crate) fn node_type_name(data: &DecodedData) -> &str {
+    match data {
+        DecodedData::Type(_) => "type",
+        DecodedData::Object {
+            object_type_name, ..
+        } => object_type_name,
+        DecodedData::Unknown { type_name, .. } => type_name,
+        DecodedData::Buffer(_) => "buffer",
+        DecodedData::None => "NoneType",
+        DecodedData::Class { .. } => "classobj",
+        DecodedData::Instance {
+            instance_class_name,
+            ..
+        } => instance_class_name,
+        DecodedData::Bytes(_) => "bytes",
+        DecodedData::String(_) => "str",
+        DecodedData::Tuple(_) => "tuple",
+        DecodedData::List(_) => "list",
+        DecodedData::Dict { .. } => "dict",
+        DecodedData::DefaultDict { .. } => "collections.defaultdict",
+        DecodedData::OrderedDict(_) => "OrderedDict",
+        DecodedData::Set { .. } => "set",
+        DecodedData::Deque(_) => "collections.deque",
+        DecodedData::Bool(_) => "bool",
+        DecodedData::Int(_) => "int",
+        DecodedData::Float(_) => "float",
+        DecodedData::Exception { type_name, .. } => type_name,
+        DecodedData::MemoryView { .. } => "memoryview",
+        DecodedData::Property { .. } => "property",
+        DecodedData::Traceback { .. } => "traceback",
+        DecodedData::ClassMethod { .. } => "classmethod",
+        DecodedData::StaticMethod { .. } => "staticmethod",
+        DecodedData::WrapperDescriptor { .. } => "wrapper_descriptor",
+        DecodedData::SlotWrapper { .. } => "method-wrapper",
+        DecodedData::Code { .. } => "code",
+        DecodedData::Mmap { .. } => "mmap.mmap",
+        DecodedData::Enumerate { .. } => "enumerate",
+        DecodedData::Reversed { .. } => "reversed",
+        DecodedData::Slice { .. } => "slice",
+        DecodedData::Capsule { .. } => "PyCapsule",
+        DecodedData::Array { .. } => "array.array",
+        #[cfg(feature = "datetime")]
+        DecodedData::DateTime { value, .. } => match value {
+            DateTimeValue::Date { .. } => "datetime.date",
+            DateTimeValue::DateTime { .. } => "datetime.datetime",
+            DateTimeValue::TimeDelta { .. } => "datetime.timedelta",
+        },
+        DecodedData::Skipped(type_name) => type_name,
+        DecodedData::FreedOrUninitialized { .. } => "<freed or uninitialized>",
+        DecodedData::Error(_) => "<error>",
+    }
+}
+
+/// Count the nodes of a walked graph per type name.
+pub fn summarize(graph: &HashMap<DataPointer, DecodedData>) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for data in graph.values() {
+        *counts.entry(node_type_name(data).to_owned()).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// The count of one type across samples. A type missing from a sample counts
+/// as zero in that sample.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TypeStats {
+    pub min: usize,
+    pub max: usize,
+    pub mean: f64,
+    /// The count in each sample, in sampling order.
+    pub counts: Vec<usize>,
+}
+
+/// The result of [`sample_types`].
+#[derive(Clone, Debug, Default)]
+pub struct TypeProfile {
+    pub samples: usize,
+    pub types: HashMap<String, TypeStats>,
+}
+
+/// Walk the graph reachable from `pointer` `samples` times, sleeping
+/// `interval` between walks, and aggregate the per-type counts.
+pub fn sample_types<I, M>(
+    mem: &M,
+    pointer: Pointer,
+    samples: usize,
+    interval: Duration,
+) -> TypeProfile
+where
+    I: Interpreter,
+    M: Memory + ?Sized,
+{
+    let mut types: HashMap<String, TypeStats> = HashMap::new();
+
+    for sample in 0..samples {
+        if sample > 0 {
+            std::thread::sleep(interval);
+        }
+
+        for (name, count) in summarize(&walk::<I, M>(mem, pointer)) {
+            let stats = types.entry(name).or_default();
+            // Types first seen in this sample were absent from earlier ones.
+            stats.counts.resize(sample, 0);
+            stats.counts.push(count);
+        }
+        for stats in types.values_mut() {
+            stats.counts.resize(sample + 1, 0);
+        }
+    }
+
+    for stats in types.values_mut() {
+        stats.min = stats.counts.iter().copied().min().unwrap_or(0);
+        stats.max = stats.counts.iter().copied().max().unwrap_or(0);
+        stats.mean = stats.counts.iter().sum::<usize>() as f64 / samples.max(1) as f64;
+    }
+
+    TypeProfile { samples, types }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cpython27::Cpython2_7;
+    use crate::testing::spawn_python27;
+
+    #[test]
+    fn growing_list() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27(
+            r#"
+            import threading, time
+            entry = [[]]
+            def grow():
+                while True:
+                    entry.append([])
+                    time.sleep(0.01)
+            thread = threading.Thread(target=grow)
+            thread.daemon = True
+            thread.start()
+            "#,
+        )?;
+        let mem = crate::connect(child.pid)?;
+
+        let profile = sample_types::<Cpython2_7, _>(
+            &mem,
+            Pointer::new(child.pointer),
+            3,
+            Duration::from_millis(200),
+        );
+        assert_eq!(profile.samples, 3);
+
+        let lists = &profile.types["list"];
+        assert_eq!(lists.counts.len(), 3);
+        assert!(lists.counts.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(lists.max > lists.min);
+        assert!(lists.mean > lists.min as f64 && lists.mean < lists.max as f64);
+
+        Ok(())
+    }
+}
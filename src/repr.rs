@@ -0,0 +1,261 @@
+//! Rendering of walked graphs in the style of Python's `repr()`.
+
+use std::collections::HashMap;
+
+use crate::profile::node_type_name;
+use crate::walker::{DataPointer, DecodedData};
+
+/// Render the node at `root` and its children like Python's `repr()` would,
+/// e.g. `['hello world', 42, <Something instance at 0x7f0e5c2b1e60>]`.
+///
+/// Collections nested deeper than `max_depth`, and collections already being
+/// rendered further up (cycles), are rendered as `[...]`, `{...}` and so on.
+/// Collections of more than `max_len` items show the first `max_len`, followed
+/// by `...`. Dict entries and set members are sorted by their rendering, as
+/// the graph does not record their order.
+///
+/// `str` and `unicode` both decode to `DecodedData::String`, so they are
+/// both rendered like `str`.
+pub fn to_repr(
+    graph: &HashMap<DataPointer, DecodedData>,
+    root: DataPointer,
+    max_depth: usize,
+    max_len: usize,
+) -> String {
+    Repr {
+        graph,
+        max_depth,
+        max_len,
+        path: Vec::new(),
+    }
+    .render(root)
+}
+
+struct Repr<'g> {
+    graph: &'g HashMap<DataPointer, DecodedData>,
+    max_depth: usize,
+    max_len: usize,
+    /// The collections being rendered, from the root down.
+    path: Vec<DataPointer>,
+}
+
+impl Repr<'_> {
+    fn render(&mut self, pointer: DataPointer) -> String {
+        let data = match self.graph.get(&pointer) {
+            Some(data) => data,
+            None => return format!("<missing at {:#x}>", pointer.0),
+        };
+
+        match data {
+            DecodedData::None => "None".to_owned(),
+            DecodedData::Bool(true) => "True".to_owned(),
+            DecodedData::Bool(false) => "False".to_owned(),
+            DecodedData::Int(int) => int.to_string(),
+            DecodedData::Float(float) => float_repr(*float),
+            DecodedData::String(string) => quote(string.chars()),
+            DecodedData::Bytes(bytes) => quote(bytes.iter().map(|&byte| char::from(byte))),
+            DecodedData::List(items) => {
+                self.collection(pointer, "[", "]", items.iter().copied(), |items| items)
+            }
+            DecodedData::Tuple(items) => {
+                self.collection(pointer, "(", ")", items.iter().copied(), |mut items| {
+                    if items.len() == 1 {
+                        items[0].push(',');
+                    }
+                    items
+                })
+            }
+            DecodedData::Deque(items) => {
+                self.collection(pointer, "deque([", "])", items.iter().copied(), |items| {
+                    items
+                })
+            }
+            DecodedData::Set { members, .. } => self.collection(
+                pointer,
+                "set([",
+                "])",
+                members.iter().copied(),
+                |mut members| {
+                    members.sort();
+                    members
+                },
+            ),
+            DecodedData::Dict { entries, .. } => {
+                if let Some(elided) = self.elide(pointer, "{", "}") {
+                    return elided;
+                }
+                self.path.push(pointer);
+                let mut items: Vec<String> = entries
+                    .iter()
+                    .map(|(&key, &value)| format!("{}: {}", self.render(key), self.render(value)))
+                    .collect();
+                self.path.pop();
+                items.sort();
+                self.join("{", "}", items)
+            }
+            DecodedData::Exception {
+                type_name, args, ..
+            } => {
+                let name = type_name.strip_prefix("exceptions.").unwrap_or(type_name);
+                format!("{}{}", name, self.render(*args))
+            }
+            DecodedData::Type(name) => format!("<type '{}'>", name),
+            DecodedData::Class { class_name, .. } => format!("<class {}>", class_name),
+            DecodedData::Instance {
+                instance_class_name,
+                ..
+            } => format!("<{} instance at {:#x}>", instance_class_name, pointer.0),
+            DecodedData::Error(error) => format!("<error at {:#x}: {}>", pointer.0, error),
+            data => format!("<{} object at {:#x}>", node_type_name(data), pointer.0),
+        }
+    }
+
+    /// The rendering of a collection that is too deep or already being
+    /// rendered, if it is.
+    fn elide(&self, pointer: DataPointer, open: &str, close: &str) -> Option<String> {
+        if self.path.len() >= self.max_depth || self.path.contains(&pointer) {
+            Some(format!("{}...{}", open, close))
+        } else {
+            None
+        }
+    }
+
+    fn collection(
+        &mut self,
+        pointer: DataPointer,
+        open: &str,
+        close: &str,
+        items: impl Iterator<Item = DataPointer>,
+        finish: impl FnOnce(Vec<String>) -> Vec<String>,
+    ) -> String {
+        if let Some(elided) = self.elide(pointer, open, close) {
+            return elided;
+        }
+        self.path.push(pointer);
+        let items = items.map(|item| self.render(item)).collect();
+        self.path.pop();
+        self.join(open, close, finish(items))
+    }
+
+    fn join(&self, open: &str, close: &str, mut items: Vec<String>) -> String {
+        if items.len() > self.max_len {
+            items.truncate(self.max_len);
+            items.push("...".to_owned());
+        }
+        format!("{}{}{}", open, items.join(", "), close)
+    }
+}
+
+fn float_repr(float: f64) -> String {
+    if float.is_nan() {
+        "nan".to_owned()
+    } else if float.is_infinite() {
+        if float > 0.0 { "inf" } else { "-inf" }.to_owned()
+    } else {
+        format!("{:?}", float)
+    }
+}
+
+/// Quote like Python 2's `str.__repr__`: in single quotes, unless the string
+/// contains single but no double quotes.
+fn quote(chars: impl Iterator<Item = char> + Clone) -> String {
+    let quote = if chars.clone().any(|c| c == '\'') && !chars.clone().any(|c| c == '"') {
+        '"'
+    } else {
+        '\''
+    };
+
+    let mut repr = String::new();
+    repr.push(quote);
+    for c in chars {
+        match c {
+            '\\' => repr.push_str("\\\\"),
+            '\n' => repr.push_str("\\n"),
+            '\r' => repr.push_str("\\r"),
+            '\t' => repr.push_str("\\t"),
+            c if c == quote => {
+                repr.push('\\');
+                repr.push(c);
+            }
+            ' '..='~' => repr.push(c),
+            c if (c as u32) < 0x100 => repr.push_str(&format!("\\x{:02x}", c as u32)),
+            c if (c as u32) < 0x10000 => repr.push_str(&format!("\\u{:04x}", c as u32)),
+            c => repr.push_str(&format!("\\U{:08x}", c as u32)),
+        }
+    }
+    repr.push(quote);
+
+    repr
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cpython27::Cpython2_7;
+    use crate::interpreter::Pointer;
+    use crate::testing::spawn_python27;
+    use crate::walker::walk;
+
+    #[test]
+    fn example_list() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27(
+            r#"
+            class Something:
+                def __init__(self, anything):
+                    self.anything = anything
+            something = Something("I'm here!")
+            entry = ["hello world", 42, something, (22, 1000), (1.5,), {'a': None}]
+            "#,
+        )?;
+        let mem = crate::connect(child.pid)?;
+
+        let graph = walk::<Cpython2_7, _>(&mem, Pointer::new(child.pointer));
+        let something = match graph.get(&DataPointer(child.pointer)) {
+            Some(DecodedData::List(items)) => items[2],
+            node => anyhow::bail!("Expected a list, got {:?}", node),
+        };
+        assert_eq!(
+            to_repr(&graph, DataPointer(child.pointer), 8, 100),
+            format!(
+                "['hello world', 42, <Something instance at {:#x}>, (22, 1000), (1.5,), {{'a': None}}]",
+                something.0
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn cycles_depth_and_length() {
+        let mut graph = HashMap::new();
+        // A list containing itself, a nested list and a long list.
+        graph.insert(
+            DataPointer(0x100),
+            DecodedData::List(vec![
+                DataPointer(0x100),
+                DataPointer(0x200),
+                DataPointer(0x400),
+            ]),
+        );
+        graph.insert(
+            DataPointer(0x200),
+            DecodedData::List(vec![DataPointer(0x300)]),
+        );
+        graph.insert(DataPointer(0x300), DecodedData::List(vec![]));
+        graph.insert(
+            DataPointer(0x400),
+            DecodedData::Tuple(vec![DataPointer(0x500); 5]),
+        );
+        graph.insert(
+            DataPointer(0x500),
+            DecodedData::String("it's \"quoted\"\n".to_owned()),
+        );
+
+        assert_eq!(
+            to_repr(&graph, DataPointer(0x100), 8, 3),
+            r#"[[...], [[]], ('it\'s "quoted"\n', 'it\'s "quoted"\n', 'it\'s "quoted"\n', ...)]"#
+        );
+        assert_eq!(to_repr(&graph, DataPointer(0x100), 2, 1), "[[...], ...]");
+        assert_eq!(to_repr(&graph, DataPointer(0x200), 1, 10), "[[...]]");
+    }
+}
@@ -0,0 +1,146 @@
+//! Scanning raw memory regions, without walking an object graph.
+
+use crate::interpreter::Pointer;
+use crate::memory::Memory;
+
+/// Regions are read in chunks of this many bytes.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Chunks that cannot be read whole are read again in pieces of this many
+/// bytes, aligned to page boundaries, so that the mapped pages of a partly
+/// mapped chunk are still scanned.
+const PAGE_SIZE: usize = 4096;
+
+/// Find the addresses of all pointer-aligned words in the region
+/// `[start, end)` whose value equals `target`. This answers "who points at
+/// this object" without walking a graph.
+///
+/// Region bounds that are not word-aligned are rounded inwards. Pages of the
+/// region that cannot be read are skipped.
+pub fn scan_pointers_to<M: Memory + ?Sized>(
+    mem: &M,
+    region: (usize, usize),
+    target: usize,
+) -> Vec<Pointer> {
+    let (start, end) = region;
+    let start = match start.checked_add(Pointer::SIZE - 1) {
+        Some(start) => start / Pointer::SIZE * Pointer::SIZE,
+        None => return Vec::new(),
+    };
+    let end = end / Pointer::SIZE * Pointer::SIZE;
+
+    let mut found = Vec::new();
+    let mut chunk_start = start;
+    while chunk_start < end {
+        let chunk_end = chunk_start + CHUNK_SIZE.min(end - chunk_start);
+        if !scan_range(mem, chunk_start, chunk_end, target, &mut found) {
+            let mut page_start = chunk_start;
+            while page_start < chunk_end {
+                let page_end = (page_start / PAGE_SIZE + 1)
+                    .saturating_mul(PAGE_SIZE)
+                    .min(chunk_end);
+                scan_range(mem, page_start, page_end, target, &mut found);
+                page_start = page_end;
+            }
+        }
+        chunk_start = chunk_end;
+    }
+
+    found
+}
+
+/// Add the addresses of the words in `[start, end)` that equal `target` to
+/// `found`. Returns `false`, finding nothing, if the range cannot be read
+/// whole.
+fn scan_range<M: Memory + ?Sized>(
+    mem: &M,
+    start: usize,
+    end: usize,
+    target: usize,
+    found: &mut Vec<Pointer>,
+) -> bool {
+    let size = end - start;
+    if !mem.is_readable(start, size) {
+        return false;
+    }
+    let bytes = match mem.get_vec(start, size) {
+        Ok(bytes) if bytes.len() == size => bytes,
+        _ => return false,
+    };
+    for (idx, word) in bytes.chunks_exact(Pointer::SIZE).enumerate() {
+        let mut bytes = [0; Pointer::SIZE];
+        bytes.copy_from_slice(word);
+        if usize::from_le_bytes(bytes) == target {
+            found.push(Pointer::new(start + idx * Pointer::SIZE));
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::SliceMemory;
+
+    #[test]
+    fn finds_planted_pointers() {
+        let target: usize = 0x7f00_dead_beef;
+        let mut data = vec![0u8; 256];
+        data[16..24].copy_from_slice(&target.to_le_bytes());
+        data[200..208].copy_from_slice(&target.to_le_bytes());
+        // Unaligned occurrences are not reported.
+        data[99..107].copy_from_slice(&target.to_le_bytes());
+        let mem = SliceMemory::new(0x1000, &data);
+
+        assert_eq!(
+            scan_pointers_to(&mem, (0x1000, 0x1100), target),
+            vec![Pointer::new(0x1010), Pointer::new(0x10c8)]
+        );
+        // Unaligned bounds are rounded inwards.
+        assert_eq!(
+            scan_pointers_to(&mem, (0x1011, 0x10cf), target),
+            Vec::<Pointer>::new()
+        );
+        assert_eq!(
+            scan_pointers_to(&mem, (0x1009, 0x10d1), target),
+            vec![Pointer::new(0x1010), Pointer::new(0x10c8)]
+        );
+    }
+
+    /// Memory with an unreadable hole, as left by an unmapped page.
+    struct HoleMemory<'a> {
+        inner: SliceMemory<'a>,
+        hole: std::ops::Range<usize>,
+    }
+
+    impl Memory for HoleMemory<'_> {
+        fn get_vec(&self, address: usize, size: usize) -> crate::error::Result<Vec<u8>> {
+            if address < self.hole.end && self.hole.start < address + size {
+                return Err(crate::error::Error::Decode);
+            }
+            self.inner.get_vec(address, size)
+        }
+    }
+
+    #[test]
+    fn skips_only_unreadable_pages() {
+        let target: usize = 0x7f00_dead_beef;
+        let base = 0x10000;
+        let mut data = vec![0u8; CHUNK_SIZE];
+        for offset in [0x10, 0x2ff8, 0x3000, 0x3ff8, 0x4000, 0xfff8] {
+            data[offset..offset + 8].copy_from_slice(&target.to_le_bytes());
+        }
+        let mem = HoleMemory {
+            inner: SliceMemory::new(base, &data),
+            hole: base + 0x3000..base + 0x4000,
+        };
+
+        assert_eq!(
+            scan_pointers_to(&mem, (base, base + CHUNK_SIZE), target),
+            [0x10, 0x2ff8, 0x4000, 0xfff8]
+                .iter()
+                .map(|offset| Pointer::new(base + offset))
+                .collect::<Vec<_>>()
+        );
+    }
+}
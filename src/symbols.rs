@@ -0,0 +1,269 @@
+//! Resolve symbols of the target's loaded executable and libraries to runtime
+//! addresses, so walks can start from e.g. `PyList_Type` without computing
+//! its address by hand.
+
+use goblin::elf::{program_header::PT_LOAD, Elf};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::error::{Error, Result};
+use crate::interpreter::{Interpreter, Pointer};
+use crate::memory::Process;
+use crate::walker::{walk, DataPointer, DecodedData};
+
+/// Symbol addresses resolved ahead of time, e.g. by external tooling or from
+/// a symbol server. Lookups that find a symbol here do not read the target's
+/// mappings or ELF files, and [`TypeCache::with_symbols`] identifies the
+/// singletons from them without reading the target at all.
+///
+/// [`TypeCache::with_symbols`]: crate::walker::TypeCache::with_symbols
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Symbols {
+    addresses: HashMap<String, usize>,
+}
+
+impl Symbols {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record `name` as being at the runtime `address` in the target.
+    pub fn insert(&mut self, name: impl Into<String>, address: usize) {
+        self.addresses.insert(name.into(), address);
+    }
+
+    pub fn get(&self, name: &str) -> Option<Pointer> {
+        self.addresses
+            .get(name)
+            .map(|&address| Pointer::new(address))
+    }
+}
+
+impl<S: Into<String>> std::iter::FromIterator<(S, usize)> for Symbols {
+    fn from_iter<T: IntoIterator<Item = (S, usize)>>(iter: T) -> Self {
+        Self {
+            addresses: iter
+                .into_iter()
+                .map(|(name, address)| (name.into(), address))
+                .collect(),
+        }
+    }
+}
+
+/// An ELF file mapped by the target: its main executable or a shared
+/// library.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Image {
+    /// The path the file was mapped from.
+    pub path: PathBuf,
+    /// The lowest address the file's first page is mapped at.
+    pub base: usize,
+}
+
+/// The files mapped by the target, in order of their first mapping. This
+/// includes the main executable, which matters when the interpreter is
+/// statically linked into a host application (e.g. uWSGI) rather than loaded
+/// as `libpython2.7.so`.
+pub fn mapped_images(process: &Process) -> Result<Vec<Image>> {
+    let maps = proc_maps::get_process_maps(process.pid())
+        .map_err(|error| Error::RemoteProcessConnect(remoteprocess::Error::IOError(error)))?;
+
+    let mut images: Vec<Image> = Vec::new();
+    for map in &maps {
+        let path = match map.filename() {
+            Some(path) if path.starts_with('/') => Path::new(path),
+            _ => continue,
+        };
+        if map.offset != 0 {
+            continue;
+        }
+        match images.iter_mut().find(|image| image.path == path) {
+            Some(image) => image.base = image.base.min(map.start()),
+            None => images.push(Image {
+                path: path.to_owned(),
+                base: map.start(),
+            }),
+        }
+    }
+
+    Ok(images)
+}
+
+/// Resolve `symbol` to its address in the target, searching every image
+/// from [`mapped_images`].
+///
+/// Note that the address is that of the symbol itself: for `PyInt_Type` it
+/// points at the type object, while for `_PyThreadState_Current` it points
+/// at a pointer to the current thread state.
+pub fn resolve_symbol(process: &Process, symbol: &str) -> Result<Pointer> {
+    resolve_symbol_in(&mapped_images(process)?, symbol)
+}
+
+/// Resolve `symbol` from `known` if it is there, and otherwise as
+/// [`resolve_symbol`] does.
+pub fn resolve_symbol_with(
+    process: &Process,
+    known: Option<&Symbols>,
+    symbol: &str,
+) -> Result<Pointer> {
+    match known.and_then(|known| known.get(symbol)) {
+        Some(pointer) => Ok(pointer),
+        None => resolve_symbol(process, symbol),
+    }
+}
+
+/// Walk the graph reachable from the object at `symbol`, such as
+/// `PyInt_Type`, resolving it as [`resolve_symbol_with`] does.
+pub fn walk_symbol<I: Interpreter>(
+    process: &Process,
+    known: Option<&Symbols>,
+    symbol: &str,
+) -> Result<HashMap<DataPointer, DecodedData>> {
+    let pointer = resolve_symbol_with(process, known, symbol)?;
+    Ok(walk::<I, _>(process, pointer))
+}
+
+/// Resolve `symbol` to its address in the first of `images` defining it,
+/// offset by that image's load bias.
+///
+/// Images are read from their paths, so they must be reachable from the
+/// calling process; unreadable files and files that are not ELF are skipped.
+pub fn resolve_symbol_in(images: &[Image], symbol: &str) -> Result<Pointer> {
+    for image in images {
+        let bytes = match std::fs::read(&image.path) {
+            Ok(bytes) => bytes,
+            Err(_) => continue,
+        };
+        let elf = match Elf::parse(&bytes) {
+            Ok(elf) => elf,
+            Err(_) => continue,
+        };
+        if let Some(value) = symbol_value(&elf, symbol) {
+            return Ok(Pointer::new(
+                load_bias(&elf, image.base).wrapping_add(value),
+            ));
+        }
+    }
+
+    Err(Error::SymbolNotFound(symbol.to_owned()))
+}
+
+/// The link-time value of `symbol`, if `elf` defines it in either its static
+/// or its dynamic symbol table.
+fn symbol_value(elf: &Elf, symbol: &str) -> Option<usize> {
+    let tables = [(&elf.syms, &elf.strtab), (&elf.dynsyms, &elf.dynstrtab)];
+    tables.iter().find_map(|(syms, strtab)| {
+        syms.iter()
+            .filter(|sym| sym.st_shndx != 0 && sym.st_value != 0)
+            .find(|sym| matches!(strtab.get(sym.st_name), Some(Ok(name)) if name == symbol))
+            .map(|sym| sym.st_value as usize)
+    })
+}
+
+/// The difference between the runtime and link-time addresses of `elf`,
+/// given the address its first page is mapped at.
+///
+/// The bias wraps when the image is mapped below its link-time address, so
+/// it has to be added with wrapping arithmetic as well.
+fn load_bias(elf: &Elf, base: usize) -> usize {
+    let first_load = elf
+        .program_headers
+        .iter()
+        .filter(|header| header.p_type == PT_LOAD)
+        .map(|header| header.p_vaddr as usize & !(header.p_align.max(1) as usize - 1))
+        .min()
+        .unwrap_or(0);
+    base.wrapping_sub(first_load)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cpython27::Cpython2_7;
+    use crate::testing::spawn_python27;
+    use crate::walker::{walk, DataPointer, DecodedData};
+    use anyhow::bail;
+
+    #[test]
+    fn resolve_int_type() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27("entry = int")?;
+        let process = crate::connect(child.pid)?;
+
+        let pointer = resolve_symbol(&process, "PyInt_Type")?;
+        assert_eq!(pointer.address(), child.pointer);
+
+        let graph = walk::<Cpython2_7, _>(&process, pointer);
+        match graph.get(&DataPointer::from(pointer)) {
+            Some(DecodedData::Type(name)) => assert_eq!(name, "int"),
+            node => bail!("Expected a type, got {:?}", node),
+        }
+
+        assert!(matches!(
+            resolve_symbol(&process, "NotASymbolInTheTarget"),
+            Err(Error::SymbolNotFound(_))
+        ));
+
+        Ok(())
+    }
+
+    /// Symbols are looked up per image, so a definition in the executable is
+    /// found just like one in a shared library. The test interpreter links
+    /// `libpython2.7.so`, leaving `PyInt_Type` undefined in the executable; to
+    /// exercise the statically linked case by hand, build CPython 2.7 with
+    /// `--disable-shared` and point `PYTHON_SYS_EXECUTABLE` at it, then
+    /// `PyInt_Type` resolves from the executable's image instead.
+    #[test]
+    fn resolve_per_image() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27("entry = int")?;
+        let process = crate::connect(child.pid)?;
+
+        let images = mapped_images(&process)?;
+        let exe = std::fs::read_link(format!("/proc/{}/exe", child.pid))?;
+        assert!(images.iter().any(|image| image.path == exe));
+
+        let defining: Vec<_> = images
+            .iter()
+            .filter(|image| resolve_symbol_in(std::slice::from_ref(image), "PyInt_Type").is_ok())
+            .collect();
+        assert_eq!(defining.len(), 1);
+        assert_eq!(
+            resolve_symbol_in(std::slice::from_ref(defining[0]), "PyInt_Type")?.address(),
+            child.pointer
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn known_symbols() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27("entry = int")?;
+        let process = crate::connect(child.pid)?;
+
+        // Neither name is in any of the target's images, so they can only be
+        // found in `known`.
+        let known: Symbols = vec![("external_int_type", child.pointer), ("unused", 0x1000)]
+            .into_iter()
+            .collect();
+        assert_eq!(
+            resolve_symbol_with(&process, Some(&known), "external_int_type")?.address(),
+            child.pointer
+        );
+        assert!(matches!(
+            resolve_symbol_with(&process, None, "external_int_type"),
+            Err(Error::SymbolNotFound(_))
+        ));
+        // Symbols missing from `known` still resolve from the images.
+        assert_eq!(
+            resolve_symbol_with(&process, Some(&known), "PyInt_Type")?.address(),
+            child.pointer
+        );
+
+        let graph = walk_symbol::<Cpython2_7>(&process, Some(&known), "external_int_type")?;
+        match graph.get(&DataPointer(child.pointer)) {
+            Some(DecodedData::Type(name)) => assert_eq!(name, "int"),
+            node => bail!("Expected a type, got {:?}", node),
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,131 @@
+//! Helpers shared by the tests in this crate.
+
+use std::io::{BufRead, BufReader};
+use std::process::{Command, Stdio};
+
+use crate::cpython27::bindings;
+use crate::memory::SliceMemory;
+use crate::Pid;
+
+/// A spawned Python child process. The child is killed when this is dropped.
+pub struct Child {
+    child: std::process::Child,
+    pub pid: Pid,
+    /// The address of the object the child bound to `entry`.
+    pub pointer: usize,
+}
+
+impl Drop for Child {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Remove the common leading indentation of all non-empty lines, so Python
+/// sources can be written as indented raw strings.
+fn dedent(source: &str) -> String {
+    let indent = source
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+
+    source
+        .lines()
+        .map(|line| line.get(indent..).unwrap_or(""))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Spawn a Python 2.7 child running `source`. The source must bind the object
+/// of interest to `entry`; the child reports its address and then blocks
+/// until killed.
+pub fn spawn_python27(source: &str) -> Result<Child, anyhow::Error> {
+    let program = format!(
+        "import sys\n{}\nprint(id(entry))\nsys.stdout.flush()\nsys.stdin.readline()\n",
+        dedent(source)
+    );
+
+    let mut child = Command::new("python2")
+        .arg("-c")
+        .arg(program)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    let pid = child.id() as Pid;
+    let stdout = child.stdout.take().unwrap();
+
+    let mut line = String::new();
+    BufReader::new(stdout).read_line(&mut line)?;
+    let pointer: usize = line.trim().parse().expect("memory address");
+
+    Ok(Child {
+        child,
+        pid,
+        pointer,
+    })
+}
+
+/// A fake target heap for tests that need objects no real interpreter would
+/// produce. Values are written at absolute addresses starting at `base`.
+pub struct FakeHeap {
+    base: usize,
+    data: Vec<u8>,
+}
+
+impl FakeHeap {
+    pub fn new(base: usize, size: usize) -> Self {
+        Self {
+            base,
+            data: vec![0; size],
+        }
+    }
+
+    /// Write the in-memory representation of `value` at `address`.
+    pub fn write<T: Copy>(&mut self, address: usize, value: T) {
+        let bytes = unsafe {
+            std::slice::from_raw_parts(&value as *const T as *const u8, std::mem::size_of::<T>())
+        };
+        self.write_bytes(address, bytes);
+    }
+
+    fn write_bytes(&mut self, address: usize, bytes: &[u8]) {
+        let start = address - self.base;
+        self.data[start..start + bytes.len()].copy_from_slice(bytes);
+    }
+
+    /// Write a CPython 2.7 type object named `name` at `address`, for
+    /// instances that are a bare `PyObject`.
+    pub fn write_type(&mut self, address: usize, name: &str) {
+        let fields = bindings::PyTypeObject {
+            tp_basicsize: std::mem::size_of::<bindings::PyObject>() as isize,
+            ..Default::default()
+        };
+        self.write_type_with(address, name, fields);
+    }
+
+    /// Write a CPython 2.7 type object named `name` at `address`, taking its
+    /// other fields from `fields`. The name is stored right behind the type
+    /// object.
+    pub fn write_type_with(&mut self, address: usize, name: &str, fields: bindings::PyTypeObject) {
+        let name_address = address + std::mem::size_of::<bindings::PyTypeObject>();
+        self.write_bytes(name_address, name.as_bytes());
+        self.write(name_address + name.len(), 0u8);
+        self.write(
+            address,
+            bindings::PyTypeObject {
+                ob_refcnt: 1,
+                tp_name: name_address as *const _,
+                ..fields
+            },
+        );
+    }
+
+    pub fn memory(&self) -> SliceMemory<'_> {
+        SliceMemory::new(self.base, &self.data)
+    }
+}
@@ -0,0 +1,245 @@
+//! Consistency checks on walked graphs, to judge how trustworthy a dump of a
+//! partially-corrupt target is.
+
+use std::collections::HashMap;
+
+use crate::walker::{DataPointer, DecodedData};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InconsistencyKind {
+    /// The referenced node is not in the graph.
+    Dangling,
+    /// The referenced node is not of the kind the edge requires, e.g. an
+    /// object's type decoded as an `Error`.
+    Mistyped { expected: &'static str },
+}
+
+/// An edge from `from` to `to` that does not resolve as it should.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Inconsistency {
+    pub from: DataPointer,
+    pub to: DataPointer,
+    pub kind: InconsistencyKind,
+}
+
+/// The kind of node an edge must point to.
+#[derive(Copy, Clone)]
+enum Expect {
+    Any,
+    Type,
+    Class,
+    Tuple,
+    Traceback,
+}
+
+impl Expect {
+    fn name(self) -> &'static str {
+        match self {
+            Expect::Any => "any node",
+            Expect::Type => "type",
+            Expect::Class => "class",
+            Expect::Tuple => "tuple",
+            Expect::Traceback => "traceback",
+        }
+    }
+
+    fn matches(self, data: &DecodedData) -> bool {
+        matches!(
+            (self, data),
+            (Expect::Any, _)
+                | (Expect::Type, DecodedData::Type(_))
+                | (Expect::Class, DecodedData::Class { .. })
+                | (Expect::Tuple, DecodedData::Tuple(_))
+                | (Expect::Traceback, DecodedData::Traceback { .. })
+        )
+    }
+}
+
+fn any<'a>(
+    pointers: impl IntoIterator<Item = &'a DataPointer>,
+    edges: &mut Vec<(DataPointer, Expect)>,
+) {
+    edges.extend(pointers.into_iter().map(|&pointer| (pointer, Expect::Any)));
+}
+
+/// The outgoing edges of a node.
+fn edges(data: &DecodedData, edges: &mut Vec<(DataPointer, Expect)>) {
+    match data {
+        DecodedData::Object {
+            object_type,
+            attributes,
+            base_value,
+            ..
+        } => {
+            edges.push((*object_type, Expect::Type));
+            any(attributes.values(), edges);
+            if let Some(base_value) = base_value {
+                self::edges(base_value, edges);
+            }
+        }
+        DecodedData::Class { bases, .. } => {
+            edges.extend(bases.iter().map(|&base| (base, Expect::Class)));
+        }
+        DecodedData::Instance {
+            instance_class,
+            attributes,
+            ..
+        } => {
+            edges.push((*instance_class, Expect::Class));
+            any(attributes.values(), edges);
+        }
+        DecodedData::Tuple(items)
+        | DecodedData::List(items)
+        | DecodedData::Set { members: items, .. }
+        | DecodedData::Deque(items) => {
+            any(items, edges);
+        }
+        DecodedData::Dict { entries, .. } => {
+            any(entries.iter().flat_map(|(key, value)| [key, value]), edges);
+        }
+        DecodedData::DefaultDict {
+            entries,
+            default_factory,
+            ..
+        } => {
+            any(entries.iter().flat_map(|(key, value)| [key, value]), edges);
+            any(default_factory, edges);
+        }
+        DecodedData::OrderedDict(entries) => {
+            any(entries.iter().flat_map(|(key, value)| [key, value]), edges);
+        }
+        DecodedData::Exception {
+            args,
+            message,
+            attributes,
+            ..
+        } => {
+            edges.push((*args, Expect::Tuple));
+            any(message, edges);
+            any(attributes.values(), edges);
+        }
+        DecodedData::MemoryView { base, .. } => any(base, edges),
+        DecodedData::Property { fget, fset, fdel } => {
+            any(fget.iter().chain(fset).chain(fdel), edges);
+        }
+        DecodedData::Slice { start, stop, step } => {
+            any(start.iter().chain(stop).chain(step), edges);
+        }
+        DecodedData::Traceback { frame, next, .. } => {
+            edges.push((*frame, Expect::Any));
+            edges.extend(next.iter().map(|&next| (next, Expect::Traceback)));
+        }
+        DecodedData::ClassMethod { callable, .. } | DecodedData::StaticMethod { callable, .. } => {
+            edges.push((*callable, Expect::Any));
+        }
+        DecodedData::WrapperDescriptor { owner, .. } => edges.push((*owner, Expect::Type)),
+        DecodedData::SlotWrapper {
+            descriptor,
+            bound_self,
+        } => {
+            edges.push((*descriptor, Expect::Any));
+            edges.push((*bound_self, Expect::Any));
+        }
+        DecodedData::Code {
+            consts,
+            names,
+            varnames,
+            ..
+        } => {
+            edges.extend([consts, names, varnames].map(|&tuple| (tuple, Expect::Tuple)));
+        }
+        DecodedData::Enumerate { source, .. } => edges.push((*source, Expect::Any)),
+        DecodedData::Reversed { source, .. } => any(source, edges),
+        #[cfg(feature = "datetime")]
+        DecodedData::DateTime { tzinfo, .. } => any(tzinfo, edges),
+        DecodedData::Type(_)
+        | DecodedData::Unknown { .. }
+        | DecodedData::Buffer(_)
+        | DecodedData::None
+        | DecodedData::Bytes(_)
+        | DecodedData::String(_)
+        | DecodedData::Bool(_)
+        | DecodedData::Int(_)
+        | DecodedData::Float(_)
+        | DecodedData::Mmap { .. }
+        | DecodedData::Capsule { .. }
+        | DecodedData::Array { .. }
+        | DecodedData::Skipped(_)
+        | DecodedData::FreedOrUninitialized { .. }
+        | DecodedData::Error(_) => {}
+    }
+}
+
+/// Check that every reference in `graph` resolves to a present node of the
+/// appropriate kind. References to `DataPointer(0)` (NULL children) are not
+/// checked, as whether they have a node depends on the `NullPolicy`.
+pub fn validate(graph: &HashMap<DataPointer, DecodedData>) -> Vec<Inconsistency> {
+    let mut inconsistencies = Vec::new();
+    let mut node_edges = Vec::new();
+
+    for (&from, data) in graph {
+        node_edges.clear();
+        edges(data, &mut node_edges);
+
+        for &(to, expect) in &node_edges {
+            if to == DataPointer(0) {
+                continue;
+            }
+            let kind = match graph.get(&to) {
+                None => InconsistencyKind::Dangling,
+                Some(data) if !expect.matches(data) => InconsistencyKind::Mistyped {
+                    expected: expect.name(),
+                },
+                Some(_) => continue,
+            };
+            inconsistencies.push(Inconsistency { from, to, kind });
+        }
+    }
+
+    inconsistencies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+
+    #[test]
+    fn dangling_and_mistyped() {
+        let mut graph = HashMap::new();
+        graph.insert(
+            DataPointer(0x100),
+            DecodedData::List(vec![DataPointer(0x200), DataPointer(0x300), DataPointer(0)]),
+        );
+        graph.insert(DataPointer(0x200), DecodedData::Bool(true));
+        graph.insert(
+            DataPointer(0x400),
+            DecodedData::Object {
+                object_type: DataPointer(0x500),
+                object_type_name: "Broken".to_owned(),
+                attributes: HashMap::new(),
+                mro_names: Vec::new(),
+                base_value: None,
+            },
+        );
+        graph.insert(DataPointer(0x500), DecodedData::Error(Error::Decode));
+
+        let mut inconsistencies = validate(&graph);
+        inconsistencies.sort_by_key(|inconsistency| inconsistency.from.0);
+        assert_eq!(
+            inconsistencies,
+            [
+                Inconsistency {
+                    from: DataPointer(0x100),
+                    to: DataPointer(0x300),
+                    kind: InconsistencyKind::Dangling,
+                },
+                Inconsistency {
+                    from: DataPointer(0x400),
+                    to: DataPointer(0x500),
+                    kind: InconsistencyKind::Mistyped { expected: "type" },
+                },
+            ]
+        );
+    }
+}
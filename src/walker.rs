@@ -1,13 +1,59 @@
 use num_bigint::BigInt;
-use std::collections::{HashMap, VecDeque};
+use std::collections::{hash_map::Entry, HashMap, HashSet, VecDeque};
+use std::sync::Arc;
 
 use crate::error::{Error, Result};
 use crate::interpreter::*;
-use crate::memory::Memory;
+use crate::memory::{CountingMemory, Memory};
+#[cfg(target_os = "linux")]
+use crate::symbols::Symbols;
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct DataPointer(pub usize);
 
+impl From<Pointer> for DataPointer {
+    fn from(pointer: Pointer) -> Self {
+        DataPointer(pointer.address())
+    }
+}
+
+impl From<DataPointer> for Pointer {
+    fn from(pointer: DataPointer) -> Self {
+        Pointer::new(pointer.0)
+    }
+}
+
+impl From<usize> for DataPointer {
+    fn from(address: usize) -> Self {
+        DataPointer(address)
+    }
+}
+
+impl From<DataPointer> for usize {
+    fn from(pointer: DataPointer) -> Self {
+        pointer.0
+    }
+}
+
+impl std::fmt::Display for DataPointer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:#x}", self.0)
+    }
+}
+
+/// Parses the hex form produced by `Display`, with or without the `0x` prefix.
+impl std::str::FromStr for DataPointer {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let digits = s
+            .strip_prefix("0x")
+            .or_else(|| s.strip_prefix("0X"))
+            .unwrap_or(s);
+        usize::from_str_radix(digits, 16).map(DataPointer)
+    }
+}
+
 #[derive(Debug)]
 pub enum DecodedData {
     Type(String),
@@ -15,11 +61,29 @@ pub enum DecodedData {
         object_type: DataPointer,
         object_type_name: String,
         attributes: HashMap<String, DataPointer>,
+        /// The names of the types in the object's MRO, if
+        /// `WalkOptions::resolve_mro_names` is set.
+        mro_names: Vec<String>,
+        /// The builtin value of instances of subclasses of `int`, `str`,
+        /// `unicode`, `tuple` and `list`.
+        base_value: Option<Box<DecodedData>>,
+    },
+    /// An object of a builtin or extension type that is not decoded, as
+    /// opposed to an instance of a user-defined class, which is an `Object`.
+    Unknown {
+        type_name: String,
+        /// The `tp_basicsize` bytes of the object, taken from the raw capture
+        /// if `WalkOptions::capture_raw` is set.
+        raw_header: Option<Vec<u8>>,
     },
+    /// The bytes exported through the buffer protocol by an object of a type
+    /// that is not otherwise decoded, such as `bytearray`; see
+    /// [`try_read_buffer`].
+    Buffer(Vec<u8>),
     None,
     Class {
         class_name: String,
-        bases: Option<DataPointer>,
+        bases: Vec<DataPointer>,
     },
     Instance {
         instance_class: DataPointer,
@@ -30,68 +94,1005 @@ pub enum DecodedData {
     String(String),
     Tuple(Vec<DataPointer>),
     List(Vec<DataPointer>),
-    Dict(HashMap<DataPointer, DataPointer>),
+    Dict {
+        entries: HashMap<DataPointer, DataPointer>,
+        /// Whether the hash table had more slots than
+        /// `WalkOptions::max_dict_slots`, so that only the entries in the first
+        /// slots are present.
+        partial: bool,
+    },
+    /// A `collections.defaultdict`.
+    DefaultDict {
+        entries: HashMap<DataPointer, DataPointer>,
+        /// See `DecodedData::Dict::partial`.
+        partial: bool,
+        /// The factory for missing keys, if set.
+        default_factory: Option<DataPointer>,
+    },
+    /// A `collections.OrderedDict`, with its `(key, value)` entries in
+    /// insertion order.
+    OrderedDict(Vec<(DataPointer, DataPointer)>),
+    /// The members of a `set` or `frozenset`.
+    Set {
+        members: Vec<DataPointer>,
+        /// See `DecodedData::Dict::partial`.
+        partial: bool,
+    },
+    /// The elements of a `collections.deque`, from left to right.
+    Deque(Vec<DataPointer>),
     Bool(bool),
     Int(BigInt),
+    /// The raw value, so NaN and the infinities are kept as they are. Compare
+    /// with `f64::total_cmp` where NaN must equal itself.
     Float(f64),
+    Exception {
+        type_name: String,
+        args: DataPointer,
+        message: Option<DataPointer>,
+        attributes: HashMap<String, DataPointer>,
+        /// See `DecodedData::Object::mro_names`.
+        mro_names: Vec<String>,
+    },
+    MemoryView {
+        base: Option<DataPointer>,
+        len: isize,
+        readonly: bool,
+    },
+    Property {
+        fget: Option<DataPointer>,
+        fset: Option<DataPointer>,
+        fdel: Option<DataPointer>,
+    },
+    Traceback {
+        frame: DataPointer,
+        lineno: i32,
+        next: Option<DataPointer>,
+    },
+    /// A `classmethod`, with the name of the wrapped callable if it is a
+    /// Python function.
+    ClassMethod {
+        callable: DataPointer,
+        name: Option<String>,
+    },
+    /// See `DecodedData::ClassMethod`.
+    StaticMethod {
+        callable: DataPointer,
+        name: Option<String>,
+    },
+    /// A `wrapper_descriptor`, e.g. `object.__dict__['__str__']`.
+    WrapperDescriptor {
+        owner: DataPointer,
+        name: String,
+    },
+    /// A `method-wrapper`, e.g. `obj.__str__` where `__str__` is a C slot.
+    SlotWrapper {
+        descriptor: DataPointer,
+        bound_self: DataPointer,
+    },
+    Code {
+        name: String,
+        filename: String,
+        firstlineno: i32,
+        /// The constant pool tuple, which holds the code objects of nested
+        /// functions.
+        consts: DataPointer,
+        names: DataPointer,
+        varnames: DataPointer,
+    },
+    /// An `mmap.mmap`. The mapped bytes are not read.
+    Mmap {
+        size: usize,
+        pos: usize,
+        fd: i32,
+    },
+    Enumerate {
+        index: isize,
+        /// The iterator over the enumerated source.
+        source: DataPointer,
+    },
+    Reversed {
+        index: isize,
+        /// The reversed sequence, or `None` once the iterator is exhausted.
+        source: Option<DataPointer>,
+    },
+    /// A `slice`. Bounds that are `None` are `None` here.
+    Slice {
+        start: Option<DataPointer>,
+        stop: Option<DataPointer>,
+        step: Option<DataPointer>,
+    },
+    /// A `PyCapsule`, wrapping a native pointer for C extensions.
+    Capsule {
+        name: Option<String>,
+        pointer: usize,
+    },
+    /// An `array.array`, with the elements of its buffer decoded according to
+    /// its typecode. Long arrays may be truncated by
+    /// `WalkOptions::max_value_len`.
+    Array {
+        typecode: char,
+        values: ArrayValues,
+    },
+    /// A `datetime.date`, `datetime.datetime` or `datetime.timedelta`.
+    #[cfg(feature = "datetime")]
+    DateTime {
+        value: DateTimeValue,
+        /// The `tzinfo` of an aware `datetime`.
+        tzinfo: Option<DataPointer>,
+    },
+    /// An object that was not decoded because its type is not in
+    /// `WalkOptions::decode_types`, with the name of its type.
+    Skipped(String),
+    /// An object whose `ob_type` is NULL, as seen with objects that are
+    /// being allocated or freed on a live heap.
+    FreedOrUninitialized {
+        ob_refcnt: isize,
+    },
     Error(Error),
 }
 
+impl DecodedData {
+    /// The kind of object this node was decoded as, or `None` for nodes that
+    /// do not describe a decoded object (`Skipped`, `FreedOrUninitialized`
+    /// and `Error`).
+    /// Unicode strings decode as `Type::String`, and objects of unrecognized
+    /// static types as `Type::Object`.
+    pub fn kind(&self) -> Option<Type> {
+        Some(match self {
+            DecodedData::Type(_) => Type::Type,
+            DecodedData::Object { .. } | DecodedData::Unknown { .. } | DecodedData::Buffer(_) => {
+                Type::Object
+            }
+            DecodedData::None => Type::None,
+            DecodedData::Class { .. } => Type::Class,
+            DecodedData::Instance { .. } => Type::Instance,
+            DecodedData::Bytes(_) => Type::Bytes,
+            DecodedData::String(_) => Type::String,
+            DecodedData::Tuple(_) => Type::Tuple,
+            DecodedData::List(_) => Type::List,
+            DecodedData::Dict { .. } | DecodedData::OrderedDict(_) => Type::Dict,
+            DecodedData::DefaultDict { .. } => Type::DefaultDict,
+            DecodedData::Set { .. } => Type::Set,
+            DecodedData::Deque(_) => Type::Deque,
+            DecodedData::Bool(_) => Type::Bool,
+            DecodedData::Int(_) => Type::Int,
+            DecodedData::Float(_) => Type::Float,
+            DecodedData::Exception { .. } => Type::Exception,
+            DecodedData::MemoryView { .. } => Type::MemoryView,
+            DecodedData::Property { .. } => Type::Property,
+            DecodedData::Traceback { .. } => Type::Traceback,
+            DecodedData::ClassMethod { .. } => Type::ClassMethod,
+            DecodedData::StaticMethod { .. } => Type::StaticMethod,
+            DecodedData::WrapperDescriptor { .. } => Type::WrapperDescriptor,
+            DecodedData::SlotWrapper { .. } => Type::SlotWrapper,
+            DecodedData::Code { .. } => Type::Code,
+            DecodedData::Mmap { .. } => Type::Mmap,
+            DecodedData::Enumerate { .. } => Type::Enumerate,
+            DecodedData::Reversed { .. } => Type::Reversed,
+            DecodedData::Slice { .. } => Type::Slice,
+            DecodedData::Capsule { .. } => Type::Capsule,
+            DecodedData::Array { .. } => Type::Array,
+            #[cfg(feature = "datetime")]
+            DecodedData::DateTime { .. } => Type::DateTime,
+            DecodedData::Skipped(_)
+            | DecodedData::FreedOrUninitialized { .. }
+            | DecodedData::Error(_) => return None,
+        })
+    }
+
+    /// A hashable copy of this node's value, if it is a scalar: `None`, a
+    /// bool, an int, a float, a string or bytes.
+    pub fn scalar(&self) -> Option<ScalarValue> {
+        Some(match self {
+            DecodedData::None => ScalarValue::None,
+            DecodedData::Bool(value) => ScalarValue::Bool(*value),
+            DecodedData::Int(value) => ScalarValue::Int(value.clone()),
+            DecodedData::Float(value) => ScalarValue::Float(value.to_bits()),
+            DecodedData::String(value) => ScalarValue::String(value.clone()),
+            DecodedData::Bytes(value) => ScalarValue::Bytes(value.clone()),
+            _ => return None,
+        })
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        if let DecodedData::String(value) = self {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        if let DecodedData::Bytes(value) = self {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        if let DecodedData::Bool(value) = self {
+            Some(*value)
+        } else {
+            None
+        }
+    }
+
+    pub fn as_int(&self) -> Option<&BigInt> {
+        if let DecodedData::Int(value) = self {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    pub fn as_float(&self) -> Option<f64> {
+        if let DecodedData::Float(value) = self {
+            Some(*value)
+        } else {
+            None
+        }
+    }
+
+    pub fn as_tuple(&self) -> Option<&[DataPointer]> {
+        if let DecodedData::Tuple(items) = self {
+            Some(items)
+        } else {
+            None
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&[DataPointer]> {
+        if let DecodedData::List(items) = self {
+            Some(items)
+        } else {
+            None
+        }
+    }
+
+    pub fn as_set(&self) -> Option<&[DataPointer]> {
+        if let DecodedData::Set { members, .. } = self {
+            Some(members)
+        } else {
+            None
+        }
+    }
+
+    /// The entries of a `dict` or `collections.defaultdict`. The entries of an
+    /// `OrderedDict` are ordered, so they are not returned here.
+    pub fn as_dict(&self) -> Option<&HashMap<DataPointer, DataPointer>> {
+        match self {
+            DecodedData::Dict { entries, .. } | DecodedData::DefaultDict { entries, .. } => {
+                Some(entries)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn as_error(&self) -> Option<&Error> {
+        if let DecodedData::Error(error) = self {
+            Some(error)
+        } else {
+            None
+        }
+    }
+}
+
+/// The value of a scalar node, usable as a map key. See
+/// [`DecodedData::scalar`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ScalarValue {
+    None,
+    Bool(bool),
+    Int(BigInt),
+    /// The bit pattern of the float, as returned by `f64::to_bits`. Floats are
+    /// equal if their bits are, so a NaN equals a NaN with the same bits, and
+    /// `0.0` does not equal `-0.0`.
+    Float(u64),
+    String(String),
+    Bytes(Vec<u8>),
+}
+
+/// How NULL child pointers (e.g. uninitialized list slots) are recorded in the
+/// graph. The parent refers to the child as `DataPointer(0)` regardless of
+/// policy; the policy decides the node stored at that address.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum NullPolicy {
+    /// Store a `DecodedData::Error(Error::NullPointer)` node.
+    #[default]
+    AsError,
+    /// Store a `DecodedData::None` node.
+    AsNone,
+    /// Do not create a node.
+    Skip,
+}
+
+/// The order in which a walk visits the objects it has discovered.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum TraversalOrder {
+    /// Visit objects in the order they were discovered, so that shallow
+    /// objects are visited before deeper ones.
+    #[default]
+    Bfs,
+    /// Visit the most recently discovered object first, so that an object's
+    /// subtree is visited before its later siblings. Siblings are visited
+    /// last to first.
+    Dfs,
+}
+
+/// Options for [`walk_with_options`].
+#[derive(Clone, Default)]
+pub struct WalkOptions {
+    pub null_policy: NullPolicy,
+    /// Attach the names of the types in each object's MRO to
+    /// `DecodedData::Object` nodes.
+    pub resolve_mro_names: bool,
+    /// Capture the raw bytes of each object: its header and, for variable-size
+    /// objects, the items up to `ob_size`. At most [`MAX_RAW_CAPTURE`] bytes
+    /// are captured per object.
+    pub capture_raw: bool,
+    /// Called with the key of each dict entry and attribute whose key is a
+    /// string. If it returns `false`, the value is recorded but not walked.
+    pub key_filter: Option<KeyFilter>,
+    /// Read at most this many bytes from the target. The first read that
+    /// would exceed the budget fails and stops the walk, leaving the object
+    /// being decoded out of the graph.
+    pub max_total_bytes: Option<usize>,
+    /// Retry decoding an object once with the target paused (see
+    /// [`Memory::suspend`]) if it fails in a way that may be due to reading it
+    /// mid-update while the target was running. Only objects that fail again
+    /// become `DecodedData::Error` nodes. At most [`MAX_SUSPENDED_RETRIES`]
+    /// objects are retried per walk.
+    pub retry_suspended: bool,
+    /// The number of hash table slots read per dict or set, or
+    /// [`DEFAULT_MAX_DICT_SLOTS`] if `None`. Larger dicts and sets are decoded
+    /// as partial.
+    pub max_dict_slots: Option<usize>,
+    /// Only decode objects of these types. Objects of other types, including
+    /// the root, are recorded as `DecodedData::Skipped` and their children are
+    /// not walked, so objects only reachable through them are not in the
+    /// graph. `str` and `unicode` are `Type::String` and `Type::Unicode`
+    /// respectively.
+    pub decode_types: Option<HashSet<Type>>,
+    /// Walk the attribute dict (`tp_dict`) of each type that is reached,
+    /// either as an object or as the type of one, so that methods and class
+    /// variables are in the graph. See `WalkOutput::type_dicts`.
+    pub follow_type_dicts: bool,
+    /// Decode at most this many items of each `str` (bytes), `unicode` (code
+    /// units) and bytes object, so that long values do not bloat the graph.
+    /// Unlike without a limit, values of any length can then be decoded. See
+    /// `WalkOutput::truncated_values`.
+    pub max_value_len: Option<usize>,
+    /// Record objects that fail to decode in `WalkOutput::errors` instead of
+    /// as `DecodedData::Error` nodes, so the graph holds only decoded objects.
+    /// With `NullPolicy::AsError`, NULL children are recorded there too.
+    pub errors_sidechannel: bool,
+    /// The order in which discovered objects are visited. The resulting graph
+    /// is the same either way, unless the walk is cut short by
+    /// `max_total_bytes`.
+    pub order: TraversalOrder,
+    /// Read the weakref list of each object that can be weakly referenced,
+    /// walking the `weakref`s and proxies referring to it. See
+    /// `WalkOutput::weak_references`.
+    pub follow_weak_references: bool,
+    /// Record how many references from the root each object was discovered
+    /// at, in `WalkOutput::depths`.
+    pub record_depth: bool,
+}
+
+impl WalkOptions {
+    fn max_dict_slots(&self) -> usize {
+        self.max_dict_slots.unwrap_or(DEFAULT_MAX_DICT_SLOTS)
+    }
+
+    fn decodes(&self, object_type: Type) -> bool {
+        self.decode_types
+            .as_ref()
+            .is_none_or(|types| types.contains(&object_type))
+    }
+}
+
+/// See `WalkOptions::key_filter`.
+pub type KeyFilter = Arc<dyn Fn(&str) -> bool + Send + Sync>;
+
+impl std::fmt::Debug for WalkOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WalkOptions")
+            .field("null_policy", &self.null_policy)
+            .field("resolve_mro_names", &self.resolve_mro_names)
+            .field("capture_raw", &self.capture_raw)
+            .field("key_filter", &self.key_filter.as_ref().map(|_| ".."))
+            .field("max_total_bytes", &self.max_total_bytes)
+            .field("retry_suspended", &self.retry_suspended)
+            .field("max_dict_slots", &self.max_dict_slots)
+            .field("decode_types", &self.decode_types)
+            .field("follow_type_dicts", &self.follow_type_dicts)
+            .field("max_value_len", &self.max_value_len)
+            .field("errors_sidechannel", &self.errors_sidechannel)
+            .field("order", &self.order)
+            .field("follow_weak_references", &self.follow_weak_references)
+            .field("record_depth", &self.record_depth)
+            .finish()
+    }
+}
+
+/// The maximum number of raw bytes captured per object when
+/// `WalkOptions::capture_raw` is set.
+pub const MAX_RAW_CAPTURE: usize = 64 * 1024;
+
+/// The result of [`walk_with_options`].
+#[derive(Debug, Default)]
+pub struct WalkOutput {
+    pub graph: HashMap<DataPointer, DecodedData>,
+    /// The raw bytes of each object, if `WalkOptions::capture_raw` is set.
+    pub raw: HashMap<DataPointer, Vec<u8>>,
+    /// Whether the walk stopped early because it exceeded
+    /// `WalkOptions::max_total_bytes`. Nodes may then refer to children that
+    /// are not in the graph.
+    pub truncated_by_bytes: bool,
+    /// Nodes whose headers are inconsistent: lists whose `ob_size` exceeds
+    /// their `allocated`, so that only part of their contents was read, and
+    /// tuples that disagree with their type's layout (see
+    /// `TupleObject::inconsistent_with_type`).
+    pub suspicious: HashSet<DataPointer>,
+    /// The attribute dict of each type in the graph, if
+    /// `WalkOptions::follow_type_dicts` is set.
+    pub type_dicts: HashMap<DataPointer, DataPointer>,
+    /// The metatype (`ob_type`) of each type in the graph, e.g. `ABCMeta` for
+    /// classes created by it, and `type` for most others. Metatypes are walked
+    /// too, so each is in the graph as well.
+    pub metatypes: HashMap<DataPointer, DataPointer>,
+    /// The full length of each value truncated to
+    /// `WalkOptions::max_value_len`, in the units it was truncated in.
+    pub truncated_values: HashMap<DataPointer, usize>,
+    /// The `weakref`s and proxies referring to each object that has any, in
+    /// the order of its weakref list, if `WalkOptions::follow_weak_references`
+    /// is set. The weakrefs are walked too.
+    pub weak_references: HashMap<DataPointer, Vec<DataPointer>>,
+    /// The depth of each node in the graph, if `WalkOptions::record_depth` is
+    /// set: 0 for the root, and one more than the node it was discovered from
+    /// for others. With `TraversalOrder::Bfs` that is the length of the
+    /// shortest path from the root; with `Dfs` it may be longer.
+    pub depths: HashMap<DataPointer, usize>,
+    /// The objects that failed to decode, in the order they were reached, if
+    /// `WalkOptions::errors_sidechannel` is set.
+    pub errors: Vec<WalkError>,
+    /// The time spent decoding objects, per type.
+    #[cfg(feature = "profiling")]
+    pub timings: DecodeTimings,
+}
+
+/// An object that failed to decode. See `WalkOptions::errors_sidechannel`.
+#[derive(Debug)]
+pub struct WalkError {
+    pub pointer: DataPointer,
+    pub error: Error,
+}
+
+/// The cumulative time spent decoding the objects of each type during a walk,
+/// as classified by [`DecodedData::kind`]. Only the decoding of the object
+/// itself counts, not that of its children; objects that fail to decode are
+/// not counted.
+#[cfg(feature = "profiling")]
+#[derive(Clone, Debug, Default)]
+pub struct DecodeTimings {
+    pub durations: HashMap<Type, std::time::Duration>,
+    /// The number of objects decoded, per type.
+    pub counts: HashMap<Type, usize>,
+}
+
+#[cfg(feature = "profiling")]
+impl DecodeTimings {
+    fn record(&mut self, object_type: Type, elapsed: std::time::Duration) {
+        *self.durations.entry(object_type).or_default() += elapsed;
+        *self.counts.entry(object_type).or_default() += 1;
+    }
+
+    #[cfg(feature = "rayon")]
+    fn merge(&mut self, other: DecodeTimings) {
+        for (object_type, duration) in other.durations {
+            *self.durations.entry(object_type).or_default() += duration;
+        }
+        for (object_type, count) in other.counts {
+            *self.counts.entry(object_type).or_default() += count;
+        }
+    }
+
+    /// The time spent decoding objects of all types.
+    pub fn total(&self) -> std::time::Duration {
+        self.durations.values().sum()
+    }
+}
+
+/// Memoized type objects and their classification, keyed by type address.
+///
+/// Every walk memoizes types; ad-hoc decoders such as [`decode_one`] and
+/// [`resolve_path`] take a cache explicitly so it can be shared between calls.
+pub struct TypeCache<I: Interpreter> {
+    types: HashMap<usize, I::TypeObject>,
+    mro_names: HashMap<usize, Vec<String>>,
+    /// Types whose objects are decoded as scalars. Once a type is known to be
+    /// scalar, its objects skip the downcast.
+    scalar_types: HashMap<usize, Type>,
+    /// The `None`, `True` and `False` singletons seen, by address, with the
+    /// address of their type if it has been read. As singletons are never
+    /// freed, objects at these addresses are decoded without reading them.
+    singletons: HashMap<usize, (Singleton, Option<usize>)>,
+    /// The entries added since `State::checkpoint`, if one is taken.
+    journal: Option<Vec<CacheEntry>>,
+}
+
+/// A `TypeCache` entry, by map and key.
+#[derive(Copy, Clone, Debug)]
+enum CacheEntry {
+    Type(usize),
+    MroNames(usize),
+    ScalarType(usize),
+    Singleton(usize),
+}
+
+#[derive(Copy, Clone, Debug)]
+enum Singleton {
+    None,
+    Bool(bool),
+}
+
+impl Singleton {
+    fn data(self) -> DecodedData {
+        match self {
+            Singleton::None => DecodedData::None,
+            Singleton::Bool(value) => DecodedData::Bool(value),
+        }
+    }
+}
+
+impl<I: Interpreter> TypeCache<I> {
+    pub fn new() -> Self {
+        Self {
+            types: HashMap::new(),
+            mro_names: HashMap::new(),
+            scalar_types: HashMap::new(),
+            singletons: HashMap::new(),
+            journal: None,
+        }
+    }
+
+    /// A cache that knows the `None`, `True` and `False` singletons at the
+    /// addresses `symbols` has for `_Py_NoneStruct`, `_Py_TrueStruct` and
+    /// `_Py_ZeroStruct`, so that they are decoded without reading the target.
+    /// A walk still reads each of them once for its type.
+    #[cfg(target_os = "linux")]
+    pub fn with_symbols(symbols: &Symbols) -> Self {
+        let mut cache = Self::new();
+        for (symbol, singleton) in [
+            ("_Py_NoneStruct", Singleton::None),
+            ("_Py_TrueStruct", Singleton::Bool(true)),
+            ("_Py_ZeroStruct", Singleton::Bool(false)),
+        ] {
+            if let Some(pointer) = symbols.get(symbol) {
+                cache
+                    .singletons
+                    .insert(pointer.address(), (singleton, None));
+            }
+        }
+        cache
+    }
+
+    fn record(&mut self, entry: CacheEntry) {
+        if let Some(journal) = &mut self.journal {
+            journal.push(entry);
+        }
+    }
+
+    /// Remove the entries added since the journal was started.
+    fn rollback(&mut self) {
+        for entry in self.journal.take().unwrap_or_default() {
+            match entry {
+                CacheEntry::Type(address) => {
+                    self.types.remove(&address);
+                }
+                CacheEntry::MroNames(address) => {
+                    self.mro_names.remove(&address);
+                }
+                CacheEntry::ScalarType(address) => {
+                    self.scalar_types.remove(&address);
+                }
+                CacheEntry::Singleton(address) => {
+                    self.singletons.remove(&address);
+                }
+            }
+        }
+    }
+
+    /// The type object of `object`, read on first use.
+    fn type_object<M>(&mut self, mem: &M, object: &I::Object) -> Result<&I::TypeObject>
+    where
+        M: Memory + ?Sized,
+    {
+        let address = object.ob_type_pointer().address();
+        if let Entry::Vacant(entry) = self.types.entry(address) {
+            entry.insert(object.ob_type(mem)?);
+            self.record(CacheEntry::Type(address));
+        }
+        Ok(&self.types[&address])
+    }
+
+    /// Like `Object::downcast`, but reads each type object, including its
+    /// `tp_name`, only once per cache.
+    pub fn downcast<M>(&mut self, mem: &M, object: I::Object) -> Result<I::TypedObject>
+    where
+        M: Memory + ?Sized,
+    {
+        self.type_object(mem, &object)?.downcast(mem, object)
+    }
+
+    /// Remember the object at `address` if `data` shows it is a singleton.
+    fn remember_singleton(&mut self, address: usize, type_address: usize, data: &DecodedData) {
+        let singleton = match data {
+            DecodedData::None => Singleton::None,
+            DecodedData::Bool(value) => Singleton::Bool(*value),
+            _ => return,
+        };
+        if self
+            .singletons
+            .insert(address, (singleton, Some(type_address)))
+            .is_none()
+        {
+            self.record(CacheEntry::Singleton(address));
+        }
+    }
+}
+
+impl<I: Interpreter> Default for TypeCache<I> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+struct State<'o, I: Interpreter> {
+    options: &'o WalkOptions,
+    cache: &'o mut TypeCache<I>,
+    queue: VecDeque<I::Object>,
+    /// The addresses of every object queued so far, so that each is queued at
+    /// most once however many objects refer to it.
+    enqueued: HashSet<usize>,
+    found_null: bool,
+    /// See `WalkOutput::suspicious`.
+    suspicious: HashSet<DataPointer>,
+    /// See `WalkOutput::type_dicts`.
+    type_dicts: HashMap<DataPointer, DataPointer>,
+    /// See `WalkOutput::metatypes`.
+    metatypes: HashMap<DataPointer, DataPointer>,
+    /// See `WalkOutput::weak_references`.
+    weak_references: HashMap<DataPointer, Vec<DataPointer>>,
+    /// The depth of the object being decoded, if `WalkOptions::record_depth`
+    /// is set.
+    depth: usize,
+    /// See `WalkOutput::depths`. This also holds objects that are queued but
+    /// not yet decoded.
+    depths: HashMap<DataPointer, usize>,
+    /// See `WalkOutput::truncated_values`.
+    truncated_values: HashMap<DataPointer, usize>,
+    /// Whether to follow the values of each dict key seen so far, by the key's
+    /// address, if `WalkOptions::key_filter` is set.
+    key_verdicts: HashMap<usize, bool>,
+    /// Keys decoded by `follow_value` before they were dequeued.
+    decoded_keys: HashMap<usize, Decoded>,
+    /// The keys added to `key_verdicts` and `decoded_keys` since `checkpoint`,
+    /// if one is taken.
+    journal: Option<Vec<usize>>,
+    /// The number of objects decoded again with `WalkOptions::retry_suspended`.
+    retries: usize,
+}
+
+/// The walk state from before an attempt to decode an object, taken by
+/// `State::checkpoint`. The maps that decoding only adds to are set aside here,
+/// so that the attempt starts them empty.
+struct Checkpoint {
+    queued: usize,
+    found_null: bool,
+    suspicious: HashSet<DataPointer>,
+    type_dicts: HashMap<DataPointer, DataPointer>,
+    metatypes: HashMap<DataPointer, DataPointer>,
+    depths: HashMap<DataPointer, usize>,
+    truncated_values: HashMap<DataPointer, usize>,
+}
+
+/// `kept` with the entries of `added`, which replace those with the same key.
+fn merge<C, T>(mut kept: C, added: C) -> C
+where
+    C: Extend<T> + IntoIterator<Item = T>,
+{
+    kept.extend(added);
+    kept
+}
+
+impl<'o, I: Interpreter> State<'o, I> {
+    fn new(options: &'o WalkOptions, cache: &'o mut TypeCache<I>) -> Self {
+        Self {
+            options,
+            cache,
+            queue: VecDeque::new(),
+            enqueued: HashSet::new(),
+            found_null: false,
+            suspicious: HashSet::new(),
+            type_dicts: HashMap::new(),
+            metatypes: HashMap::new(),
+            weak_references: HashMap::new(),
+            depth: 0,
+            depths: HashMap::new(),
+            truncated_values: HashMap::new(),
+            key_verdicts: HashMap::new(),
+            decoded_keys: HashMap::new(),
+            journal: None,
+            retries: 0,
+        }
+    }
+
+    /// Start tracking what decoding the next object adds, so that it can be
+    /// undone by `rollback` or kept by `commit`.
+    fn checkpoint(&mut self) -> Checkpoint {
+        self.journal = Some(Vec::new());
+        self.cache.journal = Some(Vec::new());
+        Checkpoint {
+            queued: self.queue.len(),
+            found_null: self.found_null,
+            suspicious: std::mem::take(&mut self.suspicious),
+            type_dicts: std::mem::take(&mut self.type_dicts),
+            metatypes: std::mem::take(&mut self.metatypes),
+            depths: std::mem::take(&mut self.depths),
+            truncated_values: std::mem::take(&mut self.truncated_values),
+        }
+    }
+
+    /// Keep what was added since `checkpoint`.
+    fn commit(&mut self, checkpoint: Checkpoint) {
+        self.journal = None;
+        self.cache.journal = None;
+        self.found_null |= checkpoint.found_null;
+        self.suspicious = merge(checkpoint.suspicious, std::mem::take(&mut self.suspicious));
+        self.type_dicts = merge(checkpoint.type_dicts, std::mem::take(&mut self.type_dicts));
+        self.metatypes = merge(checkpoint.metatypes, std::mem::take(&mut self.metatypes));
+        self.depths = merge(checkpoint.depths, std::mem::take(&mut self.depths));
+        self.truncated_values = merge(
+            checkpoint.truncated_values,
+            std::mem::take(&mut self.truncated_values),
+        );
+    }
+
+    /// Undo what was added since `checkpoint`.
+    fn rollback(&mut self, checkpoint: Checkpoint) {
+        self.truncate_queue(checkpoint.queued);
+        for address in self.journal.take().unwrap_or_default() {
+            self.key_verdicts.remove(&address);
+            self.decoded_keys.remove(&address);
+        }
+        self.cache.rollback();
+        self.found_null = checkpoint.found_null;
+        self.suspicious = checkpoint.suspicious;
+        self.type_dicts = checkpoint.type_dicts;
+        self.metatypes = checkpoint.metatypes;
+        self.depths = checkpoint.depths;
+        self.truncated_values = checkpoint.truncated_values;
+    }
+
+    /// Queue `object` for decoding, unless it has been queued before.
+    fn enqueue(&mut self, object: I::Object) {
+        if self.enqueued.insert(object.me().address()) {
+            if self.options.record_depth {
+                self.depths
+                    .insert(DataPointer::from(object.me()), self.depth + 1);
+            }
+            self.queue.push_back(object);
+        }
+    }
+
+    /// Discard the queue entries past `len`, so that they may be queued again.
+    fn truncate_queue(&mut self, len: usize) {
+        for object in self.queue.drain(len.min(self.queue.len())..) {
+            self.enqueued.remove(&object.me().address());
+        }
+    }
+}
+
 struct Decoded {
     object_data: DecodedData,
-    type_object_data: DecodedData,
-    type_object_pointer: DataPointer,
+    /// `None` if the object has no type.
+    type_object_pointer: Option<DataPointer>,
+    raw: Option<Vec<u8>>,
 }
 
-fn step<I, M>(
-    mem: &M,
-    object: I::Object,
-    graph: &mut HashMap<DataPointer, DecodedData>,
-    queue: &mut VecDeque<I::Object>,
-    memoized_types: &mut HashMap<usize, I::TypeObject>,
-) -> Result<Decoded>
+/// Read the raw bytes of `object`, bounded by [`MAX_RAW_CAPTURE`].
+fn capture_raw<I, M>(mem: &M, object: &I::Object, type_object: &I::TypeObject) -> Result<Vec<u8>>
+where
+    I: Interpreter,
+    M: Memory + ?Sized,
+{
+    let mut size = type_object.tp_basicsize().max(0) as usize;
+    if type_object.tp_itemsize() != 0 {
+        let var_object: I::VarObject = object.me().try_deref_me(mem)?;
+        size = size.saturating_add(
+            (var_object.ob_size().unsigned_abs())
+                .saturating_mul(type_object.tp_itemsize().unsigned_abs()),
+        );
+    }
+    mem.get_vec(object.me().address(), size.min(MAX_RAW_CAPTURE))
+}
+
+fn step<I, M>(mem: &M, object: I::Object, state: &mut State<I>) -> Result<Decoded>
 where
     I: Interpreter,
-    M: Memory,
+    M: Memory + ?Sized,
 {
+    let me = object.me();
+    if let Some(&(singleton, Some(type_address))) = state.cache.singletons.get(&me.address()) {
+        let object_data = singleton.data();
+        if !state.options.capture_raw
+            && object_data.kind().is_some_and(|t| state.options.decodes(t))
+        {
+            return Ok(Decoded {
+                object_data,
+                type_object_pointer: Some(DataPointer(type_address)),
+                raw: None,
+            });
+        }
+    }
+
     let type_ptr = object.ob_type_pointer();
-    let type_object = if let Some(type_object) = memoized_types.get(&type_ptr.address()) {
-        type_object
+    if type_ptr.null() {
+        return Ok(Decoded {
+            object_data: DecodedData::FreedOrUninitialized {
+                ob_refcnt: object.ob_refcnt(),
+            },
+            type_object_pointer: None,
+            raw: None,
+        });
+    }
+    state.cache.type_object(mem, &object)?;
+    let type_object = &state.cache.types[&type_ptr.address()];
+    let raw = if state.options.capture_raw {
+        Some(capture_raw::<I, M>(mem, &object, type_object)?)
     } else {
-        let type_object = object.ob_type(mem)?;
-        memoized_types.insert(type_ptr.address(), type_object);
-        memoized_types.get(&type_ptr.address()).unwrap()
+        None
     };
-    let type_name = type_object.name().to_string();
-    let type_object_data = DecodedData::Type(type_object.name().to_string());
 
+    if let Some(&scalar_type) = state.cache.scalar_types.get(&type_ptr.address()) {
+        if !state.options.decodes(scalar_type) {
+            return Ok(Decoded {
+                object_data: DecodedData::Skipped(type_object.name().to_string()),
+                type_object_pointer: Some(DataPointer::from(type_ptr)),
+                raw,
+            });
+        }
+        let object_data = decode_scalar::<I, M>(mem, object, &scalar_type, state)?;
+        state
+            .cache
+            .remember_singleton(me.address(), type_ptr.address(), &object_data);
+        return Ok(Decoded {
+            object_data,
+            type_object_pointer: Some(DataPointer::from(type_ptr)),
+            raw,
+        });
+    }
+
+    let type_name = type_object.name().to_string();
     let typed = type_object.downcast(mem, object)?;
+    let object_type = typed.object_type();
+    if !state.options.decodes(object_type) {
+        return Ok(Decoded {
+            object_data: DecodedData::Skipped(type_name),
+            type_object_pointer: Some(DataPointer::from(type_ptr)),
+            raw,
+        });
+    }
+
+    let decoded = match object_type {
+        Type::Type => {
+            let type_object = typed.as_type().unwrap();
+            let pointer = DataPointer::from(type_object.to_var_object().to_object().me());
+            // The metatype is this object's type, which the walk records.
+            state.metatypes.insert(pointer, DataPointer::from(type_ptr));
+            if let Some(dict) = type_dict::<I, _>(mem, &type_object, state.options) {
+                enqueue_type_dict(pointer, dict, state);
+            }
 
-    let decoded = match typed.object_type() {
-        Type::Type => DecodedData::Type(typed.as_type().unwrap().name().to_string()),
+            DecodedData::Type(type_object.name().to_string())
+        }
         Type::Object => {
-            let (_type_object, object) = typed.as_object().unwrap();
+            let (type_object, object) = typed.as_object().unwrap();
+            if !type_object.is_heap_type() {
+                if let Ok(Some(bytes)) = try_read_buffer::<I, M>(mem, &object) {
+                    return Ok(Decoded {
+                        object_data: DecodedData::Buffer(bytes),
+                        type_object_pointer: Some(DataPointer::from(type_ptr)),
+                        raw,
+                    });
+                }
+                let size = type_object.tp_basicsize().max(0) as usize;
+                let raw_header = raw.as_ref().map(|raw| raw[..size.min(raw.len())].to_vec());
+                return Ok(Decoded {
+                    object_data: DecodedData::Unknown {
+                        type_name,
+                        raw_header,
+                    },
+                    type_object_pointer: Some(DataPointer::from(type_ptr)),
+                    raw,
+                });
+            }
+            if type_name == "OrderedDict" {
+                if let Some(attr_dict) = object.attributes(mem)? {
+                    if let Some(entries) =
+                        ordered_dict_entries::<I, M>(mem, &object, attr_dict, state)?
+                    {
+                        let entries = entries
+                            .into_iter()
+                            .map(|entry| {
+                                let (_hash, key, value) = entry.take();
+                                let pointers =
+                                    (DataPointer::from(key.me()), DataPointer::from(value.me()));
+                                let follow = follow_value::<I, M>(mem, &key, state);
+                                state.enqueue(key);
+                                if follow {
+                                    state.enqueue(value);
+                                }
+                                pointers
+                            })
+                            .collect();
+                        return Ok(Decoded {
+                            object_data: DecodedData::OrderedDict(entries),
+                            type_object_pointer: Some(DataPointer::from(type_ptr)),
+                            raw,
+                        });
+                    }
+                }
+            }
+            let base_value = match type_object.builtin_base() {
+                Some(Type::Tuple) => {
+                    let tuple: I::TupleObject = object.me().try_deref_me(mem)?;
+                    if tuple.inconsistent_with_type(&type_object) {
+                        state.suspicious.insert(DataPointer::from(object.me()));
+                    }
+                    Some(DecodedData::Tuple(collect_items::<I>(
+                        tuple.items(mem),
+                        tuple.len(),
+                        state,
+                    )?))
+                }
+                Some(Type::List) => {
+                    let list: I::ListObject = object.me().try_deref_me(mem)?;
+                    if list.exceeds_allocated() {
+                        state.suspicious.insert(DataPointer::from(object.me()));
+                    }
+                    Some(DecodedData::List(collect_items::<I>(
+                        list.items(mem),
+                        list.len(),
+                        state,
+                    )?))
+                }
+                Some(scalar_type @ (Type::Int | Type::Long | Type::String | Type::Unicode)) => {
+                    Some(decode_scalar::<I, M>(
+                        mem,
+                        object.clone(),
+                        &scalar_type,
+                        state,
+                    )?)
+                }
+                _ => None,
+            };
             let attr_dict = object.attributes(mem)?;
+            let mro_names = if state.options.resolve_mro_names {
+                mro_names::<I, M>(mem, type_ptr, state)?
+            } else {
+                Vec::new()
+            };
 
             DecodedData::Object {
-                object_type: DataPointer(type_ptr.address()),
+                object_type: DataPointer::from(type_ptr),
                 object_type_name: type_name,
+                mro_names,
+                base_value: base_value.map(Box::new),
                 attributes: match attr_dict {
-                    Some(dict) => {
-                        let mut attributes = HashMap::new();
-                        for (_hash, key, value) in
-                            dict.entries(mem)?.into_iter().map(|entry| entry.take())
-                        {
-                            // If the input data is is bad, this might recurse forever.
-                            if let DecodedData::String(string) =
-                                step::<I, M>(mem, key, graph, queue, memoized_types)?.object_data
-                            {
-                                attributes.insert(string, DataPointer(value.me().address()));
-                                queue.push_back(value);
-                            }
-                        }
-                        attributes
-                    }
+                    Some(dict) => decode_attributes::<I, M>(mem, dict, state)?,
                     None => HashMap::new(),
                 },
             }
@@ -102,13 +1103,16 @@ where
 
             DecodedData::Class {
                 class_name: class.name().to_owned(),
-                bases: match class.bases(mem)? {
-                    Some(base_class) => {
-                        queue.push_back(base_class.to_object());
-                        Some(DataPointer(base_class.to_object().me().address()))
-                    }
-                    None => None,
-                },
+                bases: class
+                    .bases(mem)?
+                    .into_iter()
+                    .map(|base_class| {
+                        let base = base_class.to_object();
+                        let pointer = DataPointer::from(base.me());
+                        state.enqueue(base);
+                        pointer
+                    })
+                    .collect(),
             }
         }
         Type::Instance => {
@@ -117,124 +1121,2664 @@ where
             let attr_dict = instance.attributes(mem)?;
 
             DecodedData::Instance {
-                instance_class: DataPointer(class.to_object().me().address()),
+                instance_class: DataPointer::from(class.to_object().me()),
                 instance_class_name: class.name().to_owned(),
-                attributes: {
-                    let mut attributes = HashMap::new();
-                    for (_hash, key, value) in attr_dict
-                        .entries(mem)?
-                        .into_iter()
-                        .map(|entry| entry.take())
-                    {
-                        // If the input data is is bad, this might recurse forever.
-                        if let DecodedData::String(string) =
-                            step::<I, M>(mem, key, graph, queue, memoized_types)?.object_data
-                        {
-                            attributes.insert(string, DataPointer(value.me().address()));
-                            queue.push_back(value);
-                        }
-                    }
-                    attributes
-                },
+                attributes: decode_attributes::<I, M>(mem, attr_dict, state)?,
             }
         }
 
-        Type::Bytes => DecodedData::Bytes(typed.as_bytes().unwrap().read(mem)?),
-        Type::String => DecodedData::String(typed.as_string().unwrap().read(mem)?),
-        Type::Unicode => DecodedData::String(typed.as_unicode().unwrap().read(mem)?),
-        Type::Tuple => {
-            let tuple = typed.as_tuple().unwrap();
+        Type::Bytes => {
+            let bytes = typed.as_bytes().unwrap();
+            let pointer = DataPointer::from(bytes.to_var_object().to_object().me());
+
+            DecodedData::Bytes(read_value(
+                state,
+                pointer,
+                || bytes.read(mem),
+                |max_len| bytes.read_prefix(mem, max_len),
+            )?)
+        }
+        Type::String => {
+            let string = typed.as_string().unwrap();
+            let pointer = DataPointer::from(string.to_var_object().to_object().me());
 
-            let mut items = Vec::new();
+            DecodedData::String(read_value(
+                state,
+                pointer,
+                || string.read(mem),
+                |max_len| string.read_prefix(mem, max_len),
+            )?)
+        }
+        Type::Unicode => {
+            let unicode = typed.as_unicode().unwrap();
+            let pointer = DataPointer::from(unicode.to_object().me());
 
-            for item in tuple
-                .items(mem)
-                .take_while(Result::is_ok)
-                .map(Result::unwrap)
-            {
-                items.push(DataPointer(item.me().address()));
-                queue.push_back(item);
+            DecodedData::String(read_value(
+                state,
+                pointer,
+                || unicode.read(mem),
+                |max_len| unicode.read_prefix(mem, max_len),
+            )?)
+        }
+        Type::Tuple => {
+            let tuple = typed.as_tuple().unwrap();
+            if tuple.inconsistent_with_type(&state.cache.types[&type_ptr.address()]) {
+                state
+                    .suspicious
+                    .insert(DataPointer::from(tuple.to_var_object().to_object().me()));
             }
+            let items = collect_items::<I>(tuple.items(mem), tuple.len(), state)?;
 
             DecodedData::Tuple(items)
         }
         Type::List => {
             let list = typed.as_list().unwrap();
-
-            let mut items = Vec::new();
-
-            for item in list
-                .items(mem)
-                .take_while(Result::is_ok)
-                .map(Result::unwrap)
-            {
-                items.push(DataPointer(item.me().address()));
-                queue.push_back(item);
+            if list.exceeds_allocated() {
+                state
+                    .suspicious
+                    .insert(DataPointer::from(list.to_var_object().to_object().me()));
             }
+            let items = collect_items::<I>(list.items(mem), list.len(), state)?;
 
             DecodedData::List(items)
         }
         Type::Dict => {
             let dict = typed.as_dict().unwrap();
+            let (entries, partial) = decode_dict_entries::<I, M>(mem, &dict, state)?;
 
-            let mut entries = HashMap::new();
+            DecodedData::Dict { entries, partial }
+        }
+        Type::DefaultDict => {
+            let default_dict = typed.as_default_dict().unwrap();
+            let (entries, partial) = decode_dict_entries::<I, M>(mem, default_dict.dict(), state)?;
 
-            for (_hash, key, value) in dict.entries(mem)?.into_iter().map(|entry| entry.take()) {
-                entries.insert(
-                    DataPointer(key.me().address()),
-                    DataPointer(value.me().address()),
-                );
-                queue.push_back(key);
-                queue.push_back(value);
+            DecodedData::DefaultDict {
+                entries,
+                partial,
+                default_factory: enqueue_optional(default_dict.default_factory(mem)?, state),
             }
+        }
+        Type::Set => {
+            let set = typed.as_set().unwrap();
+            let (members, partial) = set.members(mem, state.options.max_dict_slots())?;
+            let len = members.len();
+            let members = collect_items::<I>(members.into_iter().map(Ok), len, state)?;
 
-            DecodedData::Dict(entries)
+            DecodedData::Set { members, partial }
+        }
+        Type::Deque => {
+            let deque = typed.as_deque().unwrap();
+            let items = deque.items(mem)?;
+            let len = items.len();
+            DecodedData::Deque(collect_items::<I>(items.into_iter().map(Ok), len, state)?)
         }
         Type::Bool => DecodedData::Bool(typed.as_bool().unwrap().value()),
         Type::Int => DecodedData::Int(typed.as_int().unwrap().read(mem)?),
+        Type::Long => DecodedData::Int(
+            typed
+                .as_long()
+                .unwrap()
+                .read(mem, &state.cache.types[&type_ptr.address()])?,
+        ),
         Type::Float => DecodedData::Float(typed.as_float().unwrap().value()),
-    };
+        Type::Exception => {
+            let exception = typed.as_exception().unwrap();
+            let args = exception.args(mem)?.to_var_object().to_object();
+            let args_pointer = DataPointer::from(args.me());
+            state.enqueue(args);
+            let mro_names = if state.options.resolve_mro_names {
+                mro_names::<I, M>(mem, type_ptr, state)?
+            } else {
+                Vec::new()
+            };
 
-    Ok(Decoded {
-        object_data: decoded,
-        type_object_data,
-        type_object_pointer: DataPointer(type_ptr.address()),
-    })
-}
+            DecodedData::Exception {
+                type_name,
+                args: args_pointer,
+                message: enqueue_optional(exception.message(mem)?, state),
+                attributes: match exception.attributes(mem)? {
+                    Some(dict) => decode_attributes::<I, M>(mem, dict, state)?,
+                    None => HashMap::new(),
+                },
+                mro_names,
+            }
+        }
+        Type::MemoryView => {
+            let view = typed.as_memory_view().unwrap();
 
-pub fn walk<I, M>(mem: &M, pointer: Pointer) -> HashMap<DataPointer, DecodedData>
-where
-    I: Interpreter,
-    M: Memory,
-{
-    let mut graph: HashMap<DataPointer, DecodedData> = HashMap::new();
-    let mut queue: VecDeque<I::Object> = VecDeque::new();
-    let mut memoized_types: HashMap<usize, I::TypeObject> = HashMap::new();
+            DecodedData::MemoryView {
+                base: enqueue_optional(view.base(mem)?, state),
+                len: view.len(),
+                readonly: view.readonly(),
+            }
+        }
+        Type::Property => {
+            let property = typed.as_property().unwrap();
 
-    if let Ok(object) = pointer.try_deref_me(mem) {
-        queue.push_back(object);
-    }
+            DecodedData::Property {
+                fget: enqueue_optional(property.fget(mem)?, state),
+                fset: enqueue_optional(property.fset(mem)?, state),
+                fdel: enqueue_optional(property.fdel(mem)?, state),
+            }
+        }
+        Type::Traceback => {
+            let traceback = typed.as_traceback().unwrap();
+            let frame = traceback.frame(mem)?;
+            let frame_pointer = DataPointer::from(frame.me());
+            state.enqueue(frame);
+            let next = traceback.next(mem)?.map(|next| next.to_object());
 
-    while let Some(object) = queue.pop_front() {
-        let address = object.me().address();
-        if graph.contains_key(&DataPointer(address)) {
-            continue;
+            DecodedData::Traceback {
+                frame: frame_pointer,
+                lineno: traceback.lineno(),
+                next: enqueue_optional(next, state),
+            }
+        }
+        Type::ClassMethod => {
+            let (callable, name) =
+                decode_method_wrapper::<I, M>(mem, typed.as_class_method().unwrap(), state)?;
+            DecodedData::ClassMethod { callable, name }
+        }
+        Type::StaticMethod => {
+            let (callable, name) =
+                decode_method_wrapper::<I, M>(mem, typed.as_static_method().unwrap(), state)?;
+            DecodedData::StaticMethod { callable, name }
         }
+        Type::WrapperDescriptor => {
+            let descriptor = typed.as_wrapper_descriptor().unwrap();
+            let owner = descriptor.owner(mem)?;
+            let owner_pointer = DataPointer::from(owner.me());
+            state.enqueue(owner);
 
-        match step::<I, M>(mem, object, &mut graph, &mut queue, &mut memoized_types) {
+            DecodedData::WrapperDescriptor {
+                owner: owner_pointer,
+                name: descriptor.name(mem)?,
+            }
+        }
+        Type::SlotWrapper => {
+            let wrapper = typed.as_slot_wrapper().unwrap();
+            let descriptor = wrapper.descriptor(mem)?;
+            let bound_self = wrapper.bound_self(mem)?;
+            let decoded = DecodedData::SlotWrapper {
+                descriptor: DataPointer::from(descriptor.me()),
+                bound_self: DataPointer::from(bound_self.me()),
+            };
+            state.enqueue(descriptor);
+            state.enqueue(bound_self);
+
+            decoded
+        }
+        Type::Code => {
+            let code = typed.as_code().unwrap();
+            let mut enqueue = |object: I::Object| {
+                let pointer = DataPointer::from(object.me());
+                state.enqueue(object);
+                pointer
+            };
+
+            DecodedData::Code {
+                name: code.name(mem)?,
+                filename: code.filename(mem)?,
+                firstlineno: code.firstlineno(),
+                consts: enqueue(code.consts(mem)?),
+                names: enqueue(code.names(mem)?),
+                varnames: enqueue(code.varnames(mem)?),
+            }
+        }
+        Type::Enumerate | Type::Reversed => {
+            let enumerate = object_type == Type::Enumerate;
+            let iterator = if enumerate {
+                typed.as_enumerate()
+            } else {
+                typed.as_reversed()
+            }
+            .unwrap();
+            let source = iterator.source(mem)?.map(|source| {
+                let pointer = DataPointer::from(source.me());
+                state.enqueue(source);
+                pointer
+            });
+
+            if enumerate {
+                DecodedData::Enumerate {
+                    index: iterator.index(),
+                    source: source.ok_or(Error::Decode)?,
+                }
+            } else {
+                DecodedData::Reversed {
+                    index: iterator.index(),
+                    source,
+                }
+            }
+        }
+        Type::Slice => {
+            let slice = typed.as_slice().unwrap();
+
+            DecodedData::Slice {
+                start: enqueue_optional(slice.start(mem)?, state),
+                stop: enqueue_optional(slice.stop(mem)?, state),
+                step: enqueue_optional(slice.step(mem)?, state),
+            }
+        }
+        #[cfg(feature = "datetime")]
+        Type::DateTime => {
+            let date_time = typed.as_date_time().unwrap();
+            let tzinfo = date_time.tzinfo(mem)?.map(|tzinfo| {
+                let pointer = DataPointer::from(tzinfo.me());
+                state.enqueue(tzinfo);
+                pointer
+            });
+
+            DecodedData::DateTime {
+                value: date_time.value(),
+                tzinfo,
+            }
+        }
+        Type::Capsule => {
+            let capsule = typed.as_capsule().unwrap();
+            DecodedData::Capsule {
+                name: capsule.name(mem)?,
+                pointer: capsule.pointer(),
+            }
+        }
+        Type::Array => {
+            let array = typed.as_array().unwrap();
+            let pointer = DataPointer::from(array.to_var_object().to_object().me());
+
+            DecodedData::Array {
+                typecode: array.typecode(mem)?,
+                values: read_value(
+                    state,
+                    pointer,
+                    || array.read(mem),
+                    |max_len| array.read_prefix(mem, max_len),
+                )?,
+            }
+        }
+        Type::Mmap => {
+            let mmap = typed.as_mmap().unwrap();
+            DecodedData::Mmap {
+                size: mmap.size(),
+                pos: mmap.pos(),
+                fd: mmap.fd(),
+            }
+        }
+    };
+
+    if matches!(
+        object_type,
+        Type::None
+            | Type::String
+            | Type::Unicode
+            | Type::Bool
+            | Type::Int
+            | Type::Long
+            | Type::Float
+    ) {
+        if state
+            .cache
+            .scalar_types
+            .insert(type_ptr.address(), object_type)
+            .is_none()
+        {
+            state
+                .cache
+                .record(CacheEntry::ScalarType(type_ptr.address()));
+        }
+        state
+            .cache
+            .remember_singleton(me.address(), type_ptr.address(), &decoded);
+    }
+
+    Ok(Decoded {
+        object_data: decoded,
+        type_object_pointer: Some(DataPointer::from(type_ptr)),
+        raw,
+    })
+}
+
+/// The most objects a walk decodes again with `WalkOptions::retry_suspended`.
+pub const MAX_SUSPENDED_RETRIES: usize = 64;
+
+/// Decode `object`. If `WalkOptions::retry_suspended` is set and the target is
+/// running, an attempt that fails in a way that may stem from a torn read is
+/// undone, including the children it queued, and made again with the target
+/// paused.
+fn step_retrying<I, M>(mem: &M, object: I::Object, state: &mut State<I>) -> Result<Decoded>
+where
+    I: Interpreter,
+    M: Memory + ?Sized,
+{
+    if !state.options.retry_suspended
+        || state.retries >= MAX_SUSPENDED_RETRIES
+        || mem.is_suspended() != Some(false)
+    {
+        return step::<I, _>(mem, object, state);
+    }
+
+    let pointer = object.me();
+    let checkpoint = state.checkpoint();
+    let error = match step::<I, _>(mem, object, state) {
+        Err(error @ (Error::Decode | Error::SegmentationFault(_))) => error,
+        result => {
+            state.commit(checkpoint);
+            return result;
+        }
+    };
+    state.rollback(checkpoint);
+    let _suspension = match mem.suspend() {
+        Some(Ok(suspension)) => suspension,
+        _ => return Err(error),
+    };
+
+    state.retries += 1;
+    let object = pointer.try_deref_me(mem)?;
+    step::<I, _>(mem, object, state)
+}
+
+/// Exported buffers longer than this are not read by [`try_read_buffer`].
+pub const MAX_BUFFER_LEN: usize = 1 << 20;
+
+/// The bytes that `object` exports through the buffer protocol, or `None` if
+/// its type exports no buffer or keeps it where the interpreter does not know
+/// to look (see `TypeObject::exported_buffer`). Buffers longer than
+/// [`MAX_BUFFER_LEN`] fail with `Error::SizeError`.
+///
+/// The walker uses this for objects of static types it does not otherwise
+/// decode, which it would record as `DecodedData::Unknown`.
+pub fn try_read_buffer<I, M>(mem: &M, object: &I::Object) -> Result<Option<Vec<u8>>>
+where
+    I: Interpreter,
+    M: Memory + ?Sized,
+{
+    let (buf, len) = match object.ob_type(mem)?.exported_buffer(mem, object)? {
+        Some(buffer) => buffer,
+        None => return Ok(None),
+    };
+    if len > MAX_BUFFER_LEN {
+        return Err(Error::SizeError);
+    }
+    // Empty buffers may have a NULL address.
+    if len == 0 {
+        return Ok(Some(Vec::new()));
+    }
+    Ok(Some(mem.get_vec(buf.address_checked()?, len)?))
+}
+
+/// Read a value with `read`, or, if `WalkOptions::max_value_len` is set, its
+/// prefix with `read_prefix`, recording the full length of truncated values.
+fn read_value<I: Interpreter, T>(
+    state: &mut State<I>,
+    pointer: DataPointer,
+    read: impl FnOnce() -> Result<T>,
+    read_prefix: impl FnOnce(usize) -> Result<(T, usize)>,
+) -> Result<T> {
+    match state.options.max_value_len {
+        Some(max_len) => {
+            let (value, len) = read_prefix(max_len)?;
+            if len > max_len {
+                state.truncated_values.insert(pointer, len);
+            }
+            Ok(value)
+        }
+        None => read(),
+    }
+}
+
+/// Decode an object of a type previously classified as `scalar_type`, without
+/// downcasting through its type object.
+fn decode_scalar<I, M>(
+    mem: &M,
+    object: I::Object,
+    scalar_type: &Type,
+    state: &mut State<I>,
+) -> Result<DecodedData>
+where
+    I: Interpreter,
+    M: Memory + ?Sized,
+{
+    let me = object.me();
+    Ok(match scalar_type {
+        Type::None => DecodedData::None,
+        Type::String => {
+            let string = me.try_deref_me::<I::StringObject>(mem)?;
+            DecodedData::String(read_value(
+                state,
+                DataPointer::from(me),
+                || string.read(mem),
+                |max_len| string.read_prefix(mem, max_len),
+            )?)
+        }
+        Type::Unicode => {
+            let unicode = me.try_deref_me::<I::UnicodeObject>(mem)?;
+            DecodedData::String(read_value(
+                state,
+                DataPointer::from(me),
+                || unicode.read(mem),
+                |max_len| unicode.read_prefix(mem, max_len),
+            )?)
+        }
+        Type::Bool => DecodedData::Bool(me.try_deref_me::<I::BoolObject>(mem)?.value()),
+        Type::Int => DecodedData::Int(me.try_deref_me::<I::IntObject>(mem)?.read(mem)?),
+        Type::Long => DecodedData::Int(
+            me.try_deref_me::<I::LongObject>(mem)?
+                .read(mem, &state.cache.types[&object.ob_type_pointer().address()])?,
+        ),
+        Type::Float => DecodedData::Float(me.try_deref_me::<I::FloatObject>(mem)?.value()),
+        _ => unreachable!("not a scalar type"),
+    })
+}
+
+/// Read the names of the types in the MRO of the (memoized) type at
+/// `type_ptr`. The names are memoized per type.
+fn mro_names<I, M>(mem: &M, type_ptr: Pointer, state: &mut State<I>) -> Result<Vec<String>>
+where
+    I: Interpreter,
+    M: Memory + ?Sized,
+{
+    if let Some(names) = state.cache.mro_names.get(&type_ptr.address()) {
+        return Ok(names.clone());
+    }
+
+    let type_object = &state.cache.types[&type_ptr.address()];
+    let names = match type_object.tp_mro(mem)? {
+        Some(mro) => mro
+            .items(mem)
+            .map(|item| {
+                let type_object: I::TypeObject = item?.me().try_deref_me(mem)?;
+                Ok(type_object.name().to_owned())
+            })
+            .collect::<Result<Vec<_>>>()?,
+        // Types that have not been readied have no MRO yet; follow their
+        // single-inheritance chain instead.
+        None => {
+            let mut names = vec![type_object.name().to_owned()];
+            let mut seen = HashSet::from([type_ptr.address()]);
+            let mut base = type_object.tp_base(mem)?;
+            while let Some(type_object) = base {
+                if !seen.insert(type_object.to_var_object().to_object().me().address()) {
+                    return Err(Error::Decode);
+                }
+                names.push(type_object.name().to_owned());
+                base = type_object.tp_base(mem)?;
+            }
+            names
+        }
+    };
+    state
+        .cache
+        .mro_names
+        .insert(type_ptr.address(), names.clone());
+    state.cache.record(CacheEntry::MroNames(type_ptr.address()));
+
+    Ok(names)
+}
+
+/// Decode the string keys of an attribute dict, enqueueing the values.
+fn decode_attributes<I, M>(
+    mem: &M,
+    dict: I::DictObject,
+    state: &mut State<I>,
+) -> Result<HashMap<String, DataPointer>>
+where
+    I: Interpreter,
+    M: Memory + ?Sized,
+{
+    let mut attributes = HashMap::new();
+    let (entries, _) = dict.entries(mem, state.options.max_dict_slots())?;
+    for (_hash, key, value) in entries.into_iter().map(|entry| entry.take()) {
+        // If the input data is is bad, this might recurse forever.
+        if let DecodedData::String(string) = step::<I, M>(mem, key, state)?.object_data {
+            let follow = state
+                .options
+                .key_filter
+                .as_ref()
+                .is_none_or(|filter| filter(&string));
+            attributes.insert(string, DataPointer::from(value.me()));
+            if follow {
+                state.enqueue(value);
+            }
+        }
+    }
+
+    Ok(attributes)
+}
+
+/// Whether to walk the value of the dict entry with `key`, according to
+/// `WalkOptions::key_filter`. Values of keys that are not strings are walked.
+fn follow_value<I, M>(mem: &M, key: &I::Object, state: &mut State<I>) -> bool
+where
+    I: Interpreter,
+    M: Memory + ?Sized,
+{
+    let filter = match &state.options.key_filter {
+        Some(filter) => filter.clone(),
+        None => return true,
+    };
+    let address = key.me().address();
+    if let Some(&follow) = state.key_verdicts.get(&address) {
+        return follow;
+    }
+
+    // The key is decoded as if it were dequeued, one level below the dict.
+    state.depth += 1;
+    let decoded = step::<I, M>(mem, key.clone(), state);
+    state.depth -= 1;
+    let decoded = match decoded {
+        Ok(decoded) => decoded,
+        Err(_) => return true,
+    };
+    let follow = match &decoded.object_data {
+        DecodedData::String(key) => filter(key),
+        _ => true,
+    };
+    state.key_verdicts.insert(address, follow);
+    if let Some(journal) = &mut state.journal {
+        journal.push(address);
+    }
+    // A key that is not queued yet is about to be; `walk_in_order` then takes
+    // this result rather than decoding it again.
+    if !state.enqueued.contains(&address) {
+        state.decoded_keys.insert(address, decoded);
+    }
+    follow
+}
+
+/// Enqueue the callable of a `classmethod` or `staticmethod`, returning its
+/// address and name.
+fn decode_method_wrapper<I, M>(
+    mem: &M,
+    wrapper: I::MethodWrapperObject,
+    state: &mut State<I>,
+) -> Result<(DataPointer, Option<String>)>
+where
+    I: Interpreter,
+    M: Memory + ?Sized,
+{
+    let name = wrapper.callable_name(mem)?;
+    let callable = wrapper.callable(mem)?;
+    let pointer = DataPointer::from(callable.me());
+    state.enqueue(callable);
+
+    Ok((pointer, name))
+}
+
+/// Record the entries of `dict`, enqueueing the keys and, unless filtered by
+/// `WalkOptions::key_filter`, the values for decoding.
+fn decode_dict_entries<I, M>(
+    mem: &M,
+    dict: &I::DictObject,
+    state: &mut State<I>,
+) -> Result<(HashMap<DataPointer, DataPointer>, bool)>
+where
+    I: Interpreter,
+    M: Memory + ?Sized,
+{
+    let mut entries = HashMap::new();
+
+    let (dict_entries, partial) = dict.entries(mem, state.options.max_dict_slots())?;
+    for (_hash, key, value) in dict_entries.into_iter().map(|entry| entry.take()) {
+        entries.insert(DataPointer::from(key.me()), DataPointer::from(value.me()));
+        let follow = follow_value::<I, M>(mem, &key, state);
+        state.enqueue(key);
+        if follow {
+            state.enqueue(value);
+        }
+    }
+
+    Ok((entries, partial))
+}
+
+/// The entries of a Python 2.7 `collections.OrderedDict` in insertion order, or
+/// `None` if `object` has no `_OrderedDict__root` attribute.
+///
+/// The order is kept in a circular doubly linked list of `[prev, next, key]`
+/// lists, starting at the sentinel `_OrderedDict__root`; the values are in the
+/// object's own dict table.
+fn ordered_dict_entries<I, M>(
+    mem: &M,
+    object: &I::Object,
+    attr_dict: I::DictObject,
+    state: &mut State<I>,
+) -> Result<Option<Vec<I::DictEntry>>>
+where
+    I: Interpreter,
+    M: Memory + ?Sized,
+{
+    let root = match lookup_str_key::<I, M>(mem, attr_dict, "_OrderedDict__root", state.cache)? {
+        Some(root) => root,
+        None => return Ok(None),
+    };
+    let table: I::DictObject = object.me().try_deref_me(mem)?;
+    let (table_entries, _) = table.entries(mem, state.options.max_dict_slots())?;
+    let mut values: HashMap<Pointer, I::DictEntry> = table_entries
+        .into_iter()
+        .map(|entry| (entry.key().me(), entry))
+        .collect();
+    let link_fields = |link: &I::Object| -> Result<Vec<I::Object>> {
+        let link: I::ListObject = link.me().try_deref_me(mem)?;
+        let fields = link.items(mem).collect::<Result<Vec<_>>>()?;
+        if fields.len() != 3 {
+            return Err(Error::Decode);
+        }
+        Ok(fields)
+    };
+
+    let mut entries = Vec::with_capacity(values.len());
+    let mut fields = link_fields(&root)?;
+    // A well-formed list has a link per entry; guard against cycles that do
+    // not pass through the root.
+    for _ in 0..=values.len() {
+        let next = fields[1].clone();
+        if next.me() == root.me() {
+            return Ok(Some(entries));
+        }
+        fields = link_fields(&next)?;
+        if let Some(entry) = values.remove(&fields[2].me()) {
+            entries.push(entry);
+        }
+    }
+
+    Err(Error::Decode)
+}
+
+/// The attribute dict of `type_object`, if `WalkOptions::follow_type_dicts` is
+/// set and the type has one that can be read.
+fn type_dict<I, M>(
+    mem: &M,
+    type_object: &I::TypeObject,
+    options: &WalkOptions,
+) -> Option<I::DictObject>
+where
+    I: Interpreter,
+    M: Memory + ?Sized,
+{
+    if !options.follow_type_dicts {
+        return None;
+    }
+    type_object.tp_dict(mem).ok().flatten()
+}
+
+/// Enqueue the attribute dict of the type at `type_pointer` for decoding.
+fn enqueue_type_dict<I: Interpreter>(
+    type_pointer: DataPointer,
+    dict: I::DictObject,
+    state: &mut State<I>,
+) {
+    let object = dict.to_object();
+    state
+        .type_dicts
+        .insert(type_pointer, DataPointer::from(object.me()));
+    state.enqueue(object);
+}
+
+/// Enqueue the metatype of the type at `type_pointer` for decoding.
+fn enqueue_metatype<I, M>(
+    mem: &M,
+    type_pointer: DataPointer,
+    metatype: Pointer,
+    state: &mut State<I>,
+) where
+    I: Interpreter,
+    M: Memory + ?Sized,
+{
+    if let Ok(object) = metatype.try_deref_me::<I::Object>(mem) {
+        state
+            .metatypes
+            .insert(type_pointer, DataPointer::from(metatype));
+        state.enqueue(object);
+    }
+}
+
+/// Record and enqueue the weakrefs referring to the object at `pointer`. An
+/// unreadable weakref list is left out, as if the object had none.
+fn enqueue_weak_references<I, M>(mem: &M, pointer: Pointer, state: &mut State<I>)
+where
+    I: Interpreter,
+    M: Memory + ?Sized,
+{
+    let references = match pointer
+        .try_deref_me::<I::Object>(mem)
+        .and_then(|object| object.weak_references(mem))
+    {
+        Ok(references) if !references.is_empty() => references,
+        _ => return,
+    };
+    let pointers = references
+        .iter()
+        .map(|reference| DataPointer::from(reference.me()))
+        .collect();
+    for reference in references {
+        state.enqueue(reference);
+    }
+    state
+        .weak_references
+        .insert(DataPointer::from(pointer), pointers);
+}
+
+/// Enqueue an optional child object for decoding, returning its address.
+fn enqueue_optional<I: Interpreter>(
+    object: Option<I::Object>,
+    state: &mut State<I>,
+) -> Option<DataPointer> {
+    object.map(|object| {
+        let pointer = DataPointer::from(object.me());
+        state.enqueue(object);
+        pointer
+    })
+}
+
+/// Capacity hints taken from object headers are capped at this, so that a
+/// corrupt length cannot make the walker allocate for items that are not there.
+const MAX_CAPACITY_HINT: usize = 10_000;
+
+/// Record the items of a sequence, enqueueing them for decoding. A NULL item is
+/// recorded as `DataPointer(0)`. Any other error, such as an unreadable item
+/// table, fails the whole sequence rather than silently truncating it. `len` is
+/// the expected number of items, as a capacity hint.
+fn collect_items<I: Interpreter>(
+    items: impl Iterator<Item = Result<I::Object>>,
+    len: usize,
+    state: &mut State<I>,
+) -> Result<Vec<DataPointer>> {
+    let mut objects = Vec::with_capacity(len.min(MAX_CAPACITY_HINT));
+
+    for item in items {
+        match item {
+            Ok(item) => objects.push(Some(item)),
+            Err(Error::NullPointer) => objects.push(None),
+            Err(error) => return Err(error),
+        }
+    }
+
+    Ok(objects
+        .into_iter()
+        .map(|object| match object {
+            Some(object) => {
+                let pointer = DataPointer::from(object.me());
+                state.enqueue(object);
+                pointer
+            }
+            None => {
+                state.found_null = true;
+                DataPointer(0)
+            }
+        })
+        .collect())
+}
+
+pub fn walk<I, M>(mem: &M, pointer: Pointer) -> HashMap<DataPointer, DecodedData>
+where
+    I: Interpreter,
+    M: Memory + ?Sized,
+{
+    walk_with_options::<I, M>(mem, pointer, &WalkOptions::default(), None).graph
+}
+
+/// The number of decoded objects between calls to the progress callback of
+/// [`walk_with_options`].
+pub const PROGRESS_INTERVAL: usize = 1000;
+
+/// Walk the graph reachable from `pointer`.
+///
+/// If given, `progress` is called with the number of objects decoded and the
+/// number still queued every [`PROGRESS_INTERVAL`] decoded objects, and once
+/// more when the walk finishes.
+pub fn walk_with_options<I, M>(
+    mem: &M,
+    pointer: Pointer,
+    options: &WalkOptions,
+    progress: Option<&mut dyn FnMut(usize, usize)>,
+) -> WalkOutput
+where
+    I: Interpreter,
+    M: Memory + ?Sized,
+{
+    walk_in_order::<I, M>(mem, pointer, options, progress).0
+}
+
+/// Walk the graphs reachable from each of `roots` concurrently, one root per
+/// task, and merge them into a single output. Objects reachable from several
+/// roots are decoded by each of their walks, but appear once in the output.
+///
+/// Each walk reads `mem` on its own thread, which is safe for any `Sync`
+/// memory such as a shared [`crate::memory::Process`]. The target is not
+/// paused; with `WalkOptions::retry_suspended`, walks share one pause, which
+/// lasts until the last walk retrying under it is done.
+#[cfg(feature = "rayon")]
+pub fn walk_roots_parallel<I, M>(mem: &M, roots: &[Pointer], options: &WalkOptions) -> WalkOutput
+where
+    I: Interpreter,
+    M: Memory + Sync + ?Sized,
+{
+    use rayon::prelude::*;
+
+    roots
+        .par_iter()
+        .map(|&root| walk_with_options::<I, M>(mem, root, options, None))
+        .reduce(WalkOutput::default, |mut merged, output| {
+            for (pointer, data) in output.graph {
+                merged.graph.entry(pointer).or_insert(data);
+            }
+            for (pointer, raw) in output.raw {
+                merged.raw.entry(pointer).or_insert(raw);
+            }
+            merged.truncated_by_bytes |= output.truncated_by_bytes;
+            merged.suspicious.extend(output.suspicious);
+            merged.type_dicts.extend(output.type_dicts);
+            merged.metatypes.extend(output.metatypes);
+            merged.weak_references.extend(output.weak_references);
+            for (pointer, depth) in output.depths {
+                let merged_depth = merged.depths.entry(pointer).or_insert(depth);
+                *merged_depth = depth.min(*merged_depth);
+            }
+            merged.truncated_values.extend(output.truncated_values);
+            for error in output.errors {
+                if !merged.errors.iter().any(|e| e.pointer == error.pointer) {
+                    merged.errors.push(error);
+                }
+            }
+            #[cfg(feature = "profiling")]
+            merged.timings.merge(output.timings);
+            merged
+        })
+}
+
+/// Like [`walk`], but returns the decoded objects in BFS discovery order,
+/// starting with the root. Each object is directly followed by its type, if the
+/// type was not seen before.
+pub fn walk_ordered<I, M>(mem: &M, pointer: Pointer) -> Vec<(DataPointer, DecodedData)>
+where
+    I: Interpreter,
+    M: Memory + ?Sized,
+{
+    let (WalkOutput { mut graph, .. }, order) =
+        walk_in_order::<I, M>(mem, pointer, &WalkOptions::default(), None);
+    order
+        .into_iter()
+        .filter_map(|pointer| graph.remove(&pointer).map(|data| (pointer, data)))
+        .collect()
+}
+
+fn walk_in_order<I, M>(
+    mem: &M,
+    pointer: Pointer,
+    options: &WalkOptions,
+    mut progress: Option<&mut dyn FnMut(usize, usize)>,
+) -> (WalkOutput, Vec<DataPointer>)
+where
+    I: Interpreter,
+    M: Memory + ?Sized,
+{
+    let mut graph: HashMap<DataPointer, DecodedData> = HashMap::new();
+    let mut order: Vec<DataPointer> = Vec::new();
+    let mut raw: HashMap<DataPointer, Vec<u8>> = HashMap::new();
+    let mut cache = TypeCache::new();
+    let mut state = State::<I>::new(options, &mut cache);
+    let mut next_report = PROGRESS_INTERVAL;
+    let mut truncated_by_bytes = false;
+    let mut errors: Vec<WalkError> = Vec::new();
+    // Objects in `errors`, which are not in the graph to mark them as seen.
+    let mut failed: HashSet<DataPointer> = HashSet::new();
+    let mem = &match options.max_total_bytes {
+        Some(max_total_bytes) => CountingMemory::with_budget(mem, max_total_bytes),
+        None => CountingMemory::new(mem),
+    };
+    #[cfg(feature = "profiling")]
+    let mut timings = DecodeTimings::default();
+
+    if let Ok(object) = pointer.try_deref_me(mem) {
+        state.enqueue(object);
+        if state.options.record_depth {
+            state.depths.insert(DataPointer::from(pointer), 0);
+        }
+    }
+
+    while let Some(object) = match state.options.order {
+        TraversalOrder::Bfs => state.queue.pop_front(),
+        TraversalOrder::Dfs => state.queue.pop_back(),
+    } {
+        let pointer = DataPointer::from(object.me());
+        if graph.contains_key(&pointer) || failed.contains(&pointer) {
+            continue;
+        }
+
+        if mem.budget_exceeded() {
+            truncated_by_bytes = true;
+            break;
+        }
+
+        if let Some(progress) = progress.as_mut() {
+            if graph.len() >= next_report {
+                progress(graph.len(), state.queue.len());
+                next_report = graph.len() + PROGRESS_INTERVAL;
+            }
+        }
+
+        let me = object.me();
+        state.depth = state.depths.get(&pointer).copied().unwrap_or(0);
+        #[cfg(feature = "profiling")]
+        let started = std::time::Instant::now();
+        let decoded = match state.decoded_keys.remove(&me.address()) {
+            Some(decoded) => Ok(decoded),
+            None => step_retrying::<I, _>(mem, object, &mut state),
+        };
+        #[cfg(feature = "profiling")]
+        if let Some(object_type) = decoded.as_ref().ok().and_then(|d| d.object_data.kind()) {
+            timings.record(object_type, started.elapsed());
+        }
+        // The object may be incomplete, or have failed, for want of budget.
+        if mem.budget_exceeded() {
+            truncated_by_bytes = true;
+            break;
+        }
+        match decoded {
             Ok(Decoded {
                 object_data,
-                type_object_data,
                 type_object_pointer,
+                raw: object_raw,
             }) => {
-                graph.insert(type_object_pointer, type_object_data);
-                graph.insert(DataPointer(address), object_data);
+                order.push(pointer);
+                graph.insert(pointer, object_data);
+                if state.options.follow_weak_references {
+                    enqueue_weak_references::<I, _>(mem, me, &mut state);
+                }
+                if let Some(type_object_pointer) = type_object_pointer {
+                    if let Entry::Vacant(entry) = graph.entry(type_object_pointer) {
+                        // `step` memoized the type before decoding the object.
+                        let type_object = &state.cache.types[&type_object_pointer.0];
+                        let metatype = type_object.to_var_object().to_object().ob_type_pointer();
+                        order.push(type_object_pointer);
+                        entry.insert(DecodedData::Type(type_object.name().to_owned()));
+                        if state.options.record_depth {
+                            state
+                                .depths
+                                .entry(type_object_pointer)
+                                .or_insert(state.depth + 1);
+                        }
+                        if let Some(dict) = type_dict::<I, _>(mem, type_object, state.options) {
+                            enqueue_type_dict(type_object_pointer, dict, &mut state);
+                        }
+                        enqueue_metatype::<I, _>(mem, type_object_pointer, metatype, &mut state);
+                    }
+                }
+                if let Some(object_raw) = object_raw {
+                    raw.insert(pointer, object_raw);
+                }
+            }
+            Err(error) if state.options.errors_sidechannel => {
+                failed.insert(pointer);
+                errors.push(WalkError { pointer, error });
             }
             Err(error) => {
-                graph.insert(DataPointer(address), DecodedData::Error(error));
+                order.push(pointer);
+                graph.insert(pointer, DecodedData::Error(error));
+            }
+        };
+    }
+
+    // Objects still queued when the walk stopped, or that failed to decode,
+    // are not in the graph.
+    state
+        .depths
+        .retain(|pointer, _| graph.contains_key(pointer));
+
+    if state.found_null {
+        order.push(DataPointer(0));
+        match state.options.null_policy {
+            NullPolicy::AsError if state.options.errors_sidechannel => {
+                errors.push(WalkError {
+                    pointer: DataPointer(0),
+                    error: Error::NullPointer,
+                });
             }
+            NullPolicy::AsError => {
+                graph.insert(DataPointer(0), DecodedData::Error(Error::NullPointer));
+            }
+            NullPolicy::AsNone => {
+                graph.insert(DataPointer(0), DecodedData::None);
+            }
+            NullPolicy::Skip => {}
+        }
+    }
+
+    if let Some(progress) = progress {
+        progress(graph.len(), 0);
+    }
+
+    (
+        WalkOutput {
+            graph,
+            raw,
+            truncated_by_bytes,
+            suspicious: state.suspicious,
+            type_dicts: state.type_dicts,
+            metatypes: state.metatypes,
+            weak_references: state.weak_references,
+            depths: state.depths,
+            truncated_values: state.truncated_values,
+            errors,
+            #[cfg(feature = "profiling")]
+            timings,
+        },
+        order,
+    )
+}
+
+/// Look up the members of a `DecodedData::Set` node in `graph`, e.g. to
+/// collect a set of scalars into a Rust collection. Members that are
+/// containers themselves still refer to their children by pointer. Members
+/// missing from the graph are skipped, and other nodes resolve to no members.
+pub fn resolve_set<'g>(
+    graph: &'g HashMap<DataPointer, DecodedData>,
+    set: &DecodedData,
+) -> Vec<&'g DecodedData> {
+    match set {
+        DecodedData::Set { members, .. } => members
+            .iter()
+            .filter_map(|member| graph.get(member))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// A step in a path passed to [`resolve_path`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PathSegment {
+    /// An attribute in the object's `__dict__`.
+    Attr(String),
+    /// An item of a list or tuple.
+    Index(usize),
+    /// The value of a string key in a dict.
+    Key(String),
+}
+
+/// Find the value of the string key `name` in `dict`.
+fn lookup_str_key<I, M>(
+    mem: &M,
+    dict: I::DictObject,
+    name: &str,
+    cache: &mut TypeCache<I>,
+) -> Result<Option<I::Object>>
+where
+    I: Interpreter,
+    M: Memory + ?Sized,
+{
+    let (entries, _) = dict.entries(mem, DEFAULT_MAX_DICT_SLOTS)?;
+    for (_hash, key, value) in entries.into_iter().map(|entry| entry.take()) {
+        let key = cache.type_object(mem, &key)?.downcast(mem, key)?;
+        let key = match key.object_type() {
+            Type::String => key.as_string().unwrap().read(mem)?,
+            Type::Unicode => key.as_unicode().unwrap().read(mem)?,
+            _ => continue,
         };
+        if key == name {
+            return Ok(Some(value));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Decode the object at `pointer` without decoding its children.
+pub fn decode_one<I, M>(mem: &M, pointer: Pointer, cache: &mut TypeCache<I>) -> Result<DecodedData>
+where
+    I: Interpreter,
+    M: Memory + ?Sized,
+{
+    if let Some(&(singleton, _)) = cache.singletons.get(&pointer.address()) {
+        return Ok(singleton.data());
     }
+    let object: I::Object = pointer.try_deref_me(mem)?;
+    let options = WalkOptions::default();
+    Ok(step::<I, M>(mem, object, &mut State::new(&options, cache))?.object_data)
+}
 
-    graph
+/// The layout fields of a type object, for diagnosing mismatches between the
+/// bindings and the target's CPython build.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TypeLayout {
+    pub name: String,
+    /// See `TypeObject::name_truncated`.
+    pub name_truncated: bool,
+    pub tp_basicsize: isize,
+    pub tp_itemsize: isize,
+    pub tp_dictoffset: isize,
+    pub is_heap_type: bool,
+}
+
+/// Read the layout of the type object at `type_ptr`, without decoding any of
+/// its instances.
+pub fn inspect_type<I, M>(mem: &M, type_ptr: Pointer) -> Result<TypeLayout>
+where
+    I: Interpreter,
+    M: Memory + ?Sized,
+{
+    let type_object: I::TypeObject = type_ptr.try_deref_me(mem)?;
+    Ok(TypeLayout {
+        name: type_object.name().to_owned(),
+        name_truncated: type_object.name_truncated(),
+        tp_basicsize: type_object.tp_basicsize(),
+        tp_itemsize: type_object.tp_itemsize(),
+        tp_dictoffset: type_object.tp_dictoffset(),
+        is_heap_type: type_object.is_heap_type(),
+    })
+}
+
+/// Follow `path` from the object at `root`, reading only the objects along the
+/// path, and decode the object it ends at. Its children are not decoded.
+///
+/// Returns `Error::Decode` if a segment does not apply to the object it is
+/// applied to, or names an attribute, index or key that does not exist.
+pub fn resolve_path<I, M>(
+    mem: &M,
+    root: Pointer,
+    path: &[PathSegment],
+    cache: &mut TypeCache<I>,
+) -> Result<DecodedData>
+where
+    I: Interpreter,
+    M: Memory + ?Sized,
+{
+    let mut object: I::Object = root.try_deref_me(mem)?;
+
+    for segment in path {
+        let next = match segment {
+            PathSegment::Attr(name) => match object.attributes(mem)? {
+                Some(dict) => lookup_str_key::<I, M>(mem, dict, name, cache)?,
+                None => None,
+            },
+            PathSegment::Index(index) => {
+                let typed = cache.type_object(mem, &object)?.downcast(mem, object)?;
+                match typed.object_type() {
+                    Type::List => typed.as_list().unwrap().items(mem).nth(*index),
+                    Type::Tuple => typed.as_tuple().unwrap().items(mem).nth(*index),
+                    _ => None,
+                }
+                .transpose()?
+            }
+            PathSegment::Key(key) => {
+                let typed = cache.type_object(mem, &object)?.downcast(mem, object)?;
+                match typed.object_type() {
+                    Type::Dict => {
+                        lookup_str_key::<I, M>(mem, typed.as_dict().unwrap(), key, cache)?
+                    }
+                    _ => None,
+                }
+            }
+        };
+        object = next.ok_or(Error::Decode)?;
+    }
+
+    let options = WalkOptions::default();
+    Ok(step::<I, M>(mem, object, &mut State::new(&options, cache))?.object_data)
+}
+
+/// Walk from `root` and collect the value of every reachable `str` and
+/// `unicode` object, including those of instances of their subclasses, ordered
+/// by address.
+pub fn all_strings<I, M>(mem: &M, root: Pointer) -> Vec<(DataPointer, String)>
+where
+    I: Interpreter,
+    M: Memory + ?Sized,
+{
+    let mut strings: Vec<(DataPointer, String)> = walk::<I, M>(mem, root)
+        .into_iter()
+        .filter_map(|(pointer, data)| match data {
+            DecodedData::String(value) => Some((pointer, value)),
+            DecodedData::Object {
+                base_value: Some(base_value),
+                ..
+            } => match *base_value {
+                DecodedData::String(value) => Some((pointer, value)),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+    strings.sort_unstable_by_key(|&(pointer, _)| pointer.0);
+    strings
+}
+
+/// The entries of `sys.path`, given the `sys` module's dict (e.g. found with
+/// [`resolve_path`] from `sys.modules`, or as the module's `__dict__`).
+/// Entries that are not strings are skipped.
+pub fn sys_path<I, M>(mem: &M, sys_module_dict: Pointer) -> Result<Vec<String>>
+where
+    I: Interpreter,
+    M: Memory + ?Sized,
+{
+    let mut cache = TypeCache::<I>::new();
+    let dict: I::Object = sys_module_dict.try_deref_me(mem)?;
+    let dict = cache.type_object(mem, &dict)?.downcast(mem, dict)?;
+    let dict = dict.as_dict().ok_or(Error::Decode)?;
+
+    let path = lookup_str_key::<I, M>(mem, dict, "path", &mut cache)?.ok_or(Error::Decode)?;
+    let path = cache.type_object(mem, &path)?.downcast(mem, path)?;
+    let path = path.as_list().ok_or(Error::Decode)?;
+
+    let mut entries = Vec::new();
+    for item in path.items(mem) {
+        let item = item?;
+        let item = cache.type_object(mem, &item)?.downcast(mem, item)?;
+        match item.object_type() {
+            Type::String => entries.push(item.as_string().unwrap().read(mem)?),
+            Type::Unicode => entries.push(item.as_unicode().unwrap().read(mem)?),
+            _ => {}
+        }
+    }
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use memoffset::offset_of;
+
+    use super::*;
+    use crate::cpython27::{bindings, Cpython2_7};
+    use crate::testing::{spawn_python27, FakeHeap};
+
+    #[test]
+    #[cfg(feature = "profiling")]
+    fn decode_timings() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27("entry = {'key': ['value', 1, 2.5]}")?;
+        let mem = crate::connect(child.pid)?;
+
+        let output = walk_with_options::<Cpython2_7, _>(
+            &mem,
+            Pointer::new(child.pointer),
+            &WalkOptions::default(),
+            None,
+        );
+        let timings = &output.timings;
+        for object_type in [Type::Dict, Type::List, Type::String, Type::Int, Type::Float] {
+            assert!(timings.counts[&object_type] >= 1, "{:?}", object_type);
+            assert!(
+                !timings.durations[&object_type].is_zero(),
+                "{:?}",
+                object_type
+            );
+        }
+        assert_eq!(timings.counts[&Type::String], 2);
+        assert!(timings.total() >= timings.durations[&Type::Dict]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn follow_type_dicts() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27(
+            r#"
+            class Limited(object):
+                limit = 42
+            entry = Limited()
+            "#,
+        )?;
+        let mem = crate::connect(child.pid)?;
+        let walk_type_dicts = |follow_type_dicts| {
+            let options = WalkOptions {
+                follow_type_dicts,
+                ..WalkOptions::default()
+            };
+            walk_with_options::<Cpython2_7, _>(&mem, Pointer::new(child.pointer), &options, None)
+        };
+
+        let output = walk_type_dicts(true);
+        let object_type = match output.graph.get(&DataPointer(child.pointer)) {
+            Some(DecodedData::Object { object_type, .. }) => *object_type,
+            node => anyhow::bail!("Expected an object, got {:?}", node),
+        };
+        let entries = match output.graph.get(&output.type_dicts[&object_type]) {
+            Some(DecodedData::Dict { entries, .. }) => entries,
+            node => anyhow::bail!("Expected a dict, got {:?}", node),
+        };
+        let limit = entries
+            .iter()
+            .find(|(key, _)| matches!(output.graph.get(key), Some(DecodedData::String(key)) if key == "limit"))
+            .map(|(_, value)| value);
+        assert!(matches!(
+            limit.and_then(|value| output.graph.get(value)),
+            Some(DecodedData::Int(limit)) if *limit == BigInt::from(42)
+        ));
+
+        assert!(walk_type_dicts(false).type_dicts.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn walk_roots_parallel() -> std::result::Result<(), anyhow::Error> {
+        // The roots share `shared`, and the first root contains the second.
+        let child = spawn_python27(
+            r#"
+            shared = {'shared': 1.5}
+            roots = [[shared, 'first'], ('second', shared), set(['third']), u'fourth']
+            roots.insert(0, [roots[0], 'zeroth'])
+            entry = roots
+            "#,
+        )?;
+        let mem = crate::connect(child.pid)?;
+        let roots = match walk::<Cpython2_7, _>(&mem, Pointer::new(child.pointer))
+            .remove(&DataPointer(child.pointer))
+        {
+            Some(DecodedData::List(items)) => items,
+            node => anyhow::bail!("Expected a list, got {:?}", node),
+        };
+        let roots: Vec<Pointer> = roots.iter().map(|root| Pointer::new(root.0)).collect();
+
+        let mut sequential = HashMap::new();
+        for root in &roots {
+            for (pointer, data) in walk::<Cpython2_7, _>(&mem, *root) {
+                sequential.entry(pointer).or_insert(data);
+            }
+        }
+        let parallel =
+            super::walk_roots_parallel::<Cpython2_7, _>(&mem, &roots, &WalkOptions::default());
+
+        let summary = |graph: &HashMap<DataPointer, DecodedData>| {
+            let mut nodes: Vec<_> = graph
+                .iter()
+                .map(|(pointer, data)| (pointer.0, crate::profile::node_type_name(data).to_owned()))
+                .collect();
+            nodes.sort();
+            nodes
+        };
+        assert_eq!(summary(&parallel.graph), summary(&sequential));
+        for root in &roots {
+            assert!(parallel.graph.contains_key(&DataPointer::from(*root)));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn max_value_len() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27("entry = ['x' * 1000000, u'\\u2603' * 5000, 'short']")?;
+        let mem = crate::connect(child.pid)?;
+        let options = WalkOptions {
+            max_value_len: Some(16),
+            ..WalkOptions::default()
+        };
+
+        let output =
+            walk_with_options::<Cpython2_7, _>(&mem, Pointer::new(child.pointer), &options, None);
+        let items = match output.graph.get(&DataPointer(child.pointer)) {
+            Some(DecodedData::List(items)) => items,
+            node => anyhow::bail!("Expected a list, got {:?}", node),
+        };
+        assert!(matches!(
+            output.graph.get(&items[0]),
+            Some(DecodedData::String(string)) if *string == "x".repeat(16)
+        ));
+        assert!(matches!(
+            output.graph.get(&items[1]),
+            Some(DecodedData::String(string)) if *string == "\u{2603}".repeat(16)
+        ));
+        assert!(matches!(
+            output.graph.get(&items[2]),
+            Some(DecodedData::String(string)) if string == "short"
+        ));
+        assert_eq!(
+            output.truncated_values,
+            HashMap::from([(items[0], 1000000), (items[1], 5000)])
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn diamond() -> std::result::Result<(), anyhow::Error> {
+        // Both branches refer to the same large array, which is queued by
+        // whichever is decoded first and must not be read again for the other.
+        const LEN: usize = 50_000;
+        let child = spawn_python27(&format!(
+            "import array\nshared = array.array('d', [0.0] * {})\nentry = ((shared,), (shared,))",
+            LEN
+        ))?;
+        let process = crate::connect(child.pid)?;
+
+        for order in [TraversalOrder::Bfs, TraversalOrder::Dfs] {
+            let mem = CountingMemory::new(&process);
+            let options = WalkOptions {
+                order,
+                ..Default::default()
+            };
+            let graph = walk_with_options::<Cpython2_7, _>(
+                &mem,
+                Pointer::new(child.pointer),
+                &options,
+                None,
+            )
+            .graph;
+
+            let branches = match graph.get(&DataPointer(child.pointer)) {
+                Some(DecodedData::Tuple(branches)) => branches,
+                node => anyhow::bail!("Expected a tuple, got {:?}", node),
+            };
+            assert_ne!(branches[0], branches[1]);
+            let shared: Vec<_> = branches
+                .iter()
+                .map(|branch| match graph.get(branch) {
+                    Some(DecodedData::Tuple(items)) => Ok(items[0]),
+                    node => anyhow::bail!("Expected a tuple, got {:?}", node),
+                })
+                .collect::<std::result::Result<_, _>>()?;
+            assert_eq!(shared[0], shared[1]);
+            match graph.get(&shared[0]) {
+                Some(DecodedData::Array { values, .. }) => assert_eq!(values.len(), LEN),
+                node => anyhow::bail!("Expected an array, got {:?}", node.map(DecodedData::kind)),
+            }
+            let buffer = LEN * std::mem::size_of::<f64>();
+            assert!(
+                mem.bytes_read() < 2 * buffer,
+                "{} bytes read",
+                mem.bytes_read()
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn singletons() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27("entry = [None] * 1000 + [True, False, True]")?;
+        let mem = CountingMemory::new(crate::connect(child.pid)?);
+
+        let graph = walk::<Cpython2_7, _>(&mem, Pointer::new(child.pointer));
+        let items = match graph.get(&DataPointer(child.pointer)) {
+            Some(DecodedData::List(items)) => items,
+            node => anyhow::bail!("Expected a list, got {:?}", node),
+        };
+        assert!(items[..1000]
+            .iter()
+            .all(|item| matches!(graph.get(item), Some(DecodedData::None))));
+        assert!(matches!(
+            graph.get(&items[1000]),
+            Some(DecodedData::Bool(true))
+        ));
+        assert!(matches!(
+            graph.get(&items[1001]),
+            Some(DecodedData::Bool(false))
+        ));
+
+        // Once seen, singletons are decoded without reading the target.
+        let mut cache = TypeCache::new();
+        for item in &items[999..] {
+            decode_one::<Cpython2_7, _>(&mem, Pointer::new(item.0), &mut cache)?;
+        }
+        let reads = mem.reads();
+        for item in &items[999..] {
+            let data = decode_one::<Cpython2_7, _>(&mem, Pointer::new(item.0), &mut cache)?;
+            assert!(matches!(data, DecodedData::None | DecodedData::Bool(_)));
+        }
+        assert_eq!(mem.reads(), reads);
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn singletons_from_symbols() -> Result<()> {
+        let symbols: Symbols = vec![
+            ("_Py_NoneStruct", 0x1000),
+            ("_Py_TrueStruct", 0x2000),
+            ("_Py_ZeroStruct", 0x3000),
+        ]
+        .into_iter()
+        .collect();
+        // None of the singletons are mapped, so decoding them must not read.
+        let mem = CountingMemory::new(crate::memory::SliceMemory::new(0x1000, &[]));
+
+        let mut cache = TypeCache::<Cpython2_7>::with_symbols(&symbols);
+        let mut decode =
+            |address| decode_one::<Cpython2_7, _>(&mem, Pointer::new(address), &mut cache);
+        assert!(matches!(decode(0x1000)?, DecodedData::None));
+        assert!(matches!(decode(0x2000)?, DecodedData::Bool(true)));
+        assert!(matches!(decode(0x3000)?, DecodedData::Bool(false)));
+        assert_eq!(mem.reads(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn group_scalars() -> std::result::Result<(), anyhow::Error> {
+        // `join` builds new, uninterned strings with equal values.
+        let child = spawn_python27(
+            "entry = [''.join(['du', 'p']), ''.join(['d', 'up']), 'other', 1.5, float('nan')]",
+        )?;
+        let mem = crate::connect(child.pid)?;
+        let graph = walk::<Cpython2_7, _>(&mem, Pointer::new(child.pointer));
+
+        let mut index: HashMap<ScalarValue, Vec<DataPointer>> = HashMap::new();
+        for (pointer, data) in &graph {
+            if let Some(value) = data.scalar() {
+                index.entry(value).or_default().push(*pointer);
+            }
+        }
+
+        assert_eq!(index[&ScalarValue::String("dup".into())].len(), 2);
+        assert_eq!(index[&ScalarValue::String("other".into())].len(), 1);
+        assert!(index.contains_key(&ScalarValue::Float(1.5f64.to_bits())));
+        assert!(index.keys().any(
+            |value| matches!(value, ScalarValue::Float(bits) if f64::from_bits(*bits).is_nan())
+        ));
+        assert!(graph[&DataPointer(child.pointer)].scalar().is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn accessors() {
+        let entries = HashMap::from([(DataPointer(1), DataPointer(2))]);
+        let nodes = [
+            DecodedData::String("text".into()),
+            DecodedData::Bytes(b"raw".to_vec()),
+            DecodedData::Bool(true),
+            DecodedData::Int(BigInt::from(42)),
+            DecodedData::Float(1.5),
+            DecodedData::Tuple(vec![DataPointer(1)]),
+            DecodedData::List(vec![DataPointer(2)]),
+            DecodedData::Set {
+                members: vec![DataPointer(3)],
+                partial: false,
+            },
+            DecodedData::Dict {
+                entries: entries.clone(),
+                partial: false,
+            },
+            DecodedData::Error(Error::NullPointer),
+        ];
+        // Each accessor matches the node at its own index.
+        let accessors: [fn(&DecodedData) -> bool; 10] = [
+            |data| data.as_str() == Some("text"),
+            |data| data.as_bytes() == Some(&b"raw"[..]),
+            |data| data.as_bool() == Some(true),
+            |data| data.as_int() == Some(&BigInt::from(42)),
+            |data| data.as_float() == Some(1.5),
+            |data| data.as_tuple() == Some(&[DataPointer(1)][..]),
+            |data| data.as_list() == Some(&[DataPointer(2)][..]),
+            |data| data.as_set() == Some(&[DataPointer(3)][..]),
+            |data| data.as_dict().map(HashMap::len) == Some(1),
+            |data| matches!(data.as_error(), Some(Error::NullPointer)),
+        ];
+        for (matching, accessor) in accessors.iter().enumerate() {
+            for (index, node) in nodes.iter().enumerate() {
+                assert_eq!(accessor(node), index == matching, "{:?}", node);
+            }
+        }
+
+        let default_dict = DecodedData::DefaultDict {
+            entries,
+            partial: false,
+            default_factory: None,
+        };
+        assert_eq!(default_dict.as_dict().map(HashMap::len), Some(1));
+        assert!(DecodedData::OrderedDict(Vec::new()).as_dict().is_none());
+        assert!(DecodedData::None.as_str().is_none());
+    }
+
+    #[test]
+    fn decode_types() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27(
+            r#"
+            class Old:
+                pass
+            entry = Old()
+            entry.name = 'kept'
+            entry.table = {'hidden key': 'hidden value'}
+            entry.items = ['hidden item']
+            "#,
+        )?;
+        let mem = crate::connect(child.pid)?;
+        let options = WalkOptions {
+            decode_types: Some(HashSet::from([Type::String, Type::Instance])),
+            ..WalkOptions::default()
+        };
+        let graph =
+            walk_with_options::<Cpython2_7, _>(&mem, Pointer::new(child.pointer), &options, None)
+                .graph;
+
+        let attributes = match graph.get(&DataPointer(child.pointer)) {
+            Some(DecodedData::Instance { attributes, .. }) => attributes,
+            node => anyhow::bail!("Expected an instance, got {:?}", node),
+        };
+        assert!(matches!(
+            graph.get(&attributes["name"]),
+            Some(DecodedData::String(name)) if name == "kept"
+        ));
+        assert!(matches!(
+            graph.get(&attributes["table"]),
+            Some(DecodedData::Skipped(type_name)) if type_name == "dict"
+        ));
+        assert!(matches!(
+            graph.get(&attributes["items"]),
+            Some(DecodedData::Skipped(type_name)) if type_name == "list"
+        ));
+        assert!(!graph.values().any(|data| matches!(
+            data,
+            DecodedData::String(string) if string.starts_with("hidden")
+        )));
+
+        Ok(())
+    }
+
+    #[test]
+    fn all_strings() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27(
+            r#"
+            class Tagged(str):
+                pass
+            shared = ''.join(['sha', 'red'])
+            entry = {
+                'planted key': [shared, u'planted \u2603', shared],
+                'nested': (Tagged('planted subclass'), 42),
+            }
+            "#,
+        )?;
+        let mem = crate::connect(child.pid)?;
+
+        let strings = super::all_strings::<Cpython2_7, _>(&mem, Pointer::new(child.pointer));
+        let values: Vec<&str> = strings.iter().map(|(_, value)| value.as_str()).collect();
+        for planted in [
+            "planted key",
+            "nested",
+            "shared",
+            "planted \u{2603}",
+            "planted subclass",
+        ] {
+            assert!(values.contains(&planted), "{} missing", planted);
+        }
+        // `shared` is listed twice but is one object.
+        assert_eq!(values.iter().filter(|&&value| value == "shared").count(), 1);
+        assert!(strings.windows(2).all(|pair| pair[0].0 .0 < pair[1].0 .0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn sys_path() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27(
+            r#"
+            sys.path.append('/walker/str')
+            sys.path.append(u'/walker/unicode')
+            sys.path.append(None)
+            entry = sys.__dict__
+            "#,
+        )?;
+        let mem = crate::connect(child.pid)?;
+
+        let path = super::sys_path::<Cpython2_7, _>(&mem, Pointer::new(child.pointer))?;
+        // The script directory, which is empty for `python -c`.
+        assert_eq!(path.first().map(String::as_str), Some(""));
+        assert_eq!(path[path.len() - 2..], ["/walker/str", "/walker/unicode"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn null_policy() -> std::result::Result<(), anyhow::Error> {
+        // A list whose first slot is NULL, as produced by `PyList_New`.
+        let child = spawn_python27(
+            r#"
+            import ctypes
+            new = ctypes.pythonapi.PyList_New
+            new.argtypes = [ctypes.c_ssize_t]
+            new.restype = ctypes.py_object
+            entry = new(1)
+            entry.append('not null')
+            "#,
+        )?;
+        let mem = crate::connect(child.pid)?;
+
+        for null_policy in [NullPolicy::AsError, NullPolicy::AsNone, NullPolicy::Skip] {
+            let options = WalkOptions {
+                null_policy,
+                ..WalkOptions::default()
+            };
+            let graph = walk_with_options::<Cpython2_7, _>(
+                &mem,
+                Pointer::new(child.pointer),
+                &options,
+                None,
+            )
+            .graph;
+
+            match graph.get(&DataPointer(child.pointer)) {
+                Some(DecodedData::List(items)) => {
+                    assert_eq!(items.len(), 2);
+                    assert_eq!(items[0], DataPointer(0));
+                    assert!(matches!(graph.get(&items[1]), Some(DecodedData::String(_))));
+                }
+                _ => anyhow::bail!("Expected a list"),
+            }
+
+            match (null_policy, graph.get(&DataPointer(0))) {
+                (NullPolicy::AsError, Some(DecodedData::Error(Error::NullPointer))) => {}
+                (NullPolicy::AsNone, Some(DecodedData::None)) => {}
+                (NullPolicy::Skip, None) => {}
+                (_, node) => anyhow::bail!("Unexpected NULL node {:?}", node),
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn capture_raw() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27("entry = 123456")?;
+        let mem = crate::connect(child.pid)?;
+
+        let options = WalkOptions {
+            capture_raw: true,
+            ..WalkOptions::default()
+        };
+        let output =
+            walk_with_options::<Cpython2_7, _>(&mem, Pointer::new(child.pointer), &options, None);
+
+        let size = std::mem::size_of::<python27_sys::PyIntObject>();
+        assert_eq!(
+            output.raw.get(&DataPointer(child.pointer)),
+            Some(&mem.get_vec(child.pointer, size)?)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn progress() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27("entry = [str(i) for i in range(5000)]")?;
+        let mem = crate::connect(child.pid)?;
+
+        let mut calls: Vec<(usize, usize)> = Vec::new();
+        let mut record = |decoded, queued| calls.push((decoded, queued));
+        let output = walk_with_options::<Cpython2_7, _>(
+            &mem,
+            Pointer::new(child.pointer),
+            &WalkOptions::default(),
+            Some(&mut record),
+        );
+
+        assert!(calls.len() >= 5);
+        assert!(calls.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        assert!(calls[..calls.len() - 1]
+            .iter()
+            .all(|&(_, queued)| queued > 0));
+        assert_eq!(calls.last(), Some(&(output.graph.len(), 0)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn non_finite_floats() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27("entry = [float('inf'), float('-inf'), float('nan')]")?;
+        let mem = crate::connect(child.pid)?;
+
+        let graph = walk::<Cpython2_7, _>(&mem, Pointer::new(child.pointer));
+        let floats: Vec<f64> = match graph.get(&DataPointer(child.pointer)) {
+            Some(DecodedData::List(items)) => items
+                .iter()
+                .filter_map(|item| match graph.get(item) {
+                    Some(DecodedData::Float(float)) => Some(*float),
+                    _ => None,
+                })
+                .collect(),
+            _ => anyhow::bail!("Expected a list"),
+        };
+
+        assert_eq!(floats.len(), 3);
+        assert_eq!(floats[0], f64::INFINITY);
+        assert_eq!(floats[1], f64::NEG_INFINITY);
+        assert!(floats[2].is_nan());
+
+        Ok(())
+    }
+
+    #[test]
+    fn key_filter() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27(
+            r#"
+            class Holder(object):
+                pass
+            holder = Holder()
+            holder.keep = 'kept attribute'
+            holder.skip_me = ['skipped attribute']
+            entry = {'keep': ['kept value'], 'skip_me': ['skipped value'], 'holder': holder}
+            "#,
+        )?;
+        let mem = crate::connect(child.pid)?;
+
+        let options = WalkOptions {
+            key_filter: Some(Arc::new(|key: &str| key != "skip_me")),
+            ..WalkOptions::default()
+        };
+        let graph =
+            walk_with_options::<Cpython2_7, _>(&mem, Pointer::new(child.pointer), &options, None)
+                .graph;
+
+        let strings: Vec<&str> = graph
+            .values()
+            .filter_map(|data| match data {
+                DecodedData::String(str) => Some(str.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert!(strings.contains(&"kept value"));
+        assert!(strings.contains(&"kept attribute"));
+        assert!(!strings.contains(&"skipped value"));
+        assert!(!strings.contains(&"skipped attribute"));
+
+        // The skipped values are still recorded, just not walked.
+        let entries = match graph.get(&DataPointer(child.pointer)) {
+            Some(DecodedData::Dict { entries, .. }) => entries,
+            _ => anyhow::bail!("Expected a dict"),
+        };
+        let skipped = entries
+            .iter()
+            .find(|(key, _)| matches!(graph.get(key), Some(DecodedData::String(key)) if key == "skip_me"))
+            .map(|(_, value)| *value)
+            .ok_or_else(|| anyhow::anyhow!("Expected the skip_me key"))?;
+        assert!(!graph.contains_key(&skipped));
+
+        Ok(())
+    }
+
+    #[test]
+    fn key_filter_decodes_keys_once() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27("entry = dict(('key%d' % i, i * 1000) for i in range(50))")?;
+        let mem = crate::connect(child.pid)?;
+
+        let reads = |key_filter: Option<KeyFilter>| {
+            let counting = CountingMemory::new(&mem);
+            let options = WalkOptions {
+                key_filter,
+                ..WalkOptions::default()
+            };
+            let output = walk_with_options::<Cpython2_7, _>(
+                &counting,
+                Pointer::new(child.pointer),
+                &options,
+                None,
+            );
+            (output.graph.len(), counting.reads())
+        };
+
+        // Testing the keys against the filter reads nothing the walk would not
+        // read anyway, and still walks every value.
+        assert_eq!(reads(Some(Arc::new(|_: &str| true))), reads(None));
+
+        Ok(())
+    }
+
+    #[test]
+    fn errors_sidechannel() -> std::result::Result<(), anyhow::Error> {
+        let mut heap = FakeHeap::new(0x1000, 0x1000);
+        let tuple_type_address = 0x1100;
+        let float_type_address = 0x1300;
+        let tuple_address = 0x1800;
+        let float_address = 0x1900;
+        let unmapped_type_object = 0x1a00;
+        let negative_size_tuple = 0x1b00;
+
+        heap.write_type(tuple_type_address, "tuple");
+        heap.write_type(float_type_address, "float");
+        heap.write(
+            tuple_address,
+            bindings::PyTupleObject {
+                ob_refcnt: 1,
+                ob_type: tuple_type_address as *mut _,
+                ob_size: 4,
+                ob_item: [float_address as *mut _],
+            },
+        );
+        heap.write(
+            tuple_address + std::mem::size_of::<bindings::PyTupleObject>(),
+            [unmapped_type_object, negative_size_tuple, 0],
+        );
+        heap.write(
+            float_address,
+            bindings::PyFloatObject {
+                ob_refcnt: 1,
+                ob_type: float_type_address as *mut _,
+                ob_fval: 1.5,
+            },
+        );
+        heap.write(
+            unmapped_type_object,
+            bindings::PyObject {
+                ob_refcnt: 1,
+                ob_type: 0x9000 as *mut _,
+            },
+        );
+        heap.write(
+            negative_size_tuple,
+            bindings::PyTupleObject {
+                ob_refcnt: 1,
+                ob_type: tuple_type_address as *mut _,
+                ob_size: -1,
+                ob_item: [std::ptr::null_mut()],
+            },
+        );
+        let mem = heap.memory();
+
+        let options = WalkOptions {
+            errors_sidechannel: true,
+            ..WalkOptions::default()
+        };
+        let output =
+            walk_with_options::<Cpython2_7, _>(&mem, Pointer::new(tuple_address), &options, None);
+
+        match output.graph.get(&DataPointer(tuple_address)) {
+            Some(DecodedData::Tuple(items)) => assert_eq!(
+                items,
+                &[
+                    DataPointer(float_address),
+                    DataPointer(unmapped_type_object),
+                    DataPointer(negative_size_tuple),
+                    DataPointer(0),
+                ]
+            ),
+            node => anyhow::bail!("Expected a tuple, got {:?}", node),
+        }
+        assert!(matches!(
+            output.graph.get(&DataPointer(float_address)),
+            Some(DecodedData::Float(value)) if *value == 1.5
+        ));
+        assert!(!output
+            .graph
+            .values()
+            .any(|data| matches!(data, DecodedData::Error(_))));
+
+        let failed: Vec<_> = output.errors.iter().map(|error| error.pointer).collect();
+        assert_eq!(
+            failed,
+            [
+                DataPointer(unmapped_type_object),
+                DataPointer(negative_size_tuple),
+                DataPointer(0),
+            ]
+        );
+        assert!(matches!(output.errors[2].error, Error::NullPointer));
+
+        // Without the option, the same failures are error nodes.
+        let output = walk_with_options::<Cpython2_7, _>(
+            &mem,
+            Pointer::new(tuple_address),
+            &WalkOptions::default(),
+            None,
+        );
+        assert!(output.errors.is_empty());
+        for pointer in failed {
+            assert!(matches!(
+                output.graph.get(&pointer),
+                Some(DecodedData::Error(_))
+            ));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn byte_budget() -> std::result::Result<(), anyhow::Error> {
+        const FLOATS: usize = 1000;
+        let mut heap = FakeHeap::new(0x1000, 0x10000 + FLOATS * 0x20);
+        let tuple_type_address = 0x1100;
+        let float_type_address = 0x1300;
+        let tuple_address = 0x2000;
+        let floats_address = 0x10000;
+
+        heap.write_type(tuple_type_address, "tuple");
+        heap.write_type(float_type_address, "float");
+        heap.write(
+            tuple_address,
+            bindings::PyTupleObject {
+                ob_refcnt: 1,
+                ob_type: tuple_type_address as *mut _,
+                ob_size: FLOATS as isize,
+                ..Default::default()
+            },
+        );
+        for idx in 0..FLOATS {
+            let float_address = floats_address + idx * 0x20;
+            heap.write(
+                tuple_address + offset_of!(bindings::PyTupleObject, ob_item) + idx * PY_SIZE_T,
+                float_address,
+            );
+            heap.write(
+                float_address,
+                bindings::PyFloatObject {
+                    ob_refcnt: 1,
+                    ob_type: float_type_address as *mut _,
+                    ob_fval: idx as f64,
+                },
+            );
+        }
+        let mem = heap.memory();
+
+        let walk_with_budget = |max_total_bytes| {
+            let counting = CountingMemory::new(&mem);
+            let options = WalkOptions {
+                max_total_bytes,
+                ..WalkOptions::default()
+            };
+            let output = walk_with_options::<Cpython2_7, _>(
+                &counting,
+                Pointer::new(tuple_address),
+                &options,
+                None,
+            );
+            (output, counting.bytes_read())
+        };
+
+        let (full, full_bytes) = walk_with_budget(None);
+        assert!(!full.truncated_by_bytes);
+        assert_eq!(full.graph.len(), FLOATS + 3);
+
+        // The budget runs out among the floats, after the tuple is complete.
+        let budget = full_bytes * 3 / 4;
+        let (truncated, bytes) = walk_with_budget(Some(budget));
+        assert!(truncated.truncated_by_bytes);
+        assert!(bytes <= budget);
+        assert!(truncated.graph.len() < full.graph.len());
+        assert!(matches!(
+            truncated.graph.get(&DataPointer(tuple_address)),
+            Some(DecodedData::Tuple(items)) if items.len() == FLOATS
+        ));
+
+        // The budget runs out within the tuple, which is then left out rather
+        // than read past the budget.
+        let (truncated, bytes) = walk_with_budget(Some(4096));
+        assert!(truncated.truncated_by_bytes);
+        assert!(bytes <= 4096);
+        assert!(!truncated.graph.contains_key(&DataPointer(tuple_address)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn data_pointer_conversions() {
+        for address in [0, 0x10, 0x7fff_ffff_f000] {
+            let pointer = Pointer::new(address);
+            let data_pointer = DataPointer::from(pointer);
+            assert_eq!(data_pointer, DataPointer(address));
+            assert_eq!(Pointer::from(data_pointer), pointer);
+            assert_eq!(DataPointer::from(address), data_pointer);
+            assert_eq!(usize::from(data_pointer), address);
+        }
+    }
+
+    #[test]
+    fn data_pointer_hex_round_trip() {
+        for address in [0, 0x10, 0xdead_beef, 0x7fff_ffff_f000, 0xffff_ffff_ffff] {
+            let pointer = DataPointer(address);
+            let hex = pointer.to_string();
+            assert!(hex.starts_with("0x"));
+            assert_eq!(hex.parse::<DataPointer>(), Ok(pointer));
+        }
+
+        assert_eq!(
+            "7fff0010".parse::<DataPointer>(),
+            Ok(DataPointer(0x7fff_0010))
+        );
+        assert!("0x".parse::<DataPointer>().is_err());
+        assert!("0xnope".parse::<DataPointer>().is_err());
+    }
+
+    #[test]
+    fn walk_ordered_is_bfs() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27("entry = ['first', ('second', 'third')]")?;
+        let mem = crate::connect(child.pid)?;
+
+        let nodes = walk_ordered::<Cpython2_7, _>(&mem, Pointer::new(child.pointer));
+        let strings: Vec<&str> = nodes
+            .iter()
+            .filter_map(|(_, data)| match data {
+                DecodedData::String(str) => Some(str.as_str()),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(nodes[0].0, DataPointer(child.pointer));
+        assert!(matches!(nodes[1].1, DecodedData::Type(ref name) if name == "list"));
+        assert!(matches!(nodes[2].1, DecodedData::String(ref str) if str == "first"));
+        assert_eq!(strings, ["first", "second", "third"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn weak_references() -> std::result::Result<(), anyhow::Error> {
+        // Plain refs without a callback are shared, so the second reference is
+        // a proxy.
+        let child = spawn_python27(
+            r#"
+            import weakref
+            class Target(object):
+                pass
+            target = Target()
+            entry = (target, weakref.ref(target), weakref.proxy(target))
+            "#,
+        )?;
+        let mem = crate::connect(child.pid)?;
+
+        let options = WalkOptions {
+            follow_weak_references: true,
+            ..Default::default()
+        };
+        let output =
+            walk_with_options::<Cpython2_7, _>(&mem, Pointer::new(child.pointer), &options, None);
+        let items = match output.graph.get(&DataPointer(child.pointer)) {
+            Some(DecodedData::Tuple(items)) => items,
+            node => anyhow::bail!("Expected a tuple, got {:?}", node),
+        };
+        let references: HashSet<_> = output.weak_references[&items[0]].iter().copied().collect();
+        assert_eq!(references, HashSet::from([items[1], items[2]]));
+
+        let output = walk_with_options::<Cpython2_7, _>(
+            &mem,
+            Pointer::new(child.pointer),
+            &WalkOptions::default(),
+            None,
+        );
+        assert!(output.weak_references.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn metatypes() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27(
+            r#"
+            import abc
+            class Abstract(object):
+                __metaclass__ = abc.ABCMeta
+            class Plain(object):
+                pass
+            entry = [Abstract(), Plain]
+            "#,
+        )?;
+        let mem = crate::connect(child.pid)?;
+
+        let output = walk_with_options::<Cpython2_7, _>(
+            &mem,
+            Pointer::new(child.pointer),
+            &WalkOptions::default(),
+            None,
+        );
+        let items = output.graph[&DataPointer(child.pointer)]
+            .as_list()
+            .ok_or_else(|| anyhow::anyhow!("Expected a list"))?;
+        let type_name = |pointer: &DataPointer| match output.graph.get(pointer) {
+            Some(DecodedData::Type(name)) => Some(name.as_str()),
+            _ => None,
+        };
+        let metatype_name = |pointer: &DataPointer| type_name(&output.metatypes[pointer]);
+
+        // `Abstract` is only reached as the type of an instance.
+        let abstract_type = match &output.graph[&items[0]] {
+            DecodedData::Object { object_type, .. } => *object_type,
+            node => anyhow::bail!("Expected an object, got {:?}", node),
+        };
+        assert_eq!(type_name(&abstract_type), Some("Abstract"));
+        assert_eq!(metatype_name(&abstract_type), Some("ABCMeta"));
+
+        // `Plain` is reached as an object itself.
+        assert_eq!(type_name(&items[1]), Some("Plain"));
+        assert_eq!(metatype_name(&items[1]), Some("type"));
+
+        // The metatypes' own metatype is `type`.
+        let abc_meta = output.metatypes[&abstract_type];
+        assert_eq!(metatype_name(&abc_meta), Some("type"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn record_depth() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27("entry = [[12345], 'child']")?;
+        let mem = crate::connect(child.pid)?;
+
+        let options = WalkOptions {
+            record_depth: true,
+            ..Default::default()
+        };
+        let output =
+            walk_with_options::<Cpython2_7, _>(&mem, Pointer::new(child.pointer), &options, None);
+        let root = DataPointer(child.pointer);
+        let items = match output.graph.get(&root) {
+            Some(DecodedData::List(items)) => items,
+            node => anyhow::bail!("Expected a list, got {:?}", node),
+        };
+        let inner = match output.graph.get(&items[0]) {
+            Some(DecodedData::List(inner)) => inner,
+            node => anyhow::bail!("Expected a list, got {:?}", node),
+        };
+
+        assert_eq!(output.depths[&root], 0);
+        assert_eq!(output.depths[&items[0]], 1);
+        assert_eq!(output.depths[&items[1]], 1);
+        assert_eq!(output.depths[&inner[0]], 2);
+        // Every node has a depth, including types reached as an object's type.
+        assert!(output
+            .graph
+            .keys()
+            .all(|node| output.depths.contains_key(node)));
+
+        let output = walk_with_options::<Cpython2_7, _>(
+            &mem,
+            Pointer::new(child.pointer),
+            &WalkOptions::default(),
+            None,
+        );
+        assert!(output.depths.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn traversal_order() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27("entry = ['x', ('a', ('b', 'c')), 'y']")?;
+        let mem = crate::connect(child.pid)?;
+
+        let walk = |order| {
+            let options = WalkOptions {
+                order,
+                ..WalkOptions::default()
+            };
+            let (output, order) =
+                walk_in_order::<Cpython2_7, _>(&mem, Pointer::new(child.pointer), &options, None);
+            let strings: Vec<String> = order
+                .iter()
+                .filter_map(|pointer| output.graph[pointer].as_str().map(str::to_owned))
+                .collect();
+            (output.graph, strings)
+        };
+        let (bfs_graph, bfs) = walk(TraversalOrder::Bfs);
+        let (dfs_graph, dfs) = walk(TraversalOrder::Dfs);
+
+        assert_eq!(bfs, ["x", "y", "a", "b", "c"]);
+        // Each tuple's subtree completes before the walk moves on to `x`.
+        assert_eq!(dfs, ["y", "c", "b", "a", "x"]);
+
+        let mut bfs_nodes: Vec<_> = bfs_graph
+            .iter()
+            .map(|(p, d)| (p.0, format!("{:?}", d)))
+            .collect();
+        let mut dfs_nodes: Vec<_> = dfs_graph
+            .iter()
+            .map(|(p, d)| (p.0, format!("{:?}", d)))
+            .collect();
+        bfs_nodes.sort();
+        dfs_nodes.sort();
+        assert_eq!(bfs_nodes, dfs_nodes);
+
+        Ok(())
+    }
+
+    #[test]
+    fn resolve_paths() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27(
+            r#"
+            class Obj(object):
+                pass
+            entry = Obj()
+            entry.attr = 'value'
+            entry.items = ['a', 'b', 'c']
+            entry.table = {'key': 42}
+            "#,
+        )?;
+        let mem = crate::connect(child.pid)?;
+        let root = Pointer::new(child.pointer);
+        let attr = |name: &str| PathSegment::Attr(name.to_owned());
+        let mut cache = TypeCache::new();
+
+        match resolve_path::<Cpython2_7, _>(&mem, root, &[attr("attr")], &mut cache)? {
+            DecodedData::String(str) => assert_eq!(str, "value"),
+            data => anyhow::bail!("Expected a string, got {:?}", data),
+        }
+        match resolve_path::<Cpython2_7, _>(
+            &mem,
+            root,
+            &[attr("items"), PathSegment::Index(1)],
+            &mut cache,
+        )? {
+            DecodedData::String(str) => assert_eq!(str, "b"),
+            data => anyhow::bail!("Expected a string, got {:?}", data),
+        }
+        match resolve_path::<Cpython2_7, _>(
+            &mem,
+            root,
+            &[attr("table"), PathSegment::Key("key".to_owned())],
+            &mut cache,
+        )? {
+            DecodedData::Int(int) => assert_eq!(int, BigInt::from(42)),
+            data => anyhow::bail!("Expected an int, got {:?}", data),
+        }
+
+        for path in [
+            vec![attr("missing")],
+            vec![attr("items"), PathSegment::Index(3)],
+            vec![attr("attr"), PathSegment::Index(0)],
+        ] {
+            assert!(matches!(
+                resolve_path::<Cpython2_7, _>(&mem, root, &path, &mut cache),
+                Err(Error::Decode)
+            ));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn scalar_fast_path() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27(
+            r#"
+            entry = [
+                {'key': 'value'}, 1, 2 ** 40, 1.5, 'a', u'b', True, None,
+                3, 'c', 2.5, u'd', False, None, -7, 'value',
+            ]
+            "#,
+        )?;
+        let mem = crate::connect(child.pid)?;
+        let root = Pointer::new(child.pointer);
+
+        let graph = walk::<Cpython2_7, _>(&mem, root);
+        let items = match graph.get(&DataPointer(child.pointer)) {
+            Some(DecodedData::List(items)) => items,
+            _ => anyhow::bail!("Expected a list"),
+        };
+        for (index, item) in items.iter().enumerate() {
+            // A path resolves with fresh state, so always takes the slow path.
+            let slow = resolve_path::<Cpython2_7, _>(
+                &mem,
+                root,
+                &[PathSegment::Index(index)],
+                &mut TypeCache::new(),
+            )?;
+            assert_eq!(format!("{:?}", graph[item]), format!("{:?}", slow));
+        }
+
+        // Types first seen through the fast path are still recorded.
+        let type_names: Vec<&str> = graph
+            .values()
+            .filter_map(|data| match data {
+                DecodedData::Type(name) => Some(name.as_str()),
+                _ => None,
+            })
+            .collect();
+        for name in ["str", "unicode", "int", "float", "bool", "NoneType"] {
+            assert!(type_names.contains(&name), "missing type {}", name);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn resolve_set_of_ints() -> std::result::Result<(), anyhow::Error> {
+        // The discarded member leaves a dummy entry in the table.
+        let child = spawn_python27(
+            r#"
+            entry = {1, 2, 3, 2 ** 40, 99}
+            entry.discard(99)
+            "#,
+        )?;
+        let mem = crate::connect(child.pid)?;
+
+        let graph = walk::<Cpython2_7, _>(&mem, Pointer::new(child.pointer));
+        let resolved = resolve_set(&graph, &graph[&DataPointer(child.pointer)]);
+        assert_eq!(resolved.len(), 4);
+        let members: std::collections::HashSet<BigInt> = resolved
+            .into_iter()
+            .filter_map(|member| match member {
+                DecodedData::Int(int) => Some(int.clone()),
+                _ => None,
+            })
+            .collect();
+
+        let expected = [1u64, 2, 3, 1 << 40]
+            .iter()
+            .copied()
+            .map(BigInt::from)
+            .collect();
+        assert_eq!(members, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn type_cache_is_shared() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27("entry = 2 ** 40")?;
+        let mem = CountingMemory::new(crate::connect(child.pid)?);
+        let root = Pointer::new(child.pointer);
+        let mut cache = TypeCache::<Cpython2_7>::new();
+
+        let mut decode = || -> Result<usize> {
+            let before = mem.reads();
+            assert!(matches!(
+                decode_one(&mem, root, &mut cache)?,
+                DecodedData::Int(_)
+            ));
+            Ok(mem.reads() - before)
+        };
+        let uncached = decode()?;
+        let cached = decode()?;
+        // The type object and its name are only read the first time.
+        assert!(cached < uncached, "{} >= {}", cached, uncached);
+
+        Ok(())
+    }
+
+    #[test]
+    fn cached_downcast() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27("entry = [i for i in range(1000, 1050)]")?;
+        let mem = crate::connect(child.pid)?;
+        let list: <Cpython2_7 as Interpreter>::ListObject =
+            Pointer::new(child.pointer).try_deref_me(&mem)?;
+        let items = list.items(&mem).collect::<Result<Vec<_>>>()?;
+
+        let counting = CountingMemory::new(&mem);
+        for &item in &items {
+            assert!(item.downcast(&counting)?.as_int().is_some());
+        }
+        let uncached = counting.reads();
+
+        let counting = CountingMemory::new(&mem);
+        let mut cache = TypeCache::<Cpython2_7>::new();
+        for &item in &items {
+            assert!(cache.downcast(&counting, item)?.as_int().is_some());
+        }
+        let cached = counting.reads();
+
+        // Uncached, every downcast reads the type object and then `tp_name`
+        // byte by byte, up to and including the NUL.
+        let type_reads = 1 + "int".len() + 1;
+        assert_eq!(uncached - cached, (items.len() - 1) * type_reads);
+
+        Ok(())
+    }
+
+    /// Memory whose reads at `torn` fail until the target has been suspended.
+    struct TornMemory<'a> {
+        inner: &'a crate::memory::Process,
+        torn: usize,
+        suspended: std::cell::Cell<bool>,
+    }
+
+    impl Memory for TornMemory<'_> {
+        fn get_vec(&self, address: usize, size: usize) -> Result<Vec<u8>> {
+            if address == self.torn && !self.suspended.get() {
+                return Err(Error::Decode);
+            }
+            self.inner.get_vec(address, size)
+        }
+
+        fn suspend(&self) -> Option<Result<crate::memory::Suspension>> {
+            self.suspended.set(true);
+            Memory::suspend(&self.inner)
+        }
+
+        fn is_suspended(&self) -> Option<bool> {
+            Memory::is_suspended(&self.inner)
+        }
+    }
+
+    #[test]
+    fn retry_suspended_recovers() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27("entry = ['torn']")?;
+        let process = crate::connect(child.pid)?;
+        let root = DataPointer(child.pointer);
+        let item = match walk::<Cpython2_7, _>(&process, Pointer::new(child.pointer)).get(&root) {
+            Some(DecodedData::List(items)) => items[0],
+            node => anyhow::bail!("Expected a list, got {:?}", node),
+        };
+
+        let walk_torn = |retry_suspended: bool| {
+            let mem = TornMemory {
+                inner: &process,
+                torn: item.0,
+                suspended: std::cell::Cell::new(false),
+            };
+            let options = WalkOptions {
+                retry_suspended,
+                ..WalkOptions::default()
+            };
+            walk_with_options::<Cpython2_7, _>(&mem, Pointer::new(child.pointer), &options, None)
+                .graph
+        };
+
+        let graph = walk_torn(false);
+        assert!(matches!(graph.get(&root), Some(DecodedData::Error(_))));
+        assert!(!graph.contains_key(&item));
+
+        let graph = walk_torn(true);
+        assert!(matches!(graph.get(&root), Some(DecodedData::List(_))));
+        assert!(matches!(graph.get(&item), Some(DecodedData::String(str)) if str == "torn"));
+
+        Ok(())
+    }
+
+    /// Memory whose reads of more than an object header at any of `corrupt`
+    /// fail, counting suspensions.
+    struct CorruptMemory<'a> {
+        inner: &'a crate::memory::Process,
+        corrupt: HashSet<usize>,
+        suspensions: std::cell::Cell<usize>,
+    }
+
+    impl Memory for CorruptMemory<'_> {
+        fn get_vec(&self, address: usize, size: usize) -> Result<Vec<u8>> {
+            if self.corrupt.contains(&address) && size > 2 * Pointer::SIZE {
+                return Err(Error::Decode);
+            }
+            self.inner.get_vec(address, size)
+        }
+
+        fn suspend(&self) -> Option<Result<crate::memory::Suspension>> {
+            self.suspensions.set(self.suspensions.get() + 1);
+            Memory::suspend(&self.inner)
+        }
+
+        fn is_suspended(&self) -> Option<bool> {
+            Memory::is_suspended(&self.inner)
+        }
+    }
+
+    #[test]
+    fn retry_suspended_limits() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27("entry = [float(i) for i in range(100)]")?;
+        let process = crate::connect(child.pid)?;
+        let items = match walk::<Cpython2_7, _>(&process, Pointer::new(child.pointer))
+            .remove(&DataPointer(child.pointer))
+        {
+            Some(DecodedData::List(items)) => items,
+            node => anyhow::bail!("Expected a list, got {:?}", node),
+        };
+
+        let suspensions = || {
+            let mem = CorruptMemory {
+                inner: &process,
+                corrupt: items.iter().map(|item| item.0).collect(),
+                suspensions: std::cell::Cell::new(0),
+            };
+            let options = WalkOptions {
+                retry_suspended: true,
+                ..WalkOptions::default()
+            };
+            walk_with_options::<Cpython2_7, _>(&mem, Pointer::new(child.pointer), &options, None);
+            mem.suspensions.get()
+        };
+
+        assert_eq!(suspensions(), MAX_SUSPENDED_RETRIES);
+        // Reads made while the target is paused anyway are not retried.
+        let _suspension = process.suspend()?;
+        assert_eq!(suspensions(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn checkpoint_rollback() {
+        let options = WalkOptions::default();
+        let mut cache = TypeCache::<Cpython2_7>::new();
+        let mut state = State::new(&options, &mut cache);
+        state.suspicious.insert(DataPointer(1));
+
+        let checkpoint = state.checkpoint();
+        state.suspicious.insert(DataPointer(2));
+        state.truncated_values.insert(DataPointer(2), 10);
+        state.found_null = true;
+        state.cache.scalar_types.insert(0x10, Type::Int);
+        state.cache.record(CacheEntry::ScalarType(0x10));
+        state.rollback(checkpoint);
+        assert_eq!(state.suspicious, HashSet::from([DataPointer(1)]));
+        assert!(state.truncated_values.is_empty());
+        assert!(!state.found_null);
+        assert!(state.cache.scalar_types.is_empty());
+
+        let checkpoint = state.checkpoint();
+        state.suspicious.insert(DataPointer(3));
+        state.commit(checkpoint);
+        assert_eq!(
+            state.suspicious,
+            HashSet::from([DataPointer(1), DataPointer(3)])
+        );
+    }
+
+    #[test]
+    fn inspect_str_type() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27("entry = str")?;
+        let mem = crate::connect(child.pid)?;
+
+        let layout = inspect_type::<Cpython2_7, _>(&mem, Pointer::new(child.pointer))?;
+        assert_eq!(layout.name, "str");
+        assert_eq!(layout.tp_itemsize, 1);
+        assert!(!layout.is_heap_type);
+        assert!(!layout.name_truncated);
+        assert!(format!("{:?}", layout).contains("tp_itemsize: 1"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn truncated_type_name() -> std::result::Result<(), anyhow::Error> {
+        let child = spawn_python27("entry = type('T' * 1500, (object,), {})")?;
+        let mem = crate::connect(child.pid)?;
+
+        let layout = inspect_type::<Cpython2_7, _>(&mem, Pointer::new(child.pointer))?;
+        assert!(layout.name_truncated);
+        assert_eq!(layout.name, "T".repeat(Cpython2_7::MAX_TYPE_NAME_LENGTH));
+
+        let exact = format!(
+            "entry = type('T' * {}, (object,), {{}})",
+            Cpython2_7::MAX_TYPE_NAME_LENGTH
+        );
+        let child = spawn_python27(&exact)?;
+        let mem = crate::connect(child.pid)?;
+        let layout = inspect_type::<Cpython2_7, _>(&mem, Pointer::new(child.pointer))?;
+        assert!(!layout.name_truncated);
+        assert_eq!(layout.name.len(), Cpython2_7::MAX_TYPE_NAME_LENGTH);
+
+        Ok(())
+    }
 }